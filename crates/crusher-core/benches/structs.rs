@@ -0,0 +1,79 @@
+//! `cargo bench -p crusher-core`: [`modify_structs`] on a generated file the
+//! size of the larger ones of rustc, for the empty forms and for halving and
+//! duplicating, then the whole `structs` operator with `--fix-uses` on and
+//! off, and the variants made of its mutations.
+
+use std::{fmt::Write, time::Instant};
+
+use crusher_core::{
+    higher_order,
+    operators::{find_all_structs, modify_structs, Crush, KeepGenerics, Mutator, StructCrusher},
+};
+
+/// Structs of the generated file, every one constructed and read once.
+const STRUCTS: usize = 2000;
+
+fn source() -> String {
+    let mut source_code = String::new();
+    for i in 0..STRUCTS {
+        writeln!(
+            source_code,
+            "struct S{i} {{ a{i}: u8, b: u32 }}\n\
+             fn f{i}() -> u32 {{ let s = S{i} {{ a{i}: 1, b: 2 }}; s.a{i} as u32 + s.b }}"
+        )
+        .unwrap();
+    }
+    source_code
+}
+
+fn main() {
+    let source_code = source();
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_rust::language()).unwrap();
+    let tree = parser.parse(&source_code, None).unwrap();
+    println!("{} bytes, {} structs", source_code.len(), STRUCTS);
+
+    let started = Instant::now();
+    let structs = find_all_structs(&source_code, &tree);
+    println!(
+        "find_all_structs: {} structs in {:?}",
+        structs.len(),
+        started.elapsed()
+    );
+    let forms = [Crush::Unit, Crush::Tuple, Crush::Braced];
+    let resizes = [Crush::Halved, Crush::Duplicated];
+    for crushes in [&[][..], &forms, &resizes] {
+        let started = Instant::now();
+        let mutations = modify_structs(&source_code, &tree, &structs, crushes, KeepGenerics::Both);
+        println!(
+            "modify_structs {:?}: {} mutations in {:?}",
+            crushes,
+            mutations.len(),
+            started.elapsed()
+        );
+    }
+
+    for fix_uses in [false, true] {
+        let crusher = StructCrusher {
+            fix_uses,
+            ..StructCrusher::default()
+        };
+        let started = Instant::now();
+        let mutations = crusher.mutations(&source_code, &tree);
+        let found = started.elapsed();
+        let started = Instant::now();
+        let bytes: usize = mutations
+            .iter()
+            .map(|m| higher_order::apply_all(&source_code, &[m]).len())
+            .sum();
+        println!(
+            "fix_uses {}: {} mutations in {:?}, replacements of {} bytes, {} bytes of variants in {:?}",
+            fix_uses,
+            mutations.len(),
+            found,
+            mutations.iter().map(|m| m.replacement.len()).sum::<usize>(),
+            bytes,
+            started.elapsed()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! A parser kept across files, so that running operators over a corpus does
+//! not set up tree-sitter again for every file and operator.
+
+use std::{borrow::Cow, time::Instant};
+
+use tree_sitter::{Parser, Tree};
+
+use crate::{
+    operators::{Mutation, MutationSite, Mutator},
+    targets,
+};
+
+pub struct Crusher {
+    parser: Parser,
+}
+
+impl Default for Crusher {
+    fn default() -> Crusher {
+        Crusher::new()
+    }
+}
+
+impl Crusher {
+    pub fn new() -> Crusher {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        Crusher { parser }
+    }
+
+    /// Parse `source_code`, giving up with `None` once `deadline` has passed.
+    pub fn parse(&mut self, source_code: &str, deadline: Option<Instant>) -> Option<Tree> {
+        let micros = deadline.map_or(0, |deadline| {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            (remaining.as_micros() as u64).max(1)
+        });
+        // zero means no limit
+        self.parser.set_timeout_micros(micros);
+        let tree = self.parser.parse(source_code, None);
+        if tree.is_none() {
+            // a parse cut short would otherwise resume on the next call
+            self.parser.reset();
+        }
+        tree
+    }
+
+    /// Parse `source_code` and collect the mutations `mutator` proposes for
+    /// it, but the no-ops, giving up once `deadline` has passed.
+    pub fn mutations_until(
+        &mut self,
+        mutator: &dyn Mutator,
+        source_code: &str,
+        deadline: Option<Instant>,
+    ) -> Option<Vec<Mutation>> {
+        let tree = self.parse(source_code, deadline)?;
+        let mut found = mutator.mutations(source_code, &tree);
+        targets::retain_admitted(&tree, source_code, &targets::Everything, &mut found);
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return None;
+        }
+        Some(found)
+    }
+
+    /// The mutants `mutator` makes of `source_code`, but the no-ops, each
+    /// building its text only when asked for it, so that callers can pick by
+    /// site or kind first.
+    pub fn mutants<'s>(
+        &mut self,
+        mutator: &dyn Mutator,
+        source_code: &'s str,
+    ) -> impl Iterator<Item = Mutant<'s>> {
+        self.mutations_until(mutator, source_code, None)
+            .unwrap()
+            .into_iter()
+            .map(move |mutation| Mutant {
+                source_code,
+                mutation,
+            })
+    }
+
+    /// Whether tree-sitter finds ERROR or MISSING nodes in `source_code`.
+    pub fn has_syntax_errors(&mut self, source_code: &str) -> bool {
+        self.parse(source_code, None)
+            .unwrap()
+            .root_node()
+            .has_error()
+    }
+}
+
+/// One mutation of a source, with the source it applies to.
+#[derive(Debug, Clone)]
+pub struct Mutant<'s> {
+    pub source_code: &'s str,
+    pub mutation: Mutation,
+}
+
+impl<'s> Mutant<'s> {
+    pub fn site(&self) -> MutationSite {
+        self.mutation.site(self.source_code)
+    }
+
+    /// The text the mutation replaces.
+    pub fn original(&self) -> &'s str {
+        self.mutation.original(self.source_code)
+    }
+
+    /// The mutated source as the text before the mutation, the replacement
+    /// and the text after it, to write out without joining them.
+    pub fn parts(&self) -> [&str; 3] {
+        [
+            &self.source_code[..self.mutation.start],
+            &self.mutation.replacement,
+            &self.source_code[self.mutation.end..],
+        ]
+    }
+
+    /// The mutated source, only copied when it differs from the original.
+    pub fn text(&self) -> Cow<'s, str> {
+        match self.mutation.is_noop(self.source_code) {
+            true => Cow::Borrowed(self.source_code),
+            false => Cow::Owned(self.mutation.apply(self.source_code)),
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Nightly feature gates to enable in mutants, since many compiler paths are
+//! only reached behind one.
+
+use crate::rng::{self, Rng};
+
+/// Language feature gates of recent nightlies.
+pub const NIGHTLY_FEATURES: [&str; 40] = [
+    "adt_const_params",
+    "arbitrary_self_types",
+    "associated_const_equality",
+    "associated_type_defaults",
+    "async_closure",
+    "async_fn_in_dyn_trait",
+    "auto_traits",
+    "box_patterns",
+    "const_trait_impl",
+    "coroutines",
+    "deref_patterns",
+    "dyn_star",
+    "effects",
+    "explicit_tail_calls",
+    "extern_types",
+    "fn_traits",
+    "generic_arg_infer",
+    "generic_associated_types_extended",
+    "generic_const_exprs",
+    "generic_const_items",
+    "impl_trait_in_assoc_type",
+    "impl_trait_in_bindings",
+    "inherent_associated_types",
+    "lang_items",
+    "lazy_type_alias",
+    "min_specialization",
+    "negative_impls",
+    "never_type",
+    "non_lifetime_binders",
+    "return_type_notation",
+    "rustc_attrs",
+    "specialization",
+    "trait_alias",
+    "trait_upcasting",
+    "transmutability",
+    "type_alias_impl_trait",
+    "unboxed_closures",
+    "unsized_fn_params",
+    "unsized_locals",
+    "with_negative_coherence",
+];
+
+/// `count` different features of [`NIGHTLY_FEATURES`], in list order.
+pub fn sample(rng: &mut Rng, count: usize) -> Vec<&'static str> {
+    rng::sample_indices(rng, NIGHTLY_FEATURES.len(), count)
+        .into_iter()
+        .map(|i| NIGHTLY_FEATURES[i])
+        .collect()
+}
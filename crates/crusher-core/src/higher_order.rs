@@ -0,0 +1,103 @@
+//! Higher-order mutants: several independent mutations applied to one file.
+
+use std::{collections::HashSet, ops::Range};
+
+use crate::{operators::Mutation, rng::Rng};
+
+/// Two mutations can be combined when they touch disjoint text; insertions
+/// at the same point conflict since their relative order would be arbitrary.
+pub fn independent(a: &Mutation, b: &Mutation) -> bool {
+    a.start != b.start && (a.end <= b.start || b.end <= a.start)
+}
+
+/// Apply pairwise independent mutations at once.
+pub fn apply_all(source_code: &str, mutations: &[&Mutation]) -> String {
+    apply_within(source_code, mutations, 0..source_code.len())
+}
+
+/// The text `within` of `source_code` spans once the non-overlapping
+/// `mutations` inside it are applied, without copying the rest of the file.
+pub fn apply_within(source_code: &str, mutations: &[&Mutation], within: Range<usize>) -> String {
+    let mut sorted = mutations.to_vec();
+    sorted.sort_by_key(|m| m.start);
+    let mut out = String::with_capacity(within.len());
+    let mut at = within.start;
+    for m in sorted {
+        out.push_str(&source_code[at..m.start]);
+        out.push_str(&m.replacement);
+        at = m.end;
+    }
+    out.push_str(&source_code[at..within.end]);
+    out
+}
+
+/// Sets of `order` pairwise independent mutations, as sorted indices into
+/// `mutations`: every such set when there are at most `limit`, otherwise
+/// `limit` distinct sets drawn with `rng`.
+pub fn combinations(
+    mutations: &[Mutation],
+    order: usize,
+    limit: usize,
+    rng: &mut Rng,
+) -> Vec<Vec<usize>> {
+    let mut all = Vec::new();
+    if exhaustive(mutations, order, limit + 1, &mut Vec::new(), 0, &mut all) {
+        return all;
+    }
+
+    let mut picked: Vec<Vec<usize>> = Vec::new();
+    let mut seen: HashSet<Vec<usize>> = HashSet::new();
+    // draws with a conflict are thrown away, so bound the number of tries
+    let mut attempts = limit * 16;
+    while picked.len() < limit && attempts > 0 {
+        attempts -= 1;
+        let mut set: Vec<usize> = Vec::with_capacity(order);
+        while set.len() < order {
+            let i = rng.below(mutations.len());
+            // a mutation is never independent of itself, so no duplicates
+            if set
+                .iter()
+                .all(|&j| independent(&mutations[i], &mutations[j]))
+            {
+                set.push(i);
+            } else {
+                break;
+            }
+        }
+        set.sort_unstable();
+        if set.len() == order && seen.insert(set.clone()) {
+            picked.push(set);
+        }
+    }
+    picked
+}
+
+/// Depth-first enumeration; returns false as soon as more than `cap - 1`
+/// sets exist.
+fn exhaustive(
+    mutations: &[Mutation],
+    order: usize,
+    cap: usize,
+    current: &mut Vec<usize>,
+    from: usize,
+    acc: &mut Vec<Vec<usize>>,
+) -> bool {
+    if current.len() == order {
+        acc.push(current.clone());
+        return acc.len() < cap;
+    }
+    for i in from..mutations.len() {
+        if current
+            .iter()
+            .all(|&j| independent(&mutations[i], &mutations[j]))
+        {
+            current.push(i);
+            let more = exhaustive(mutations, order, cap, current, i + 1, acc);
+            current.pop();
+            if !more {
+                return false;
+            }
+        }
+    }
+    true
+}
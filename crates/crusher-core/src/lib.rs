@@ -0,0 +1,13 @@
+//! The mutation engine of the crushers: operators, their templates and the
+//! splice-based edits they produce.
+//!
+//! Everything `pub` here is the supported API and follows semver; downstream
+//! tools can depend on this crate alone, without the command line front end.
+
+pub mod crusher;
+pub mod features;
+pub mod higher_order;
+pub mod operators;
+pub mod reduce;
+pub mod rng;
+pub mod targets;
@@ -0,0 +1,146 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::TYPE, for_each_node, node_text, single_type_argument, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+
+/// Lengths every array type gets instead of its own: empty, the largest
+/// `usize`, one that overflows it, a negative one and one no constant
+/// evaluation can compute.
+const LENGTHS: [&str; 5] = [
+    "0",
+    "usize::MAX",
+    "1 << 64",
+    "-1",
+    "std::process::id() as usize",
+];
+
+const LENGTH: Template = Template::new("type _T = [u8; @@];", "$length");
+const SLICE: Template = Template::new(TYPE, "[$element]");
+const ARRAY: Template = Template::new(TYPE, "[$element; 0]");
+const VEC: Template = Template::new(TYPE, "Vec<$element>");
+static TEMPLATES: [Template; 4] = [LENGTH, SLICE, ARRAY, VEC];
+
+/// Mutates array and slice types: swaps the length of arrays for odd ones,
+/// turns `[T; N]` into `[T]` and `Vec<T>`, `[T]` into `[T; 0]` and
+/// `Vec<T>`, and `Vec<T>` into `[T]`.
+pub struct ArrayCrusher;
+
+impl Mutator for ArrayCrusher {
+    fn name(&self) -> &'static str {
+        "arrays"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Replaces array lengths with 0, huge, negative and non-const ones, removes them, and swaps arrays, slices and `Vec`s for each other.",
+            node_kinds: &["array_type", "generic_type"],
+            example: "struct S { a: [u8; 4] }\nfn f(s: &[u8]) -> Vec<u8> { s.to_vec() }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "array_type" => crush_array(source_code, node, &mut acc),
+            "generic_type" => crush_vec(source_code, node, &mut acc),
+            _ => {}
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_array(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(element) = node.child_by_field_name("element") else {
+        return;
+    };
+    let element = node_text(element, source_code);
+    let Some(length) = node.child_by_field_name("length") else {
+        for template in [ARRAY, VEC] {
+            acc.push(Mutation::new(
+                node,
+                template.instantiate(&[("element", element)]),
+            ));
+        }
+        return;
+    };
+    let own = node_text(length, source_code);
+    for replacement in LENGTHS.into_iter().filter(|&l| l != own) {
+        acc.push(Mutation {
+            start: length.start_byte(),
+            end: length.end_byte(),
+            replacement: LENGTH.instantiate(&[("length", replacement)]),
+            kind: node.kind(),
+        });
+    }
+    for template in [SLICE, VEC] {
+        acc.push(Mutation::new(
+            node,
+            template.instantiate(&[("element", element)]),
+        ));
+    }
+}
+
+/// `Vec<T>`, by any path ending in `Vec`, with a single type argument.
+fn crush_vec(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(element) = single_type_argument(source_code, node, "Vec") else {
+        return;
+    };
+    let element = node_text(element, source_code);
+    acc.push(Mutation::new(
+        node,
+        SLICE.instantiate(&[("element", element)]),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ArrayCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn lengths_but_their_own() {
+        assert_eq!(
+            crushed("type A = [u8; 0];"),
+            [
+                "type A = [u8; usize::MAX];",
+                "type A = [u8; 1 << 64];",
+                "type A = [u8; -1];",
+                "type A = [u8; std::process::id() as usize];",
+                "type A = [u8];",
+                "type A = Vec<u8>;",
+            ]
+        );
+        // array expressions have no type
+        assert!(crushed("fn f() { [0u8; 4]; }").is_empty());
+    }
+
+    #[test]
+    fn slices_and_vecs() {
+        assert_eq!(
+            crushed("fn f(s: &[u8]) -> alloc::vec::Vec</* c */ u8> { Vec::<u8>::new() }"),
+            [
+                "fn f(s: &[u8; 0]) -> alloc::vec::Vec</* c */ u8> { Vec::<u8>::new() }",
+                "fn f(s: &Vec<u8>) -> alloc::vec::Vec</* c */ u8> { Vec::<u8>::new() }",
+                "fn f(s: &[u8]) -> [u8] { Vec::<u8>::new() }",
+            ]
+        );
+        assert!(crushed("type V = Vec<u8, A>;").is_empty());
+    }
+}
@@ -0,0 +1,143 @@
+use tree_sitter::{Node, Tree};
+
+use super::{for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const PREDICATE: &str = "#[cfg(@@)]\nfn _f() {}";
+const DELETE: Template = Template::new(super::context::ITEM, "");
+const NEGATE: Template = Template::new(PREDICATE, "not($predicate)");
+const ALL: Template = Template::new(PREDICATE, "all()");
+const ANY: Template = Template::new(PREDICATE, "any()");
+const MALFORMED: [Template; 3] = [
+    Template::malformed(PREDICATE, "not("),
+    Template::malformed(PREDICATE, "= \"crushed\""),
+    Template::malformed(PREDICATE, "all(,,)"),
+];
+static TEMPLATES: [Template; 7] = [
+    DELETE,
+    NEGATE,
+    ALL,
+    ANY,
+    MALFORMED[0],
+    MALFORMED[1],
+    MALFORMED[2],
+];
+
+/// Mutates conditional compilation: deletes `#[cfg(..)]` and
+/// `#[cfg_attr(..)]` attributes, negates their predicate or replaces it by
+/// `all()`, `any()` and malformed tokens.
+pub struct CfgCrusher;
+
+impl Mutator for CfgCrusher {
+    fn name(&self) -> &'static str {
+        "cfg"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Deletes `#[cfg]`/`#[cfg_attr]` attributes, negates their predicate and replaces it by `all()`, `any()` or malformed tokens.",
+            node_kinds: &["attribute_item", "inner_attribute_item", "attribute"],
+            example: "#[cfg_attr(test, derive(Debug))]\nstruct S;\n#[cfg(unix)]\nfn f() {}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if matches!(node.kind(), "attribute_item" | "inner_attribute_item") {
+                crush_cfg(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_cfg(source_code: &str, item: Node, acc: &mut Vec<Mutation>) {
+    let Some(attribute) = item.named_child(0).filter(|a| a.kind() == "attribute") else {
+        return;
+    };
+    let name = attribute
+        .named_child(0)
+        .map(|n| node_text(n, source_code))
+        .unwrap_or_default();
+    if name != "cfg" && name != "cfg_attr" {
+        return;
+    }
+    let Some((start, end)) = attribute
+        .child_by_field_name("arguments")
+        .and_then(predicate_span)
+    else {
+        return;
+    };
+    let predicate = &source_code[start..end];
+
+    acc.push(Mutation::new(item, DELETE.instantiate(&[])));
+    for template in [NEGATE, ALL, ANY].iter().chain(MALFORMED.iter()) {
+        acc.push(Mutation {
+            start,
+            end,
+            replacement: template.instantiate(&[("predicate", predicate)]),
+            kind: "token_tree",
+        });
+    }
+}
+
+/// Byte range of the predicate inside `(...)`: everything for `cfg`, up to
+/// the first top-level comma for `cfg_attr`. An empty `cfg()` has an empty
+/// predicate right after its `(`.
+fn predicate_span(arguments: Node) -> Option<(usize, usize)> {
+    let mut cursor = arguments.walk();
+    let tokens: Vec<Node> = arguments.children(&mut cursor).collect();
+    // strip the parentheses
+    let inner = tokens.get(1..tokens.len().checked_sub(1)?)?;
+    let Some(first) = inner.first() else {
+        let open = tokens.first()?.end_byte();
+        return Some((open, open));
+    };
+    let last = inner.iter().take_while(|t| t.kind() != ",").last()?;
+    Some((first.start_byte(), last.end_byte()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        CfgCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(
+            crushed("#[cfg_attr(all(unix, test), derive(Debug))] struct S;"),
+            [
+                " struct S;",
+                "#[cfg_attr(not(all(unix, test)), derive(Debug))] struct S;",
+                "#[cfg_attr(all(), derive(Debug))] struct S;",
+                "#[cfg_attr(any(), derive(Debug))] struct S;",
+                "#[cfg_attr(not(, derive(Debug))] struct S;",
+                "#[cfg_attr(= \"crushed\", derive(Debug))] struct S;",
+                "#[cfg_attr(all(,,), derive(Debug))] struct S;",
+            ]
+        );
+        // a trailing comma stays behind the predicate
+        assert_eq!(crushed("#![cfg(unix,)]")[1], "#![cfg(not(unix),)]");
+        assert_eq!(crushed("#[cfg()] fn f() {}")[2], "#[cfg(all())] fn f() {}");
+    }
+
+    #[test]
+    fn other_attributes_stay() {
+        assert!(crushed("#[my::cfg(x)] #[derive(Debug)] #[cfg] struct S;").is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::ITEM, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+/// Where the implementing type of an impl goes.
+const SELF_TYPE: &str = "impl<T> Tr for @@ {}";
+
+const DUPLICATE: Template = Template::new(ITEM, "\n\n$item");
+const PRIMITIVE: Template = Template::new(SELF_TYPE, "i32");
+const FOREIGN: Template = Template::new(SELF_TYPE, "Vec<$param>");
+const BLANKET: Template = Template::new(SELF_TYPE, "$param");
+const BARE: Template = Template::new(SELF_TYPE, "$name");
+const NO_PARAMS: Template = Template::new("impl@@ Tr for S<T> {}", "");
+static TEMPLATES: [Template; 6] = [DUPLICATE, PRIMITIVE, FOREIGN, BLANKET, BARE, NO_PARAMS];
+
+/// Provokes orphan and overlap errors: repeats impl blocks, implements them
+/// for foreign types or every type instead, and takes the generic
+/// parameters out of their headers.
+pub struct CoherenceCrusher;
+
+impl Mutator for CoherenceCrusher {
+    fn name(&self) -> &'static str {
+        "coherence"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Duplicates impl blocks, swaps the implementing type for `i32`, `Vec<T>`, or the type parameter `T` itself, and removes the generic parameters of impls and the arguments of their implementing type.",
+            node_kinds: &["impl_item"],
+            example: "struct S<T>(T);\nimpl<T> Clone for S<T> { fn clone(&self) -> Self { todo!() } }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.kind() == "impl_item" {
+                crush_impl(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_impl(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let text = node_text(node, source_code);
+    acc.push(Mutation {
+        start: node.end_byte(),
+        end: node.end_byte(),
+        replacement: DUPLICATE.instantiate(&[("item", text)]),
+        kind: node.kind(),
+    });
+
+    let Some(ty) = node.child_by_field_name("type") else {
+        return;
+    };
+    let parameters = node.child_by_field_name("type_parameters");
+    // the first type parameter, lifetimes and consts are no type
+    let param = parameters.and_then(|parameters| {
+        let mut cursor = parameters.walk();
+        let param = parameters
+            .named_children(&mut cursor)
+            .find_map(|p| match p.kind() {
+                "type_identifier" => Some(p),
+                "constrained_type_parameter" | "optional_type_parameter" => p
+                    .child_by_field_name("left")
+                    .or_else(|| p.child_by_field_name("name")),
+                _ => None,
+            })
+            .map(|p| node_text(p, source_code));
+        param
+    });
+
+    let own = node_text(ty, source_code);
+    let mut swaps = vec![PRIMITIVE.instantiate(&[])];
+    swaps.push(FOREIGN.instantiate(&[("param", param.unwrap_or("u8"))]));
+    if let Some(param) = param {
+        swaps.push(BLANKET.instantiate(&[("param", param)]));
+    }
+    if ty.kind() == "generic_type" {
+        if let Some(name) = ty.child_by_field_name("type") {
+            swaps.push(BARE.instantiate(&[("name", node_text(name, source_code))]));
+        }
+    }
+    for swap in swaps.into_iter().filter(|swap| swap != own) {
+        acc.push(Mutation::new(ty, swap));
+    }
+
+    if let Some(parameters) = parameters {
+        acc.push(Mutation::new(parameters, NO_PARAMS.instantiate(&[])));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        CoherenceCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn incoherent_impls() {
+        assert_eq!(
+            crushed("impl<T: Copy> Tr for S<T> {}\n"),
+            [
+                "impl<T: Copy> Tr for S<T> {}\n\nimpl<T: Copy> Tr for S<T> {}\n",
+                "impl<T: Copy> Tr for i32 {}\n",
+                "impl<T: Copy> Tr for Vec<T> {}\n",
+                "impl<T: Copy> Tr for T {}\n",
+                "impl<T: Copy> Tr for S {}\n",
+                "impl Tr for S<T> {}\n",
+            ]
+        );
+        // nothing swaps in for what is already there
+        assert_eq!(
+            crushed("impl Tr for i32 {}\n"),
+            [
+                "impl Tr for i32 {}\n\nimpl Tr for i32 {}\n",
+                "impl Tr for Vec<u8> {}\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn first_type_parameter() {
+        // lifetimes and consts are no type to implement for
+        assert_eq!(
+            crushed("impl<'a, T: 'a, const N: usize> Tr for S<'a, T, N> {}\n")[2..5],
+            [
+                "impl<'a, T: 'a, const N: usize> Tr for Vec<T> {}\n",
+                "impl<'a, T: 'a, const N: usize> Tr for T {}\n",
+                "impl<'a, T: 'a, const N: usize> Tr for S {}\n",
+            ]
+        );
+        assert_eq!(
+            crushed("impl<const N: usize> Tr for [u8; N] {}\n")[1..],
+            [
+                "impl<const N: usize> Tr for i32 {}\n",
+                "impl<const N: usize> Tr for Vec<u8> {}\n",
+                "impl Tr for [u8; N] {}\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn blanket_and_inherent_impls() {
+        // a blanket impl is not swapped for itself
+        assert_eq!(
+            crushed("impl<T> Tr for T where T: Copy {}\n")[1..],
+            [
+                "impl<T> Tr for i32 where T: Copy {}\n",
+                "impl<T> Tr for Vec<T> where T: Copy {}\n",
+                "impl Tr for T where T: Copy {}\n",
+            ]
+        );
+        assert_eq!(
+            crushed("impl<T> S<T> {}\n").last().unwrap(),
+            "impl S<T> {}\n"
+        );
+        assert!(crushed("struct S<T>(T);\ntrait Tr {}\nfn f() {}\n").is_empty());
+    }
+}
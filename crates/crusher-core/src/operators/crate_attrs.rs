@@ -0,0 +1,165 @@
+use tree_sitter::Tree;
+
+use super::{context::ITEM, Mutation, Mutator, OperatorDoc, Template};
+use crate::rng::Rng;
+
+const DELETE: Template = Template::new(ITEM, "");
+const NO_STD: Template = Template::new(ITEM, "#![no_std]\n");
+const FEATURE: Template = Template::new(ITEM, "#![feature($feature)]\n");
+const RECURSION_LIMIT: Template = Template::new(ITEM, "#![recursion_limit = \"$limit\"]\n");
+static TEMPLATES: [Template; 4] = [DELETE, NO_STD, FEATURE, RECURSION_LIMIT];
+
+/// Nightly features that change how rustc treats a crate a lot.
+const FEATURES: [&str; 12] = [
+    "generic_const_exprs",
+    "specialization",
+    "min_specialization",
+    "type_alias_impl_trait",
+    "impl_trait_in_assoc_type",
+    "adt_const_params",
+    "unboxed_closures",
+    "fn_traits",
+    "lang_items",
+    "rustc_attrs",
+    "never_type",
+    "non_lifetime_binders",
+];
+/// Features sampled per file, plus one made-up name.
+const SAMPLED_FEATURES: usize = 3;
+const RECURSION_LIMITS: [&str; 4] = ["0", "1", "4294967295", "18446744073709551616"];
+
+/// Mutates crate-level attributes: inserts `#![no_std]`, `#![feature(..)]`
+/// and extreme `#![recursion_limit]`s at the top of the file and deletes
+/// the inner attributes already there.
+pub struct CrateAttrCrusher;
+
+impl Mutator for CrateAttrCrusher {
+    fn name(&self) -> &'static str {
+        "crate-attrs"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Inserts `#![no_std]`, `#![feature(..)]` and extreme `#![recursion_limit]`s at the top of the file, and deletes existing inner attributes.",
+            node_kinds: &["source_file", "shebang", "inner_attribute_item"],
+            example: "#![allow(dead_code)]\nfn f() {}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let children: Vec<_> = root.children(&mut cursor).collect();
+        let insert = |attribute: String| insert_inner_attribute(source_code, tree, attribute);
+
+        let mut acc = vec![insert(NO_STD.instantiate(&[]))];
+        for feature in sample_features(source_code) {
+            acc.push(insert(FEATURE.instantiate(&[("feature", &feature)])));
+        }
+        for limit in RECURSION_LIMITS {
+            acc.push(insert(RECURSION_LIMIT.instantiate(&[("limit", limit)])));
+        }
+        for child in children {
+            if child.kind() == "inner_attribute_item" {
+                acc.push(Mutation::new(child, DELETE.instantiate(&[])));
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// Where a new inner attribute goes: first, but after a `#!` interpreter line.
+pub fn inner_attribute_offset(source_code: &str, tree: &Tree) -> usize {
+    match tree.root_node().child(0) {
+        Some(first) if first.kind() == "shebang" => source_code[first.end_byte()..]
+            .find('\n')
+            .map_or(source_code.len(), |i| first.end_byte() + i + 1),
+        _ => 0,
+    }
+}
+
+/// Inserts `attribute` (a whole line) at [`inner_attribute_offset`],
+/// starting a new line first when a `#!` line ends the file unterminated.
+pub fn insert_inner_attribute(source_code: &str, tree: &Tree, attribute: String) -> Mutation {
+    let at = inner_attribute_offset(source_code, tree);
+    let unterminated = at == source_code.len() && at > 0 && !source_code.ends_with('\n');
+    Mutation {
+        start: at,
+        end: at,
+        replacement: if unterminated {
+            format!("\n{attribute}")
+        } else {
+            attribute
+        },
+        kind: "source_file",
+    }
+}
+
+/// A few features picked by a generator seeded from the file itself, so a
+/// run is reproducible while different files get different features.
+fn sample_features(source_code: &str) -> Vec<String> {
+    let mut rng = Rng::from_text(source_code);
+    let mut pool: Vec<&str> = FEATURES.to_vec();
+    let mut picked = Vec::new();
+    for _ in 0..SAMPLED_FEATURES {
+        let i = rng.below(pool.len());
+        picked.push(pool.swap_remove(i).to_string());
+    }
+    picked.push(format!("crusher_unknown_{:x}", rng.next_u64() & 0xffff));
+    picked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        CrateAttrCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn inserted_after_shebang() {
+        let mutants = crushed("#!/usr/bin/env run\nfn f() {}\n");
+        assert_eq!(mutants[0], "#!/usr/bin/env run\n#![no_std]\nfn f() {}\n");
+        // the file's last line
+        let mutants = crushed("#!/usr/bin/env run");
+        assert_eq!(mutants[0], "#!/usr/bin/env run\n#![no_std]\n");
+        // an inner attribute is no shebang
+        let mutants = crushed("#![allow(x)]\n//! doc\nfn f() {}\n");
+        assert_eq!(mutants[0], "#![no_std]\n#![allow(x)]\n//! doc\nfn f() {}\n");
+        assert_eq!(mutants.last().unwrap(), "\n//! doc\nfn f() {}\n");
+    }
+
+    #[test]
+    fn features_follow_the_file() {
+        let features = |source_code| {
+            let picked = sample_features(source_code);
+            assert_eq!(picked.len(), SAMPLED_FEATURES + 1);
+            for feature in &picked[..SAMPLED_FEATURES] {
+                assert!(FEATURES.contains(&feature.as_str()));
+                assert_eq!(picked.iter().filter(|f| *f == feature).count(), 1);
+            }
+            assert!(picked[SAMPLED_FEATURES].starts_with("crusher_unknown_"));
+            picked
+        };
+        assert_eq!(features("fn f() {}"), features("fn f() {}"));
+        // nested inner attributes stay
+        let mutants = crushed("mod m { #![allow(x)] }");
+        assert_eq!(
+            mutants.len(),
+            1 + SAMPLED_FEATURES + 1 + RECURSION_LIMITS.len()
+        );
+    }
+}
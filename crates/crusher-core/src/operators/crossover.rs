@@ -0,0 +1,162 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::ITEM, Mutation, Mutator, OperatorDoc, Template};
+use crate::rng::{self, Rng};
+
+/// Top-level items taken from the corpus.
+const HARVESTED: [&str; 6] = [
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "impl_item",
+    "function_item",
+    "trait_item",
+];
+/// Longer items are left out, they would bury the file they go into.
+pub const MAX_SNIPPET_BYTES: usize = 4096;
+/// Snippets spliced into every file.
+const SPLICES: usize = 8;
+
+const SPLICE: Template = Template::new(ITEM, "\n\n$item\n");
+static TEMPLATES: [Template; 1] = [SPLICE];
+
+/// Splices items of other files, structs, enums, impls, functions and
+/// traits, into every file between its top-level items.
+#[derive(Default)]
+pub struct CrossoverCrusher {
+    /// Items of the corpus, from [`harvest`].
+    pub snippets: Vec<String>,
+    /// Picks the snippets and places of every file.
+    pub seed: u64,
+}
+
+impl Mutator for CrossoverCrusher {
+    fn name(&self) -> &'static str {
+        "crossover"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Splices structs, enums, impls, functions and traits of the other input files in between the top-level items of every file.",
+            node_kinds: &["source_file"],
+            example: "struct S;\nfn f() {}\n",
+            flags: &[("--input-dir", "the files harvested for items")],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let places: Vec<usize> = root
+            .named_children(&mut cursor)
+            .filter(|child| !is_attribute_or_comment(*child))
+            .map(|child| child.end_byte())
+            .collect();
+        let places = match places.is_empty() {
+            true => vec![source_code.len()],
+            false => places,
+        };
+        // items of the file itself are no crossover, and items of several
+        // files go in once
+        let mut foreign: Vec<&String> = Vec::new();
+        for snippet in &self.snippets {
+            if !source_code.contains(snippet.as_str()) && !foreign.contains(&snippet) {
+                foreign.push(snippet);
+            }
+        }
+        let mut rng = Rng::derive(self.seed, source_code);
+        rng::sample_indices(&mut rng, foreign.len(), SPLICES)
+            .into_iter()
+            .map(|i| {
+                let at = places[rng.below(places.len())];
+                Mutation {
+                    start: at,
+                    end: at,
+                    replacement: SPLICE.instantiate(&[("item", foreign[i])]),
+                    kind: "source_file",
+                }
+            })
+            .collect()
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn is_attribute_or_comment(node: Node) -> bool {
+    matches!(
+        node.kind(),
+        "attribute_item" | "inner_attribute_item" | "line_comment" | "block_comment"
+    )
+}
+
+/// The top-level items of a file [`CrossoverCrusher`] splices elsewhere,
+/// each with the outer attributes and doc comments before it.
+pub fn harvest(source_code: &str, tree: &Tree) -> Vec<String> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let children: Vec<Node> = root.named_children(&mut cursor).collect();
+    let mut snippets = Vec::new();
+    for (i, child) in children.iter().enumerate() {
+        if !HARVESTED.contains(&child.kind()) || child.has_error() {
+            continue;
+        }
+        let first = children[..i]
+            .iter()
+            .rev()
+            .take_while(|c| {
+                c.kind() == "attribute_item" || c.child_by_field_name("outer").is_some()
+            })
+            .last()
+            .unwrap_or(child);
+        let snippet = &source_code[first.start_byte()..child.end_byte()];
+        if snippet.len() <= MAX_SNIPPET_BYTES {
+            snippets.push(snippet.to_string());
+        }
+    }
+    snippets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source_code: &str) -> Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        parser.parse(source_code, None).unwrap()
+    }
+
+    #[test]
+    fn harvested_with_attributes_and_docs() {
+        let source_code = "//! crate\nuse a::b;\n// c\n#[derive(Debug)]\n/// doc\nstruct S;\nfn f( {}\nconst C: u8 = 0;\n";
+        assert_eq!(
+            harvest(source_code, &parse(source_code)),
+            ["#[derive(Debug)]\n/// doc\nstruct S;"]
+        );
+    }
+
+    #[test]
+    fn spliced_between_items_once() {
+        let crusher = CrossoverCrusher {
+            snippets: ["struct A;", "fn g() {}", "struct A;", "struct S;"]
+                .map(String::from)
+                .to_vec(),
+            seed: 7,
+        };
+        let source_code = "#![no_std]\nstruct S;\n// c\nfn f() {}\n";
+        let mutations = crusher.mutations(source_code, &parse(source_code));
+        // not `struct S;` of the file itself, nor `struct A;` twice
+        assert_eq!(mutations.len(), 2);
+        for mutation in &mutations {
+            assert!([20, 35].contains(&mutation.start), "{}", mutation.start);
+        }
+        let mut spliced: Vec<&str> = mutations.iter().map(|m| m.replacement.trim()).collect();
+        spliced.sort();
+        assert_eq!(spliced, ["fn g() {}", "struct A;"]);
+        // files of nothing but attributes get theirs at the end
+        let mutations = crusher.mutations("#![no_std]\n", &parse("#![no_std]\n"));
+        assert!(mutations.iter().all(|m| m.start == 11));
+    }
+}
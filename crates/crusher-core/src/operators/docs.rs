@@ -0,0 +1,153 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::ITEM, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const DELETE: Template = Template::new(ITEM, "");
+const TO_INNER: Template = Template::new("//@@ doc\nfn _f() {}", "!");
+const TO_OUTER_LINE: Template = Template::new("//@@ doc\nfn _f() {}", "/");
+const TO_OUTER_BLOCK: Template = Template::new("/*@@ doc */\nfn _f() {}", "*");
+static TEMPLATES: [Template; 4] = [DELETE, TO_INNER, TO_OUTER_LINE, TO_OUTER_BLOCK];
+
+/// Mutates doc comments for rustdoc's sake: deletes them, swaps `///` and
+/// `//!` (and their block forms), and truncates fenced code examples.
+pub struct DocCrusher;
+
+impl Mutator for DocCrusher {
+    fn name(&self) -> &'static str {
+        "docs"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Deletes doc comments, swaps `///` and `//!`, and truncates fenced code examples mid-way.",
+            node_kinds: &["line_comment", "block_comment", "doc_comment"],
+            example: "/// ```\n/// let a = 1;\n/// assert_eq!(a, 1);\n/// ```\nfn f() {}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        let mut fence = FenceTracker::default();
+        for_each_node(tree.root_node(), &mut |node| {
+            if !matches!(node.kind(), "line_comment" | "block_comment") {
+                return;
+            }
+            let Some(marker) = doc_marker(node) else {
+                return;
+            };
+            acc.push(Mutation::new(node, DELETE.instantiate(&[])));
+
+            let flipped = match (node.kind(), marker.kind()) {
+                (_, "outer_doc_comment_marker") => TO_INNER,
+                ("line_comment", _) => TO_OUTER_LINE,
+                _ => TO_OUTER_BLOCK,
+            };
+            acc.push(Mutation::new(marker, flipped.instantiate(&[])));
+
+            if node.kind() == "line_comment" {
+                fence.feed(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn doc_marker(comment: Node) -> Option<Node> {
+    comment
+        .child_by_field_name("outer")
+        .or_else(|| comment.child_by_field_name("inner"))
+}
+
+/// Follows ```` ``` ```` fences across consecutive `///` lines and, once a
+/// fence closes, truncates the example in the middle (with and without
+/// its closing fence). A fence left open at the end of one run of doc
+/// lines is dropped rather than closed by the next item's docs.
+#[derive(Default)]
+struct FenceTracker<'t> {
+    open: bool,
+    code_lines: Vec<Node<'t>>,
+    last: Option<Node<'t>>,
+}
+
+impl<'t> FenceTracker<'t> {
+    fn feed(&mut self, source_code: &str, line: Node<'t>, acc: &mut Vec<Mutation>) {
+        if line.prev_sibling() != self.last.replace(line) {
+            self.open = false;
+            self.code_lines.clear();
+        }
+        let doc = line
+            .child_by_field_name("doc")
+            .map(|d| node_text(d, source_code))
+            .unwrap_or_default();
+        if !doc.trim_start().starts_with("```") {
+            if self.open {
+                self.code_lines.push(line);
+            }
+            return;
+        }
+        if self.open && !self.code_lines.is_empty() {
+            let cut = &self.code_lines[self.code_lines.len() / 2];
+            let last = self.code_lines.last().unwrap();
+            for end in [last.end_byte(), line.end_byte()] {
+                acc.push(Mutation {
+                    start: cut.start_byte(),
+                    end,
+                    replacement: DELETE.instantiate(&[]),
+                    kind: line.kind(),
+                });
+            }
+        }
+        self.open = !self.open;
+        self.code_lines.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        DocCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn markers_flip() {
+        assert_eq!(
+            crushed("/// a\n//! b\n/** c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n"),
+            [
+                "//! b\n/** c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "//! a\n//! b\n/** c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n/** c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n/// b\n/** c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n//! b\n\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n//! b\n/*! c */\n/*! d */\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n//! b\n/** c */\n\n//// e\n/*** f */\nfn f() {}\n",
+                "/// a\n//! b\n/** c */\n/** d */\n//// e\n/*** f */\nfn f() {}\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn fences_truncate_within_one_item() {
+        let source_code = "/// ```\n/// a;\n/// b;\n/// ```\nfn f() {}\n";
+        let mutants = crushed(source_code);
+        assert_eq!(mutants.len(), 10);
+        assert_eq!(mutants[8], "/// ```\n/// a;\n/// ```\nfn f() {}\n");
+        assert_eq!(mutants[9], "/// ```\n/// a;\nfn f() {}\n");
+        // a fence left open is not closed by the next item's docs
+        let source_code = "/// ```\n/// a;\n/// b;\nfn f() {}\n/// ```\nfn g() {}\n";
+        assert_eq!(crushed(source_code).len(), 8);
+    }
+}
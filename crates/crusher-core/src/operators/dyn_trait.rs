@@ -0,0 +1,145 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::TYPE, for_each_node, in_type_position, node_text, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+
+/// Node kinds that name a concrete type and may get a `dyn` prepended.
+const CONCRETE_TYPES: [&str; 4] = [
+    "type_identifier",
+    "generic_type",
+    "scoped_type_identifier",
+    "primitive_type",
+];
+
+const REMOVE_DYN: Template = Template::new(TYPE, "$trait");
+const AUTO_TRAITS: Template = Template::new(TYPE, "$type + Send + Sync");
+const AUTO_TRAITS_PARENS: Template = Template::new("type _T = &@@;", "($type + Send + Sync)");
+const TO_IMPL: Template = Template::new(TYPE, "impl $trait");
+const ADD_DYN: Template = Template::new(TYPE, "dyn $type");
+static TEMPLATES: [Template; 5] = [
+    REMOVE_DYN,
+    AUTO_TRAITS,
+    AUTO_TRAITS_PARENS,
+    TO_IMPL,
+    ADD_DYN,
+];
+
+/// Mutates trait object types: strips `dyn`, adds it in front of concrete
+/// types, adds `+ Send + Sync` and turns `dyn Trait` into `impl Trait`.
+pub struct DynCrusher;
+
+impl Mutator for DynCrusher {
+    fn name(&self) -> &'static str {
+        "dyn"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Strips `dyn`, adds it in front of concrete types, adds `+ Send + Sync` and turns `dyn Trait` into `impl Trait`.",
+            node_kinds: &["dynamic_type", "type_identifier", "generic_type", "scoped_type_identifier", "primitive_type"],
+            example: "fn f(a: &dyn Tr, b: Box<u8>) {}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.kind() == "dynamic_type" {
+                crush_dynamic_type(source_code, node, &mut acc);
+            } else if CONCRETE_TYPES.contains(&node.kind()) && in_type_position(node) {
+                let text = node_text(node, source_code);
+                acc.push(Mutation::new(node, ADD_DYN.instantiate(&[("type", text)])));
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_dynamic_type(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let text = node_text(node, source_code);
+    let Some(trait_node) = node.child_by_field_name("trait") else {
+        return;
+    };
+    let trait_text = node_text(trait_node, source_code);
+
+    acc.push(Mutation::new(
+        node,
+        REMOVE_DYN.instantiate(&[("trait", trait_text)]),
+    ));
+
+    // `&dyn A + Send` does not parse, the bounds need parentheses there
+    let needs_parens = node
+        .parent()
+        .is_some_and(|p| matches!(p.kind(), "reference_type" | "pointer_type"));
+    let with_auto_traits = if needs_parens {
+        AUTO_TRAITS_PARENS
+    } else {
+        AUTO_TRAITS
+    };
+    acc.push(Mutation::new(
+        node,
+        with_auto_traits.instantiate(&[("type", text)]),
+    ));
+
+    acc.push(Mutation::new(
+        node,
+        TO_IMPL.instantiate(&[("trait", trait_text)]),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        DynCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn trait_objects() {
+        assert_eq!(
+            crushed("fn f(a: &dyn Tr, b: Box<u8>) {}"),
+            [
+                "fn f(a: &Tr, b: Box<u8>) {}",
+                // `&dyn Tr + Send` would not parse
+                "fn f(a: &(dyn Tr + Send + Sync), b: Box<u8>) {}",
+                "fn f(a: &impl Tr, b: Box<u8>) {}",
+                "fn f(a: &dyn Tr, b: dyn Box<u8>) {}",
+                "fn f(a: &dyn Tr, b: Box<dyn u8>) {}",
+            ]
+        );
+        assert_eq!(
+            crushed("type T = Box<dyn Tr>;")[2],
+            "type T = Box<dyn Tr + Send + Sync>;"
+        );
+    }
+
+    #[test]
+    fn traits_get_no_dyn() {
+        // the traits of `dyn`, `impl` and `for<'a>` are no types
+        for source_code in [
+            "fn f(a: &dyn for<'a> X<'a>) {}",
+            "fn f(a: impl for<'a> X<'a>) {}",
+            "fn f<T: X<u8>>() {}",
+        ] {
+            for mutant in crushed(source_code) {
+                assert!(!mutant.contains("dyn X"), "{}", mutant);
+            }
+        }
+        assert!(crushed("fn f<T: X>() {}").is_empty());
+    }
+}
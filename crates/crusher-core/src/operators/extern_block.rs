@@ -0,0 +1,150 @@
+use tree_sitter::{Node, Tree};
+
+use super::{for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const EXTERN_MODIFIER: &str = "@@ {}";
+const ABIS: [Template; 5] = [
+    Template::new(EXTERN_MODIFIER, "extern \"C\""),
+    Template::new(EXTERN_MODIFIER, "extern \"Rust\""),
+    Template::new(EXTERN_MODIFIER, "extern \"system\""),
+    Template::new(EXTERN_MODIFIER, "extern \"C-unwind\""),
+    Template::new(EXTERN_MODIFIER, "extern \"crusher-gibberish\""),
+];
+const WITH_BODY: Template = Template::new("extern \"C\" { @@ }", "$signature {}");
+static TEMPLATES: [Template; 6] = [ABIS[0], ABIS[1], ABIS[2], ABIS[3], ABIS[4], WITH_BODY];
+
+/// Mutates `extern "ABI" { ... }` blocks: swaps the ABI string, deletes
+/// foreign items and gives foreign functions a body.
+pub struct ExternCrusher;
+
+impl Mutator for ExternCrusher {
+    fn name(&self) -> &'static str {
+        "extern"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Swaps the ABI of extern blocks, deletes foreign items and gives foreign functions a body.",
+            node_kinds: &["foreign_mod_item", "extern_modifier", "function_signature_item"],
+            example: "extern \"C\" {\n    fn g(x: u8);\n}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.kind() == "foreign_mod_item" {
+                crush_foreign_mod(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_foreign_mod(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let mut cursor = node.walk();
+    if let Some(modifier) = node
+        .children(&mut cursor)
+        .find(|c| c.kind() == "extern_modifier")
+    {
+        // without an ABI string the block is `extern "C"` already
+        let current = match node_text(modifier, source_code) {
+            "extern" => "extern \"C\"",
+            current => current,
+        };
+        for abi in ABIS {
+            let replacement = abi.instantiate(&[]);
+            if replacement != current {
+                acc.push(Mutation::new(modifier, replacement));
+            }
+        }
+    }
+
+    let Some(body) = node.child_by_field_name("body") else {
+        return;
+    };
+    let mut cursor = body.walk();
+    for item in body.named_children(&mut cursor) {
+        if matches!(
+            item.kind(),
+            "line_comment" | "block_comment" | "attribute_item"
+        ) {
+            continue;
+        }
+        // the attributes of a deleted item go with it
+        let mut start = item;
+        while let Some(attribute) = start
+            .prev_named_sibling()
+            .filter(|prev| prev.kind() == "attribute_item")
+        {
+            start = attribute;
+        }
+        acc.push(Mutation {
+            start: start.start_byte(),
+            end: item.end_byte(),
+            replacement: String::new(),
+            kind: item.kind(),
+        });
+
+        if item.kind() == "function_signature_item" {
+            let signature = node_text(item, source_code).trim_end_matches(';');
+            acc.push(Mutation::new(
+                item,
+                WITH_BODY.instantiate(&[("signature", signature)]),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ExternCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn abis_and_foreign_items() {
+        assert_eq!(
+            crushed("extern \"C\" { fn f(a: u8); static X: u8; }"),
+            [
+                "extern \"Rust\" { fn f(a: u8); static X: u8; }",
+                "extern \"system\" { fn f(a: u8); static X: u8; }",
+                "extern \"C-unwind\" { fn f(a: u8); static X: u8; }",
+                "extern \"crusher-gibberish\" { fn f(a: u8); static X: u8; }",
+                "extern \"C\" {  static X: u8; }",
+                "extern \"C\" { fn f(a: u8) {} static X: u8; }",
+                "extern \"C\" { fn f(a: u8);  }",
+            ]
+        );
+        // functions outside extern blocks keep their ABI
+        assert!(crushed("extern \"C\" fn f() {}\nextern crate core;").is_empty());
+    }
+
+    #[test]
+    fn implicit_abi_and_attributes() {
+        let mutants = crushed("extern {\n    #[link_name = \"g\"]\n    fn f();\n}");
+        // `extern` is `extern "C"`, no mutant spells that out
+        assert!(!mutants.iter().any(|m| m.starts_with("extern \"C\"")));
+        assert_eq!(mutants.len(), 6);
+        // the attribute goes with the item, it is never deleted alone
+        assert_eq!(mutants[4], "extern {\n    \n}");
+        assert_eq!(
+            mutants[5],
+            "extern {\n    #[link_name = \"g\"]\n    fn f() {}\n}"
+        );
+    }
+}
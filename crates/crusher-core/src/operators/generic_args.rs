@@ -0,0 +1,133 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::TYPE, for_each_node, in_type_position, node_text, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+
+const NO_ARGS: Template = Template::new(TYPE, "$head");
+const WITH_ARGS: Template = Template::new(TYPE, "$head<$args>");
+const ADD_ARGS: Template = Template::new(TYPE, "$type<u8>");
+static TEMPLATES: [Template; 3] = [NO_ARGS, WITH_ARGS, ADD_ARGS];
+
+/// Changes the generic arguments where types are used: drops them all or
+/// the last one, repeats the last one, puts a lifetime first or a const last,
+/// and gives plain types an argument.
+pub struct GenericArgsCrusher;
+
+impl Mutator for GenericArgsCrusher {
+    fn name(&self) -> &'static str {
+        "generic-args"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Drops, repeats and adds generic arguments at type uses, including lifetimes and consts where types go.",
+            node_kinds: &["generic_type", "type_identifier", "primitive_type"],
+            example: "struct S { v: Vec<u8>, m: HashMap<String, u8> }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if !in_type_position(node) {
+                return;
+            }
+            match node.kind() {
+                "generic_type" => crush_arguments(source_code, node, &mut acc),
+                "type_identifier" | "primitive_type" => {
+                    let text = node_text(node, source_code);
+                    acc.push(Mutation::new(node, ADD_ARGS.instantiate(&[("type", text)])));
+                }
+                _ => {}
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_arguments(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let (Some(head), Some(arguments)) = (
+        node.child_by_field_name("type"),
+        node.child_by_field_name("type_arguments"),
+    ) else {
+        return;
+    };
+    let head = node_text(head, source_code);
+    let mut cursor = arguments.walk();
+    let args: Vec<&str> = arguments
+        .named_children(&mut cursor)
+        .filter(|arg| !matches!(arg.kind(), "line_comment" | "block_comment"))
+        .map(|arg| node_text(arg, source_code))
+        .collect();
+    let Some(&last) = args.last() else {
+        return;
+    };
+
+    acc.push(Mutation::new(node, NO_ARGS.instantiate(&[("head", head)])));
+    let mut lists = vec![
+        [args.as_slice(), &[last]].concat(),
+        [&["'static"], args.as_slice()].concat(),
+        [args.as_slice(), &["0"]].concat(),
+    ];
+    if args.len() > 1 {
+        lists.push(args[..args.len() - 1].to_vec());
+    }
+    for list in lists {
+        let list = list.join(", ");
+        acc.push(Mutation::new(
+            node,
+            WITH_ARGS.instantiate(&[("head", head), ("args", &list)]),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        GenericArgsCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn argument_lists() {
+        assert_eq!(
+            crushed("type M = HashMap<K, /* c */ V>;"),
+            [
+                "type M = HashMap;",
+                "type M = HashMap<K, V, V>;",
+                "type M = HashMap<'static, K, V>;",
+                "type M = HashMap<K, V, 0>;",
+                "type M = HashMap<K>;",
+                "type M = HashMap<K<u8>, /* c */ V>;",
+                "type M = HashMap<K, /* c */ V<u8>>;",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_types_get_arguments() {
+        // no trait bounds, binding names, turbofish heads or alias names
+        assert_eq!(
+            crushed("fn f(x: impl Iterator<Item = u8>) { Vec::<T>::new(); }"),
+            [
+                "fn f(x: impl Iterator<Item = u8<u8>>) { Vec::<T>::new(); }",
+                "fn f(x: impl Iterator<Item = u8>) { Vec::<T<u8>>::new(); }",
+            ]
+        );
+    }
+}
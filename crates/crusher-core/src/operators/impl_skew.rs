@@ -0,0 +1,231 @@
+use tree_sitter::{Node, Tree};
+
+use super::{for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const EXTRA_PARAM: Template = Template::new("fn _f(a: u8@@) {}", ", _skewed: u8");
+const ONLY_PARAM: Template = Template::new("fn _f(@@) {}", "_skewed: u8");
+const RETURN_TYPE: Template = Template::new("fn _f() -> @@ {}", "$type");
+const ADDED_RETURN: Template = Template::new("fn _f()@@ {}", " -> u8");
+const VALUE_SELF: Template = Template::new("impl S { fn _f(@@) {} }", "self");
+const SHARED_SELF: Template = Template::new("impl S { fn _f(@@) {} }", "&self");
+const UNIQUE_SELF: Template = Template::new("impl S { fn _f(@@) {} }", "&mut self");
+const ADDED_SELF: Template = Template::new("impl S { fn _f(@@a: u8) {} }", "&self, ");
+const NO_SELF: Template = Template::new("impl S { fn _f(@@) {} }", "");
+static TEMPLATES: [Template; 9] = [
+    EXTRA_PARAM,
+    ONLY_PARAM,
+    RETURN_TYPE,
+    ADDED_RETURN,
+    VALUE_SELF,
+    SHARED_SELF,
+    UNIQUE_SELF,
+    ADDED_SELF,
+    NO_SELF,
+];
+
+/// Makes the methods of trait impls disagree with the trait: one mutant per
+/// method with an extra parameter, another return type or another kind of
+/// `self`.
+pub struct ImplSkewCrusher;
+
+impl Mutator for ImplSkewCrusher {
+    fn name(&self) -> &'static str {
+        "impl-skew"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Skews the methods of `impl Trait for Type` blocks from the trait: adds a parameter, changes or adds the return type, and swaps `self`, `&self` and `&mut self`, adds or removes it.",
+            node_kinds: &["impl_item", "function_item"],
+            example: "trait T { fn f(&self) -> u8; }\nimpl T for u8 { fn f(&self) -> u8 { 0 } }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.kind() != "impl_item" || node.child_by_field_name("trait").is_none() {
+                return;
+            }
+            let Some(body) = node.child_by_field_name("body") else {
+                return;
+            };
+            let mut cursor = body.walk();
+            for method in body.named_children(&mut cursor) {
+                if method.kind() == "function_item" {
+                    skew_method(source_code, method, &mut acc);
+                }
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn insert(at: usize, template: Template) -> Mutation {
+    Mutation {
+        start: at,
+        end: at,
+        replacement: template.instantiate(&[]),
+        kind: "function_item",
+    }
+}
+
+/// Whether the parameter `param` is `self`, also with a type like
+/// `self: Box<Self>`.
+fn is_receiver(param: Node) -> bool {
+    match param.kind() {
+        "self_parameter" => true,
+        "parameter" => param
+            .child_by_field_name("pattern")
+            .is_some_and(|pattern| pattern.kind() == "self"),
+        _ => false,
+    }
+}
+
+fn comma_after(node: Node) -> Option<Node> {
+    node.next_sibling().filter(|next| next.kind() == ",")
+}
+
+fn skew_method(source_code: &str, method: Node, acc: &mut Vec<Mutation>) {
+    let Some(parameters) = method.child_by_field_name("parameters") else {
+        return;
+    };
+    let mut cursor = parameters.walk();
+    let params: Vec<Node> = parameters
+        .named_children(&mut cursor)
+        .filter(|p| {
+            !matches!(
+                p.kind(),
+                "line_comment" | "block_comment" | "attribute_item"
+            )
+        })
+        .collect();
+
+    acc.push(match params.last() {
+        Some(last) => insert(last.end_byte(), EXTRA_PARAM),
+        None => insert(parameters.start_byte() + 1, ONLY_PARAM),
+    });
+
+    match method.child_by_field_name("return_type") {
+        Some(ty) => {
+            let other = match node_text(ty, source_code) {
+                "u8" => "()",
+                _ => "u8",
+            };
+            acc.push(Mutation::new(
+                ty,
+                RETURN_TYPE.instantiate(&[("type", other)]),
+            ));
+        }
+        None => acc.push(insert(parameters.end_byte(), ADDED_RETURN)),
+    }
+
+    match params.first().filter(|&&p| is_receiver(p)) {
+        Some(&receiver) => {
+            let own = node_text(receiver, source_code);
+            for template in [VALUE_SELF, SHARED_SELF, UNIQUE_SELF] {
+                if template.text != own {
+                    acc.push(Mutation::new(receiver, template.instantiate(&[])));
+                }
+            }
+            // the comma after `self` goes with it, also a trailing one
+            let end = match params.get(1) {
+                Some(next) => next.start_byte(),
+                None => comma_after(receiver).map_or(receiver.end_byte(), |c| c.end_byte()),
+            };
+            acc.push(Mutation {
+                start: receiver.start_byte(),
+                end,
+                replacement: NO_SELF.instantiate(&[]),
+                kind: "self_parameter",
+            });
+        }
+        None => {
+            let template = match params.is_empty() {
+                true => SHARED_SELF,
+                false => ADDED_SELF,
+            };
+            acc.push(insert(parameters.start_byte() + 1, template));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ImplSkewCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn skewed_methods() {
+        assert_eq!(
+            crushed("impl T for S { fn f(&self, a: u8) -> u8 { a } }\n"),
+            [
+                "impl T for S { fn f(&self, a: u8, _skewed: u8) -> u8 { a } }\n",
+                "impl T for S { fn f(&self, a: u8) -> () { a } }\n",
+                "impl T for S { fn f(self, a: u8) -> u8 { a } }\n",
+                "impl T for S { fn f(&mut self, a: u8) -> u8 { a } }\n",
+                "impl T for S { fn f(a: u8) -> u8 { a } }\n",
+            ]
+        );
+        assert_eq!(
+            crushed("impl T for S { fn f() {} }\n"),
+            [
+                "impl T for S { fn f(_skewed: u8) {} }\n",
+                "impl T for S { fn f() -> u8 {} }\n",
+                "impl T for S { fn f(&self) {} }\n",
+            ]
+        );
+        assert_eq!(
+            crushed("impl T for S { fn f(a: u8) {} }\n")[2],
+            "impl T for S { fn f(&self, a: u8) {} }\n"
+        );
+    }
+
+    #[test]
+    fn typed_and_trailing_receivers() {
+        let mutants = crushed("impl T for S { fn f(self: Box<Self>) {} }\n");
+        assert_eq!(
+            mutants[2..],
+            [
+                "impl T for S { fn f(self) {} }\n",
+                "impl T for S { fn f(&self) {} }\n",
+                "impl T for S { fn f(&mut self) {} }\n",
+                "impl T for S { fn f() {} }\n",
+            ]
+        );
+        // no `(,)` is left of `(&self,)`
+        assert_eq!(
+            crushed("impl T for S { fn f(&self,) {} }\n")
+                .last()
+                .unwrap(),
+            "impl T for S { fn f() {} }\n"
+        );
+        assert_eq!(
+            crushed("impl T for S { fn f(mut self, a: u8,) {} }\n")
+                .last()
+                .unwrap(),
+            "impl T for S { fn f(a: u8,) {} }\n"
+        );
+    }
+
+    #[test]
+    fn inherent_impls_stay() {
+        assert!(crushed("trait T { fn f(&self) -> u8; }\nimpl S { fn f(&self) {} }\n").is_empty());
+        assert!(crushed("impl T for S { type A = u8; const C: u8 = 0; }\n").is_empty());
+    }
+}
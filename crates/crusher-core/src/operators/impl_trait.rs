@@ -0,0 +1,165 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::{STATEMENT, TYPE},
+    for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template,
+};
+
+const REMOVE_IMPL: Template = Template::new(TYPE, "$trait");
+const LET_BINDING: Template = Template::new(STATEMENT, " let _impl_trait: $type = todo!();");
+const STRUCT_FIELD: Template = Template::new("struct _S { a: @@ }", "$type");
+const APPEND_ARG: Template = Template::new("fn _f@@ {}", "($params, _ret: $type)");
+const ONLY_ARG: Template = Template::new("fn _f@@ {}", "(_ret: $type)");
+static TEMPLATES: [Template; 5] = [REMOVE_IMPL, LET_BINDING, STRUCT_FIELD, APPEND_ARG, ONLY_ARG];
+
+/// Mutates `impl Trait` types: drops the `impl`, moves them into positions
+/// where they are not allowed (let bindings, struct fields) and turns
+/// return-position `impl Trait` into an argument.
+pub struct ImplTraitCrusher;
+
+impl Mutator for ImplTraitCrusher {
+    fn name(&self) -> &'static str {
+        "impl-trait"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Drops `impl` from `impl Trait`, moves it into let bindings and struct fields, and turns return-position `impl Trait` into an argument.",
+            node_kinds: &["abstract_type", "field_declaration"],
+            example: "struct S { a: u8 }\nfn f(a: impl Tr) -> impl Tr { a }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut impl_types: Vec<Node> = Vec::new();
+        let mut field_types: Vec<Node> = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "abstract_type" => impl_types.push(node),
+            "field_declaration" => field_types.extend(node.child_by_field_name("type")),
+            _ => {}
+        });
+
+        let mut acc = Vec::new();
+        for &node in impl_types.iter() {
+            let text = node_text(node, source_code);
+            if let Some(trait_node) = node.child_by_field_name("trait") {
+                let trait_text = node_text(trait_node, source_code);
+                acc.push(Mutation::new(
+                    node,
+                    REMOVE_IMPL.instantiate(&[("trait", trait_text)]),
+                ));
+            }
+            if let Some(body) = enclosing_fn_body(node) {
+                let at = body.start_byte() + 1;
+                let binding = Mutation {
+                    start: at,
+                    end: at,
+                    replacement: LET_BINDING.instantiate(&[("type", text)]),
+                    kind: body.kind(),
+                };
+                // the same type elsewhere in the signature binds the same
+                if !acc.contains(&binding) {
+                    acc.push(binding);
+                }
+            }
+            acc.extend(return_to_argument(source_code, node, text));
+        }
+
+        let mut distinct: Vec<&str> = impl_types
+            .iter()
+            .map(|&n| node_text(n, source_code))
+            .collect();
+        distinct.sort_unstable();
+        distinct.dedup();
+        for &field_type in field_types.iter() {
+            for text in distinct.iter() {
+                acc.push(Mutation::new(
+                    field_type,
+                    STRUCT_FIELD.instantiate(&[("type", text)]),
+                ));
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn enclosing_fn_body(node: Node) -> Option<Node> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if n.kind() == "function_item" {
+            return n.child_by_field_name("body");
+        }
+        current = n.parent();
+    }
+    None
+}
+
+/// `fn f(a: A) -> impl T` becomes `fn f(a: A, _ret: impl T)`.
+fn return_to_argument(source_code: &str, node: Node, text: &str) -> Option<Mutation> {
+    let function = node.parent()?;
+    if function.child_by_field_name("return_type")? != node {
+        return None;
+    }
+    let parameters = function.child_by_field_name("parameters")?;
+    let params_text = node_text(parameters, source_code);
+    let inner = params_text[1..params_text.len() - 1]
+        .trim()
+        .trim_end_matches(',');
+    let new_params = if inner.is_empty() {
+        ONLY_ARG.instantiate(&[("type", text)])
+    } else {
+        APPEND_ARG.instantiate(&[("params", inner), ("type", text)])
+    };
+    Some(Mutation {
+        start: parameters.start_byte(),
+        end: node.end_byte(),
+        replacement: new_params,
+        kind: parameters.kind(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ImplTraitCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn one_binding_per_type() {
+        assert_eq!(
+            crushed("struct S { a: u8 }\nfn f(a: impl Tr) -> impl Tr { a }\n"),
+            [
+                "struct S { a: u8 }\nfn f(a: Tr) -> impl Tr { a }\n",
+                "struct S { a: u8 }\nfn f(a: impl Tr) -> impl Tr { let _impl_trait: impl Tr = todo!(); a }\n",
+                "struct S { a: u8 }\nfn f(a: impl Tr) -> Tr { a }\n",
+                "struct S { a: u8 }\nfn f(a: impl Tr, _ret: impl Tr) { a }\n",
+                "struct S { a: impl Tr }\nfn f(a: impl Tr) -> impl Tr { a }\n",
+            ]
+        );
+        // other types in one function, or the same in another, bind again
+        let mutants = crushed("fn f(a: impl A, b: impl B) {}\nfn g(a: impl A) {}\n");
+        let bindings = mutants
+            .iter()
+            .filter(|m| m.contains("let _impl_trait"))
+            .count();
+        assert_eq!(bindings, 3);
+        let mut distinct = mutants.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), mutants.len());
+    }
+}
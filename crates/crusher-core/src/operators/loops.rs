@@ -0,0 +1,175 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::EXPRESSION, for_each_node, Mutation, Mutator, OperatorDoc, Template};
+
+const LABELED: [&str; 4] = [
+    "loop_expression",
+    "while_expression",
+    "for_expression",
+    "block",
+];
+
+const TO_LOOP: Template = Template::new("fn _f() { @@{} }", "loop ");
+const UNDECLARED_LABEL: Template =
+    Template::new("fn _f() { loop { break @@; } }", "'crusher_undeclared");
+const BREAK_VALUE: Template = Template::new("fn _f() { loop { break@@ } }", " 0");
+const UNIT_EXPR: Template = Template::new(EXPRESSION, "()");
+static TEMPLATES: [Template; 4] = [TO_LOOP, UNDECLARED_LABEL, BREAK_VALUE, UNIT_EXPR];
+
+/// Mutates loops and their control flow: deletes labels, points `break` and
+/// `continue` at undeclared labels, turns `while` into `loop`, gives `break`
+/// a value and removes `break`/`continue` altogether.
+pub struct LoopCrusher;
+
+impl Mutator for LoopCrusher {
+    fn name(&self) -> &'static str {
+        "loops"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Deletes loop labels, breaks to undeclared labels, turns `while` into `loop`, gives `break` a value and removes `break`/`continue`.",
+            node_kinds: &["loop_expression", "while_expression", "for_expression", "block", "label", "break_expression", "continue_expression"],
+            example: "fn f() {\n    'a: while true {\n        break 'a;\n    }\n}\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, _source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "while_expression" => {
+                delete_label(node, &mut acc);
+                while_to_loop(node, &mut acc);
+            }
+            kind if LABELED.contains(&kind) => delete_label(node, &mut acc),
+            "break_expression" | "continue_expression" => crush_jump(node, &mut acc),
+            _ => {}
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// `'a: loop {}` becomes `loop {}`.
+fn delete_label(node: Node, acc: &mut Vec<Mutation>) {
+    let Some(label) = node.child(0).filter(|c| c.kind() == "label") else {
+        return;
+    };
+    let Some(after_colon) = label.next_sibling().and_then(|colon| colon.next_sibling()) else {
+        return;
+    };
+    acc.push(Mutation {
+        start: label.start_byte(),
+        end: after_colon.start_byte(),
+        replacement: String::new(),
+        kind: label.kind(),
+    });
+}
+
+/// `while cond {}` becomes `loop {}`.
+fn while_to_loop(node: Node, acc: &mut Vec<Mutation>) {
+    let (Some(keyword), Some(body)) = (
+        node.children(&mut node.walk())
+            .find(|c| c.kind() == "while"),
+        node.child_by_field_name("body"),
+    ) else {
+        return;
+    };
+    acc.push(Mutation {
+        start: keyword.start_byte(),
+        end: body.start_byte(),
+        replacement: TO_LOOP.instantiate(&[]),
+        kind: node.kind(),
+    });
+}
+
+fn crush_jump(node: Node, acc: &mut Vec<Mutation>) {
+    let mut cursor = node.walk();
+    let label = node.children(&mut cursor).find(|c| c.kind() == "label");
+    if let Some(label) = label {
+        acc.push(Mutation::new(label, UNDECLARED_LABEL.instantiate(&[])));
+    }
+
+    // value-carrying break, which `while` and `for` loops reject
+    if node.kind() == "break_expression"
+        && node
+            .named_children(&mut cursor)
+            .all(|c| c.kind() == "label")
+    {
+        let at = label.unwrap_or(node).end_byte();
+        acc.push(Mutation {
+            start: at,
+            end: at,
+            replacement: BREAK_VALUE.instantiate(&[]),
+            kind: node.kind(),
+        });
+    }
+
+    // drop the whole statement, or leave `()` behind in tail position
+    match node.parent().filter(|p| p.kind() == "expression_statement") {
+        Some(statement) => acc.push(Mutation::new(statement, String::new())),
+        None => acc.push(Mutation::new(node, UNIT_EXPR.instantiate(&[]))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        LoopCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn labels_and_jumps() {
+        assert_eq!(
+            crushed("fn f() { 'a: while let Some(x) = g() { continue 'a; } }"),
+            [
+                "fn f() { while let Some(x) = g() { continue 'a; } }",
+                "fn f() { 'a: loop { continue 'a; } }",
+                "fn f() { 'a: while let Some(x) = g() { continue 'crusher_undeclared; } }",
+                "fn f() { 'a: while let Some(x) = g() {  } }",
+            ]
+        );
+        // labeled blocks too
+        assert_eq!(
+            crushed("fn f() { 'c: { break 'c; } }"),
+            [
+                "fn f() { { break 'c; } }",
+                "fn f() { 'c: { break 'crusher_undeclared; } }",
+                "fn f() { 'c: { break 'c 0; } }",
+                "fn f() { 'c: {  } }",
+            ]
+        );
+    }
+
+    #[test]
+    fn values_and_tail_positions() {
+        // a break with a value already gets no other
+        assert_eq!(
+            crushed("fn f() { let v = loop { break 1; }; }"),
+            ["fn f() { let v = loop {  }; }"]
+        );
+        // jumps that are no statement leave `()` behind
+        assert_eq!(
+            crushed("fn f() { for i in x { match i { 0 => break, _ => continue } } }"),
+            [
+                "fn f() { for i in x { match i { 0 => break 0, _ => continue } } }",
+                "fn f() { for i in x { match i { 0 => (), _ => continue } } }",
+                "fn f() { for i in x { match i { 0 => break, _ => () } } }",
+            ]
+        );
+        assert!(crushed("fn f() { loop {} }").is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+//! Mutation operators ("crushers") and the glue to run them over a source file.
+
+mod arrays;
+mod cfg;
+mod coherence;
+mod crate_attrs;
+mod crossover;
+mod docs;
+mod dyn_trait;
+mod extern_block;
+mod generic_args;
+mod impl_skew;
+mod impl_trait;
+mod loops;
+mod nesting;
+mod params;
+mod primitives;
+mod query;
+mod recursive;
+mod references;
+mod registry;
+mod repr;
+mod struct_enum;
+mod structs;
+mod template;
+mod tokens;
+mod trivia;
+mod tuples;
+mod typenames;
+mod wrap;
+
+pub use arrays::ArrayCrusher;
+pub use cfg::CfgCrusher;
+pub use coherence::CoherenceCrusher;
+pub use crate_attrs::{inner_attribute_offset, insert_inner_attribute, CrateAttrCrusher};
+pub use crossover::{harvest, CrossoverCrusher};
+pub use docs::DocCrusher;
+pub use dyn_trait::DynCrusher;
+pub use extern_block::ExternCrusher;
+pub use generic_args::GenericArgsCrusher;
+pub use impl_skew::ImplSkewCrusher;
+pub use impl_trait::ImplTraitCrusher;
+pub use loops::LoopCrusher;
+pub use nesting::NestingCrusher;
+pub use params::ParamCrusher;
+pub use primitives::PrimitiveCrusher;
+pub use query::QueryCrusher;
+pub use recursive::RecursiveCrusher;
+pub use references::ReferenceCrusher;
+pub use registry::Registry;
+pub use repr::ReprCrusher;
+pub use struct_enum::StructEnumCrusher;
+pub use structs::{
+    find_all_structs, modify_structs, Crush, KeepAttrs, KeepGenerics, StructCrusher, StructForm,
+    StructInfo,
+};
+pub use template::{context, validate, Template};
+pub use tokens::TokenCrusher;
+pub use trivia::TriviaCrusher;
+pub use tuples::TupleCrusher;
+pub use typenames::{TypeContext, TypePool, TypePositions, TypenameCrusher};
+pub use wrap::WrapCrusher;
+
+use std::time::Instant;
+
+use tree_sitter::{Node, Point, Tree};
+
+use crate::crusher::Crusher;
+
+/// Where in a file a mutation applies: its byte span and the rows and byte
+/// columns of its ends, counted from 0 as tree-sitter does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MutationSite {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_point: Point,
+    pub end_point: Point,
+}
+
+impl MutationSite {
+    pub fn of(node: Node) -> MutationSite {
+        MutationSite {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            start_point: node.start_position(),
+            end_point: node.end_position(),
+        }
+    }
+
+    /// The site of `source_code[start..end]`, for spans that are no node.
+    pub fn from_bytes(source_code: &str, start: usize, end: usize) -> MutationSite {
+        let point = |byte: usize| {
+            let before = &source_code.as_bytes()[..byte];
+            let line_start = before
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1);
+            Point {
+                row: before.iter().filter(|&&b| b == b'\n').count(),
+                column: byte - line_start,
+            }
+        };
+        MutationSite {
+            start_byte: start,
+            end_byte: end,
+            start_point: point(start),
+            end_point: point(end),
+        }
+    }
+}
+
+/// One candidate mutant: replace `source[start..end]` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mutation {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+    /// Kind of the syntax node the mutation rewrites (or inserts into).
+    pub kind: &'static str,
+}
+
+impl Mutation {
+    pub fn new(node: Node, replacement: String) -> Mutation {
+        Mutation {
+            start: node.start_byte(),
+            end: node.end_byte(),
+            replacement,
+            kind: node.kind(),
+        }
+    }
+
+    /// Where the mutation applies in `source_code`.
+    pub fn site(&self, source_code: &str) -> MutationSite {
+        MutationSite::from_bytes(source_code, self.start, self.end)
+    }
+
+    /// The text the mutation replaces.
+    pub fn original<'s>(&self, source_code: &'s str) -> &'s str {
+        &source_code[self.start..self.end]
+    }
+
+    /// Whether the replacement is the text it replaces, leaving the
+    /// source as it was.
+    pub fn is_noop(&self, source_code: &str) -> bool {
+        self.replacement == self.original(source_code)
+    }
+
+    pub fn apply(&self, source_code: &str) -> String {
+        let before = &source_code[..self.start];
+        let after = &source_code[self.end..];
+        format!("{}{}{}", before, self.replacement, after)
+    }
+}
+
+/// What `explain <operator>` prints about an operator.
+pub struct OperatorDoc {
+    pub summary: &'static str,
+    pub node_kinds: &'static [&'static str],
+    /// Input the operator is run on to show before/after pairs.
+    pub example: &'static str,
+    /// `(flag, effect)` for every flag changing the operator's behavior.
+    pub flags: &'static [(&'static str, &'static str)],
+}
+
+/// `Sync` so one set of operators can serve every generating thread.
+pub trait Mutator: Sync {
+    /// Name used to select the operator with `--operator`.
+    fn name(&self) -> &'static str;
+
+    fn doc(&self) -> OperatorDoc;
+
+    /// Every mutation this operator proposes for one parsed file.
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation>;
+
+    /// Every replacement template the operator instantiates.
+    fn templates(&self) -> &'static [Template] {
+        &[]
+    }
+}
+
+pub fn all() -> Vec<Box<dyn Mutator>> {
+    vec![
+        Box::new(StructCrusher::default()),
+        Box::new(StructEnumCrusher),
+        Box::new(RecursiveCrusher),
+        Box::new(GenericArgsCrusher),
+        Box::new(ReprCrusher),
+        Box::new(TypenameCrusher::default()),
+        Box::new(DynCrusher),
+        Box::new(ImplTraitCrusher),
+        Box::new(ExternCrusher),
+        Box::new(LoopCrusher),
+        Box::new(DocCrusher),
+        Box::new(CfgCrusher),
+        Box::new(CrateAttrCrusher),
+        Box::new(CrossoverCrusher::default()),
+        Box::new(PrimitiveCrusher),
+        Box::new(ReferenceCrusher),
+        Box::new(WrapCrusher),
+        Box::new(TupleCrusher),
+        Box::new(ArrayCrusher),
+        Box::new(ParamCrusher::default()),
+        Box::new(ImplSkewCrusher),
+        Box::new(CoherenceCrusher),
+        Box::new(NestingCrusher::default()),
+        Box::new(TokenCrusher::default()),
+        Box::new(TriviaCrusher::default()),
+    ]
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn Mutator>> {
+    all().into_iter().find(|m| m.name() == name)
+}
+
+/// Parse `source_code` and collect the mutations `mutator` proposes for it.
+pub fn mutations(mutator: &dyn Mutator, source_code: &str) -> Vec<Mutation> {
+    mutations_until(mutator, source_code, None).unwrap()
+}
+
+/// Like [`mutations`], but gives up once `deadline` has passed.
+pub fn mutations_until(
+    mutator: &dyn Mutator,
+    source_code: &str,
+    deadline: Option<Instant>,
+) -> Option<Vec<Mutation>> {
+    Crusher::new().mutations_until(mutator, source_code, deadline)
+}
+
+pub fn crushed_sources(mutator: &dyn Mutator, source_code: &str) -> Vec<String> {
+    crushed_sources_until(mutator, source_code, None).unwrap()
+}
+
+/// Like [`crushed_sources`], but gives up once `deadline` has passed.
+pub fn crushed_sources_until(
+    mutator: &dyn Mutator,
+    source_code: &str,
+    deadline: Option<Instant>,
+) -> Option<Vec<String>> {
+    let found = mutations_until(mutator, source_code, deadline)?;
+    let mut sources = Vec::with_capacity(found.len());
+    for m in found.iter() {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return None;
+        }
+        sources.push(m.apply(source_code));
+    }
+    Some(sources)
+}
+
+/// Whether tree-sitter finds ERROR or MISSING nodes in `source_code`.
+pub fn has_syntax_errors(source_code: &str) -> bool {
+    Crusher::new().has_syntax_errors(source_code)
+}
+
+/// Pre-order walk over every node below `node`.
+pub(crate) fn for_each_node<'t>(node: Node<'t>, f: &mut dyn FnMut(Node<'t>)) {
+    f(node);
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        for_each_node(child, f);
+    }
+}
+
+pub(crate) fn node_text<'s>(node: Node, source_code: &'s str) -> &'s str {
+    &source_code[node.start_byte()..node.end_byte()]
+}
+
+/// Whether `node` is part of a comment or a string, where tokens are text.
+pub(crate) fn in_comment_or_string(node: Node) -> bool {
+    let mut ancestor = Some(node);
+    while let Some(node) = ancestor {
+        if matches!(
+            node.kind(),
+            "line_comment" | "block_comment" | "string_literal" | "raw_string_literal"
+        ) {
+            return true;
+        }
+        ancestor = node.parent();
+    }
+    false
+}
+
+/// The `T` of the type `node` if it is a `name<T>`, by any path ending in
+/// `name`, with one type argument past comments; not the `name::<T>` of a
+/// path in an expression.
+pub(crate) fn single_type_argument<'t>(
+    source_code: &str,
+    node: Node<'t>,
+    name: &str,
+) -> Option<Node<'t>> {
+    if node.kind() != "generic_type" {
+        return None;
+    }
+    let ty = node.child_by_field_name("type")?;
+    let head = ty.child_by_field_name("name").unwrap_or(ty);
+    let mut cursor = node.walk();
+    let is_turbofish = node.children(&mut cursor).any(|c| c.kind() == "::");
+    if node_text(head, source_code) != name || is_turbofish {
+        return None;
+    }
+    let arguments = node.child_by_field_name("type_arguments")?;
+    let mut cursor = arguments.walk();
+    let types: Vec<Node> = arguments
+        .named_children(&mut cursor)
+        .filter(|a| !matches!(a.kind(), "line_comment" | "block_comment"))
+        .collect();
+    match types[..] {
+        [inner] if inner.kind() != "lifetime" => Some(inner),
+        _ => None,
+    }
+}
+
+/// Whether `node` is used as a type, as opposed to being the name of an item,
+/// the head of a generic type, a path segment or already a trait bound.
+pub(crate) fn in_type_position(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "type_arguments" | "tuple_type" => true,
+        "generic_type"
+        | "dynamic_type"
+        | "abstract_type"
+        | "scoped_type_identifier"
+        | "trait_bounds"
+        | "bounded_type"
+        | "higher_ranked_trait_bound" => false,
+        _ => {
+            let mut cursor = parent.walk();
+            let is_type_field = parent
+                .children_by_field_name("type", &mut cursor)
+                .any(|child| child == node);
+            is_type_field || parent.child_by_field_name("return_type") == Some(node)
+        }
+    }
+}
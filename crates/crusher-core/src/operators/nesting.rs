@@ -0,0 +1,173 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::{EXPRESSION, TYPE},
+    for_each_node, in_type_position, node_text, Mutation, Mutator, OperatorDoc, Template,
+};
+
+/// Layers [`NestingCrusher`] wraps around every site when not configured.
+pub const DEFAULT_DEPTH: usize = 256;
+
+/// Types that get wrapped, where they are used as a type.
+const WRAPPED_TYPES: [&str; 10] = [
+    "array_type",
+    "function_type",
+    "generic_type",
+    "pointer_type",
+    "primitive_type",
+    "reference_type",
+    "scoped_type_identifier",
+    "tuple_type",
+    "type_identifier",
+    "unit_type",
+];
+
+const OPTION: Template = Template::new(TYPE, "Option<$type>");
+const PARENS: Template = Template::new(EXPRESSION, "($expr)");
+const BLOCK: Template = Template::new(EXPRESSION, "{ $expr }");
+static TEMPLATES: [Template; 3] = [OPTION, PARENS, BLOCK];
+
+/// Nests types `depth` layers deep in `Option<..>`, and the initializers of
+/// bindings, constants and statics and the arguments of calls in as many
+/// parentheses or blocks, for the recursion limits and stacks of the
+/// compiler.
+pub struct NestingCrusher {
+    pub depth: usize,
+}
+
+impl Default for NestingCrusher {
+    fn default() -> NestingCrusher {
+        NestingCrusher {
+            depth: DEFAULT_DEPTH,
+        }
+    }
+}
+
+impl Mutator for NestingCrusher {
+    fn name(&self) -> &'static str {
+        "nesting"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Wraps every type in layers of `Option<..>`, and initializers and call arguments in layers of parentheses or of blocks, 256 deep.",
+            node_kinds: &WRAPPED_TYPES,
+            example: "fn f(a: u8) { let b = a; }\n",
+            flags: &[("--nesting-depth N", "wrap in N layers instead of 256")],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            let text = node_text(node, source_code);
+            if WRAPPED_TYPES.contains(&node.kind()) && in_type_position(node) {
+                acc.push(Mutation::new(node, self.nested(OPTION, "type", text)));
+            } else if is_wrapped_expression(node) {
+                for template in [PARENS, BLOCK] {
+                    acc.push(Mutation::new(node, self.nested(template, "expr", text)));
+                }
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+impl NestingCrusher {
+    /// `text` in [`NestingCrusher::depth`] layers of `template`.
+    fn nested(&self, template: Template, placeholder: &str, text: &str) -> String {
+        let (open, close) = template
+            .instantiate(&[(placeholder, "\0")])
+            .split_once('\0')
+            .map(|(open, close)| (open.to_string(), close.to_string()))
+            .unwrap();
+        open.repeat(self.depth) + text + &close.repeat(self.depth)
+    }
+}
+
+/// Whether `node` is the initializer of a `let`, `const` or `static`, or
+/// an argument of a call.
+fn is_wrapped_expression(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "let_declaration" | "const_item" | "static_item" => {
+            parent.child_by_field_name("value") == Some(node)
+        }
+        "arguments" => {
+            node.is_named()
+                && !matches!(
+                    node.kind(),
+                    "line_comment" | "block_comment" | "attribute_item"
+                )
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        NestingCrusher { depth: 2 }
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn nested_types_and_expressions() {
+        assert_eq!(
+            crushed("fn f(a: u8) { let b = a; }\n"),
+            [
+                "fn f(a: Option<Option<u8>>) { let b = a; }\n",
+                "fn f(a: u8) { let b = ((a)); }\n",
+                "fn f(a: u8) { let b = { { a } }; }\n",
+            ]
+        );
+        assert_eq!(
+            crushed("const C: () = g(1);\n"),
+            [
+                "const C: Option<Option<()>> = g(1);\n",
+                "const C: () = ((g(1)));\n",
+                "const C: () = { { g(1) } };\n",
+                "const C: () = g(((1)));\n",
+                "const C: () = g({ { 1 } });\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn only_types_in_type_position() {
+        // not the head of a generic type, a path or a bound
+        assert_eq!(
+            crushed("fn f<T: Copy>(a: Vec<u8>) -> u8 { u8::MAX }\n"),
+            [
+                "fn f<T: Copy>(a: Option<Option<Vec<u8>>>) -> u8 { u8::MAX }\n",
+                "fn f<T: Copy>(a: Vec<Option<Option<u8>>>) -> u8 { u8::MAX }\n",
+                "fn f<T: Copy>(a: Vec<u8>) -> Option<Option<u8>> { u8::MAX }\n",
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_macro_arguments_stay() {
+        assert_eq!(
+            crushed("fn f() { let x; g(/* c */ 1); println!(\"{}\", x); }\n"),
+            [
+                "fn f() { let x; g(/* c */ ((1))); println!(\"{}\", x); }\n",
+                "fn f() { let x; g(/* c */ { { 1 } }); println!(\"{}\", x); }\n",
+            ]
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use tree_sitter::{Node, Tree};
+
+use super::{for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+use crate::higher_order;
+
+const PARAMS: Template = Template::new("fn _f(@@) {}", "$params");
+const ARGUMENTS: Template = Template::new("fn _f() { _g(@@); }", "$arguments");
+static TEMPLATES: [Template; 2] = [PARAMS, ARGUMENTS];
+
+/// Reorders, duplicates and deletes the parameters of function definitions,
+/// and with `fix_calls` the arguments of the calls to them in the file.
+#[derive(Default)]
+pub struct ParamCrusher {
+    /// Also rewrites the arguments of the calls in the file to match.
+    pub fix_calls: bool,
+}
+
+impl Mutator for ParamCrusher {
+    fn name(&self) -> &'static str {
+        "params"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Swaps neighbouring parameters of function definitions, reverses them, duplicates each and deletes each; `self` stays first.",
+            node_kinds: &["function_item"],
+            example: "fn f(a: u8, b: i32) {}\nfn g() { f(1, 2) }\n",
+            flags: &[(
+                "--fix-calls",
+                "also reorder, duplicate or delete the arguments of the calls to the function in the file",
+            )],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut functions = Vec::new();
+        let mut calls = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "function_item" => functions.push(node),
+            "call_expression" if self.fix_calls => calls.push(node),
+            _ => {}
+        });
+
+        let mut acc = Vec::new();
+        for function in functions {
+            let Some(parameters) = function.child_by_field_name("parameters") else {
+                continue;
+            };
+            let (params, has_self) = list(parameters);
+            let name = function
+                .child_by_field_name("name")
+                .map_or("", |n| node_text(n, source_code));
+            let mut calls: Vec<Node> = calls
+                .iter()
+                .copied()
+                .filter(|call| calls_function(source_code, *call, name, has_self))
+                .collect();
+            // calls among the arguments of a call are rewritten before it
+            calls.sort_by_key(|call| call.byte_range().len());
+            for order in orders(params.len()) {
+                let raw = |node: Node| node_text(node, source_code).to_string();
+                let mut edits = vec![reordered(&params, &order, true, &raw)];
+                for call in &calls {
+                    let Some(arguments) = call.child_by_field_name("arguments") else {
+                        continue;
+                    };
+                    let (arguments, _) = list(arguments);
+                    if arguments.len() == params.len() {
+                        let rewritten = |node: Node| rewritten(source_code, node, &edits);
+                        let edit = reordered(&arguments, &order, false, &rewritten);
+                        edits.push(edit);
+                    }
+                }
+                acc.push(combined(source_code, edits));
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// The parameters or arguments of `list`, without `self`, comments and
+/// attributes, and whether there was a `self`.
+fn list(list: Node) -> (Vec<Node>, bool) {
+    let mut cursor = list.walk();
+    let mut has_self = false;
+    let nodes = list
+        .named_children(&mut cursor)
+        .filter(|node| match node.kind() {
+            "self_parameter" => {
+                has_self = true;
+                false
+            }
+            "line_comment" | "block_comment" | "attribute_item" => false,
+            _ => true,
+        })
+        .collect();
+    (nodes, has_self)
+}
+
+/// The new orders of `n` parameters, by their old indices: every pair of
+/// neighbours swapped, all of them reversed, each duplicated and each
+/// deleted.
+fn orders(n: usize) -> Vec<Vec<usize>> {
+    let mut orders = Vec::new();
+    for i in 1..n {
+        let mut swapped: Vec<usize> = (0..n).collect();
+        swapped.swap(i - 1, i);
+        orders.push(swapped);
+    }
+    if n > 2 {
+        orders.push((0..n).rev().collect());
+    }
+    for i in 0..n {
+        let mut duplicated: Vec<usize> = (0..n).collect();
+        duplicated.insert(i + 1, i);
+        orders.push(duplicated);
+    }
+    for i in 0..n {
+        orders.push((0..n).filter(|&j| j != i).collect());
+    }
+    orders
+}
+
+/// `nodes` replaced by their `text` in `order`; the copy of a duplicated
+/// parameter binding a plain name is renamed `<name>_2`.
+fn reordered(
+    nodes: &[Node],
+    order: &[usize],
+    is_definition: bool,
+    text: &dyn Fn(Node) -> String,
+) -> Mutation {
+    let mut seen = vec![false; nodes.len()];
+    let texts: Vec<String> = order
+        .iter()
+        .map(|&i| {
+            let node = nodes[i];
+            let text = text(node);
+            let copy = std::mem::replace(&mut seen[i], true);
+            let pattern = node
+                .child_by_field_name("pattern")
+                .filter(|p| p.kind() == "identifier");
+            match pattern {
+                Some(pattern) if copy && is_definition => {
+                    let at = pattern.end_byte() - node.start_byte();
+                    format!("{}_2{}", &text[..at], &text[at..])
+                }
+                _ => text,
+            }
+        })
+        .collect();
+    let template = match is_definition {
+        true => (PARAMS, "params"),
+        false => (ARGUMENTS, "arguments"),
+    };
+    Mutation {
+        start: nodes[0].start_byte(),
+        end: nodes[nodes.len() - 1].end_byte(),
+        replacement: template.0.instantiate(&[(template.1, &texts.join(", "))]),
+        kind: match is_definition {
+            true => "parameters",
+            false => "arguments",
+        },
+    }
+}
+
+/// Whether `call` calls the function `name`: by its name or a path ending
+/// in it, or as a method when the function takes `self`.
+fn calls_function(source_code: &str, call: Node, name: &str, has_self: bool) -> bool {
+    let Some(function) = call.child_by_field_name("function") else {
+        return false;
+    };
+    let last = match function.kind() {
+        "identifier" if !has_self => Some(function),
+        "scoped_identifier" if !has_self => function.child_by_field_name("name"),
+        "field_expression" if has_self => function.child_by_field_name("field"),
+        _ => None,
+    };
+    last.is_some_and(|last| node_text(last, source_code) == name)
+}
+
+/// The text of `node` with the outermost of `edits` inside it applied.
+fn rewritten(source_code: &str, node: Node, edits: &[Mutation]) -> String {
+    let inside = edits
+        .iter()
+        .filter(|m| node.start_byte() <= m.start && m.end <= node.end_byte());
+    higher_order::apply_within(source_code, &outermost(inside), node.byte_range())
+}
+
+/// The edits of `edits` not nested in another one.
+fn outermost<'a>(edits: impl Iterator<Item = &'a Mutation>) -> Vec<&'a Mutation> {
+    let mut sorted: Vec<&Mutation> = edits.collect();
+    sorted.sort_by_key(|m| (m.start, std::cmp::Reverse(m.end)));
+    let mut kept: Vec<&Mutation> = Vec::new();
+    for edit in sorted {
+        if kept.last().is_none_or(|last| edit.start >= last.end) {
+            kept.push(edit);
+        }
+    }
+    kept
+}
+
+/// `edits` as one mutation of the span they cover; an edit nested in
+/// another, a call among the arguments of a call, is already part of it.
+fn combined(source_code: &str, edits: Vec<Mutation>) -> Mutation {
+    let kept = outermost(edits.iter());
+    let start = kept[0].start;
+    let end = kept.iter().map(|m| m.end).max().unwrap_or(start);
+    Mutation {
+        start,
+        end,
+        replacement: higher_order::apply_within(source_code, &kept, start..end),
+        kind: "function_item",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str, fix_calls: bool) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ParamCrusher { fix_calls }
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn reordered_duplicated_and_deleted() {
+        assert_eq!(
+            crushed("impl S { fn f(&self, a: u8, b: u16) {} }", false),
+            [
+                "impl S { fn f(&self, b: u16, a: u8) {} }",
+                "impl S { fn f(&self, a: u8, a_2: u8, b: u16) {} }",
+                "impl S { fn f(&self, a: u8, b: u16, b_2: u16) {} }",
+                "impl S { fn f(&self, b: u16) {} }",
+                "impl S { fn f(&self, a: u8) {} }",
+            ]
+        );
+        assert!(crushed("fn f() {}\nfn g(&self) {}", true).is_empty());
+    }
+
+    #[test]
+    fn nested_calls() {
+        let mutants = crushed("fn f(a: u8, b: u16) {}\nfn g() { f(f(3, 4), 5); }", true);
+        assert_eq!(
+            mutants[0],
+            "fn f(b: u16, a: u8) {}\nfn g() { f(5, f(4, 3)); }"
+        );
+        assert_eq!(
+            mutants[1],
+            "fn f(a: u8, a_2: u8, b: u16) {}\nfn g() { f(f(3, 3, 4), f(3, 3, 4), 5); }"
+        );
+        // only calls of the function change, whatever they are nested in
+        let mutants = crushed(
+            "fn f(a: u8, b: u16) {}\nfn g() { h(f(3, 4), f(5, f(6, 7))); }",
+            true,
+        );
+        assert_eq!(
+            mutants[0],
+            "fn f(b: u16, a: u8) {}\nfn g() { h(f(4, 3), f(f(7, 6), 5)); }"
+        );
+    }
+}
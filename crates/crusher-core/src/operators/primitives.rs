@@ -0,0 +1,117 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::TYPE, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+/// Integer widths every integer type is swapped to, besides its own.
+const WIDTHS: [&str; 4] = ["8", "64", "128", "size"];
+
+const SWAP: Template = Template::new(TYPE, "$primitive");
+static TEMPLATES: [Template; 1] = [SWAP];
+
+/// Swaps the widths and signedness of primitive types: `u8` becomes `u64`,
+/// `i32` becomes `u32`, `f32` becomes `f64` and `usize` becomes `isize`.
+pub struct PrimitiveCrusher;
+
+impl Mutator for PrimitiveCrusher {
+    fn name(&self) -> &'static str {
+        "primitives"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Swaps the signedness of integer types and their width for 8, 64, 128 bits or the pointer size, and `f32` for `f64` and back.",
+            node_kinds: &["primitive_type"],
+            example: "struct S { a: u8, b: i32, c: f32 }\nfn f(n: usize) -> u64 { n as u64 }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            // macro arguments are tokens, not necessarily types
+            let in_macro = node.parent().is_some_and(|p| p.kind() == "token_tree");
+            if node.kind() == "primitive_type" && !in_macro {
+                crush_primitive(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// The other signedness first, then the other widths; nothing for `bool`,
+/// `char`, `str` and the like.
+fn swapped(primitive: &str) -> Vec<String> {
+    match primitive {
+        "f32" => return vec!["f64".to_string()],
+        "f64" => return vec!["f32".to_string()],
+        _ => {}
+    }
+    let (sign, width) = primitive.split_at(1);
+    let flipped = match sign {
+        "u" => "i",
+        "i" => "u",
+        _ => return Vec::new(),
+    };
+    if !matches!(width, "8" | "16" | "32" | "64" | "128" | "size") {
+        return Vec::new();
+    }
+    let mut swapped = vec![format!("{}{}", flipped, width)];
+    swapped.extend(
+        WIDTHS
+            .iter()
+            .filter(|&&w| w != width)
+            .map(|w| format!("{}{}", sign, w)),
+    );
+    swapped
+}
+
+fn crush_primitive(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    for primitive in swapped(node_text(node, source_code)) {
+        acc.push(Mutation::new(
+            node,
+            SWAP.instantiate(&[("primitive", &primitive)]),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        PrimitiveCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn widths_and_signedness() {
+        assert_eq!(swapped("i16"), ["u16", "i8", "i64", "i128", "isize"]);
+        assert_eq!(swapped("usize"), ["isize", "u8", "u64", "u128"]);
+        assert_eq!(swapped("f64"), ["f32"]);
+        for primitive in ["bool", "char", "str", "f16", "u7"] {
+            assert!(swapped(primitive).is_empty(), "{}", primitive);
+        }
+    }
+
+    #[test]
+    fn casts_but_no_macro_arguments() {
+        assert_eq!(
+            crushed("fn f(b: bool) -> f32 { m!(u32); 1u8 as f32 }"),
+            [
+                "fn f(b: bool) -> f64 { m!(u32); 1u8 as f32 }",
+                "fn f(b: bool) -> f32 { m!(u32); 1u8 as f64 }",
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use tree_sitter::{Query, QueryCursor, QueryErrorKind, Tree};
+
+use super::{Mutation, Mutator, OperatorDoc};
+
+/// The `#set!` key of a replacement.
+const REPLACE: &str = "replace";
+
+/// The capture rewritten when a `#set!` names none.
+const TARGET: &str = "target";
+
+/// A piece of a replacement template.
+enum Piece {
+    Text(String),
+    /// The text of every node of the capture, from the first to the last.
+    Capture(u32),
+}
+
+struct Replacement {
+    target: u32,
+    pieces: Vec<Piece>,
+}
+
+/// Mutations declared in a tree-sitter query instead of Rust code: every
+/// pattern sets the replacements of a captured node, which may quote the
+/// other captures of the match.
+///
+/// ```scheme
+/// ; structs become unit structs of the same name
+/// ((struct_item name: (type_identifier) @name) @target
+///   (#set! replace "struct @name;"))
+/// ```
+///
+/// `(#set! @capture replace "...")` rewrites `@capture` instead of
+/// `@target`; a pattern may set several replacements, each one mutation, and
+/// `@@` stands for `@`. The `#eq?` and `#match?` predicates narrow the
+/// matches down as usual.
+pub struct QueryCrusher {
+    name: &'static str,
+    summary: &'static str,
+    query: Query,
+    /// By pattern index.
+    replacements: Vec<Vec<Replacement>>,
+}
+
+impl QueryCrusher {
+    /// The operator `name` of the query `source`, or why it is not one:
+    /// a syntax error, a pattern without replacement or a replacement
+    /// quoting an unknown capture.
+    pub fn new(name: &'static str, source: &str) -> Result<QueryCrusher, String> {
+        let query = Query::new(&tree_sitter_rust::language(), source).map_err(|err| {
+            let what = match err.kind {
+                QueryErrorKind::NodeType => "unknown node kind ",
+                QueryErrorKind::Field => "unknown field ",
+                QueryErrorKind::Capture => "unknown capture ",
+                QueryErrorKind::Structure => "impossible pattern ",
+                _ => "",
+            };
+            format!(
+                "{}:{}: {}{}",
+                err.row + 1,
+                err.column + 1,
+                what,
+                err.message.trim()
+            )
+        })?;
+        let mut replacements = Vec::with_capacity(query.pattern_count());
+        for pattern in 0..query.pattern_count() {
+            let line = line_of(source, query.start_byte_for_pattern(pattern));
+            let mut pattern_replacements = Vec::new();
+            for property in query.property_settings(pattern) {
+                if &*property.key != REPLACE {
+                    continue;
+                }
+                let target = match property.capture_id {
+                    Some(id) => id as u32,
+                    None => query
+                        .capture_index_for_name(TARGET)
+                        .ok_or_else(|| format!("{}: the pattern captures no @{}", line, TARGET))?,
+                };
+                let template = property.value.as_deref().unwrap_or_default();
+                pattern_replacements.push(Replacement {
+                    target,
+                    pieces: pieces(&query, template).map_err(|err| format!("{}: {}", line, err))?,
+                });
+            }
+            if pattern_replacements.is_empty() {
+                return Err(format!(
+                    "{}: the pattern sets no replacement, add (#set! {} \"...\")",
+                    line, REPLACE
+                ));
+            }
+            replacements.push(pattern_replacements);
+        }
+        // `;` comment lines at the top describe the operator
+        let summary = source
+            .lines()
+            .map_while(|line| line.trim_start().strip_prefix(';'))
+            .map(|line| line.trim_start_matches(';').trim())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(QueryCrusher {
+            name,
+            // leaked, as templates are: operators live as long as the run
+            summary: String::leak(summary),
+            query,
+            replacements,
+        })
+    }
+}
+
+/// Line of `offset` in `source`, counting from 1.
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+fn pieces(query: &Query, template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut rest = template;
+    while let Some(at) = rest.find('@') {
+        text.push_str(&rest[..at]);
+        rest = &rest[at + 1..];
+        if let Some(after) = rest.strip_prefix('@') {
+            text.push('@');
+            rest = after;
+            continue;
+        }
+        let len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(rest.len());
+        let name = &rest[..len];
+        let Some(index) = query.capture_index_for_name(name) else {
+            return Err(format!(
+                "the replacement quotes an unknown capture @{}",
+                name
+            ));
+        };
+        if !text.is_empty() {
+            pieces.push(Piece::Text(std::mem::take(&mut text)));
+        }
+        pieces.push(Piece::Capture(index));
+        rest = &rest[len..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    Ok(pieces)
+}
+
+impl Mutator for QueryCrusher {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: self.summary,
+            node_kinds: &[],
+            example: "",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        let mut cursor = QueryCursor::new();
+        for found in cursor.matches(&self.query, tree.root_node(), source_code.as_bytes()) {
+            let nodes = |index: u32| found.captures.iter().filter(move |c| c.index == index);
+            for replacement in &self.replacements[found.pattern_index] {
+                let Some(target) = nodes(replacement.target).next() else {
+                    continue;
+                };
+                let mut text = String::new();
+                for piece in &replacement.pieces {
+                    match piece {
+                        Piece::Text(piece) => text.push_str(piece),
+                        Piece::Capture(index) => {
+                            let mut captured = nodes(*index).map(|c| c.node);
+                            if let Some(first) = captured.next() {
+                                let last = captured.next_back().unwrap_or(first);
+                                text.push_str(&source_code[first.start_byte()..last.end_byte()]);
+                            }
+                        }
+                    }
+                }
+                acc.push(Mutation::new(target.node, text));
+            }
+        }
+        acc
+    }
+}
@@ -0,0 +1,187 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::TYPE, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const OWN: Template = Template::new(TYPE, "$name$args");
+const BOXED: Template = Template::new(TYPE, "Box<Self>");
+static TEMPLATES: [Template; 2] = [OWN, BOXED];
+
+/// Makes structs contain themselves: rewrites field types to the struct's
+/// own type, unboxed, or to `Box<Self>`, and makes consecutive structs
+/// contain each other.
+pub struct RecursiveCrusher;
+
+impl Mutator for RecursiveCrusher {
+    fn name(&self) -> &'static str {
+        "recursive"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Rewrites struct field types to the struct itself, unboxed or as `Box<Self>`, and makes consecutive structs contain each other.",
+            node_kinds: &["struct_item"],
+            example: "struct A<T> { t: T, n: u8 }\nstruct B(u8);\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut structs = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.kind() == "struct_item" {
+                structs.push(node);
+            }
+        });
+        let mut acc = Vec::new();
+        for &node in &structs {
+            let own = own_type(source_code, node);
+            for field in field_types(node) {
+                acc.push(Mutation::new(
+                    field,
+                    OWN.instantiate(&[("name", &own.0), ("args", &own.1)]),
+                ));
+                acc.push(Mutation::new(field, BOXED.instantiate(&[])));
+            }
+        }
+        for pair in structs.windows(2) {
+            if let Some(mutation) = mutual(source_code, pair[0], pair[1]) {
+                acc.push(mutation);
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// The name of the struct and its parameters as arguments, `("S", "<'a, T>")`.
+fn own_type(source_code: &str, node: Node) -> (String, String) {
+    let name = node
+        .child_by_field_name("name")
+        .map_or("", |n| node_text(n, source_code));
+    let Some(params) = node.child_by_field_name("type_parameters") else {
+        return (name.to_string(), String::new());
+    };
+    let mut args = Vec::new();
+    let mut cursor = params.walk();
+    for param in params.named_children(&mut cursor) {
+        if let Some(arg) = parameter_name(param) {
+            args.push(node_text(arg, source_code));
+        }
+    }
+    (name.to_string(), format!("<{}>", args.join(", ")))
+}
+
+/// The name a generic parameter is used by, through its bounds and
+/// default: `T` of `T: Copy = u8`.
+fn parameter_name(param: Node) -> Option<Node> {
+    match param.kind() {
+        "lifetime" | "type_identifier" => Some(param),
+        "constrained_type_parameter" => parameter_name(param.child_by_field_name("left")?),
+        "optional_type_parameter" | "const_parameter" => {
+            parameter_name(param.child_by_field_name("name")?)
+        }
+        "identifier" => Some(param),
+        _ => None,
+    }
+}
+
+/// The type of every named and tuple field of the struct `node`.
+fn field_types(node: Node) -> Vec<Node> {
+    let Some(body) = node.child_by_field_name("body") else {
+        return Vec::new();
+    };
+    let mut types = Vec::new();
+    let mut cursor = body.walk();
+    match body.kind() {
+        "field_declaration_list" => {
+            for field in body.named_children(&mut cursor) {
+                types.extend(field.child_by_field_name("type"));
+            }
+        }
+        _ => types.extend(body.children_by_field_name("type", &mut cursor)),
+    }
+    types
+}
+
+/// `first`'s first field of type `second` and `second`'s of type `first`,
+/// as one edit spanning both; generic structs are left out, their
+/// arguments are not in scope in the other, and so are structs of
+/// different scopes.
+fn mutual(source_code: &str, first: Node, second: Node) -> Option<Mutation> {
+    if first.parent() != second.parent()
+        || first.child_by_field_name("type_parameters").is_some()
+        || second.child_by_field_name("type_parameters").is_some()
+    {
+        return None;
+    }
+    let (a, b) = (*field_types(first).first()?, *field_types(second).first()?);
+    // a struct inside the array length of another
+    if a.end_byte() > b.start_byte() {
+        return None;
+    }
+    let name = |node: Node| {
+        node.child_by_field_name("name")
+            .map(|n| node_text(n, source_code))
+    };
+    let replacement = format!(
+        "{}{}{}",
+        OWN.instantiate(&[("name", name(second)?), ("args", "")]),
+        &source_code[a.end_byte()..b.start_byte()],
+        OWN.instantiate(&[("name", name(first)?), ("args", "")]),
+    );
+    Some(Mutation {
+        start: a.start_byte(),
+        end: b.end_byte(),
+        replacement,
+        kind: "struct_item",
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        RecursiveCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn own_type_with_parameters() {
+        assert_eq!(
+            crushed("struct S<'a, T: Copy = u8, const N: usize>(&'a [T; N]);"),
+            [
+                "struct S<'a, T: Copy = u8, const N: usize>(S<'a, T, N>);",
+                "struct S<'a, T: Copy = u8, const N: usize>(Box<Self>);",
+            ]
+        );
+    }
+
+    #[test]
+    fn mutual_within_one_scope() {
+        assert_eq!(
+            crushed("struct A { a: u8 }\nstruct B(pub u8);"),
+            [
+                "struct A { a: A }\nstruct B(pub u8);",
+                "struct A { a: Box<Self> }\nstruct B(pub u8);",
+                "struct A { a: u8 }\nstruct B(pub B);",
+                "struct A { a: u8 }\nstruct B(pub Box<Self>);",
+                "struct A { a: B }\nstruct B(pub A);",
+            ]
+        );
+        assert_eq!(
+            crushed("mod m { struct A { a: u8 } }\nstruct B(u8);").len(),
+            4
+        );
+        assert_eq!(crushed("struct A<T>(T);\nstruct B(u8);").len(), 4);
+    }
+}
@@ -0,0 +1,208 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::TYPE, for_each_node, node_text, single_type_argument, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+
+/// `mut` in and out of a reference, which keeps its lifetime.
+const MUT_INSERT: Template = Template::new("type _T = &@@u8;", "mut ");
+const MUT_DELETE: Template = Template::new("type _T = &mut @@u8;", "");
+const SHARED: Template = Template::new(TYPE, "&$type");
+const UNIQUE: Template = Template::new(TYPE, "&mut $type");
+const STATIC: Template = Template::new(TYPE, "&'static $type");
+const CONST_POINTER: Template = Template::new(TYPE, "*const $type");
+const MUT_POINTER: Template = Template::new(TYPE, "*mut $type");
+const BOXED: Template = Template::new(TYPE, "Box<$type>");
+const UNWRAPPED: Template = Template::new(TYPE, "$type");
+static TEMPLATES: [Template; 9] = [
+    MUT_INSERT,
+    MUT_DELETE,
+    SHARED,
+    UNIQUE,
+    STATIC,
+    CONST_POINTER,
+    MUT_POINTER,
+    BOXED,
+    UNWRAPPED,
+];
+
+/// Mutates references, raw pointers and boxes: toggles `mut`, turns
+/// references into pointers and back, boxes into `&'static` references, and
+/// adds or removes a level of indirection.
+pub struct ReferenceCrusher;
+
+impl Mutator for ReferenceCrusher {
+    fn name(&self) -> &'static str {
+        "references"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Toggles `mut` on references and raw pointers, turns `&T` into `*const T` and back, `&T` into `Box<T>` and `Box<T>` into `&'static T`, and adds or removes a level of indirection.",
+            node_kinds: &["reference_type", "pointer_type", "generic_type"],
+            example: "fn f<'a>(a: &'a u8, b: *mut u8) -> Box<u8> { Box::new(*a) }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "reference_type" => crush_reference(source_code, node, &mut acc),
+            "pointer_type" => crush_pointer(source_code, node, &mut acc),
+            "generic_type" => crush_box(source_code, node, &mut acc),
+            _ => {}
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn mutable_specifier(node: Node) -> Option<Node> {
+    let mut cursor = node.walk();
+    let specifier = node
+        .named_children(&mut cursor)
+        .find(|child| child.kind() == "mutable_specifier");
+    specifier
+}
+
+fn crush_reference(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(inner) = node.child_by_field_name("type") else {
+        return;
+    };
+    let inner_text = node_text(inner, source_code);
+    let is_mut = match mutable_specifier(node) {
+        Some(specifier) => {
+            acc.push(Mutation {
+                start: specifier.start_byte(),
+                end: inner.start_byte(),
+                replacement: MUT_DELETE.instantiate(&[]),
+                kind: node.kind(),
+            });
+            true
+        }
+        None => {
+            acc.push(Mutation {
+                start: inner.start_byte(),
+                end: inner.start_byte(),
+                replacement: MUT_INSERT.instantiate(&[]),
+                kind: node.kind(),
+            });
+            false
+        }
+    };
+    let pointer = match is_mut {
+        true => MUT_POINTER,
+        false => CONST_POINTER,
+    };
+    let text = node_text(node, source_code);
+    for (template, ty) in [
+        (pointer, inner_text),
+        (BOXED, inner_text),
+        (UNWRAPPED, inner_text),
+        (SHARED, text),
+    ] {
+        acc.push(Mutation::new(node, template.instantiate(&[("type", ty)])));
+    }
+}
+
+fn crush_pointer(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(inner) = node.child_by_field_name("type") else {
+        return;
+    };
+    let inner_text = node_text(inner, source_code);
+    let (toggled, reference) = match mutable_specifier(node) {
+        Some(_) => (CONST_POINTER, UNIQUE),
+        None => (MUT_POINTER, SHARED),
+    };
+    let text = node_text(node, source_code);
+    for (template, ty) in [
+        (toggled, inner_text),
+        (reference, inner_text),
+        (UNWRAPPED, inner_text),
+        (CONST_POINTER, text),
+    ] {
+        acc.push(Mutation::new(node, template.instantiate(&[("type", ty)])));
+    }
+}
+
+/// `Box<T>`, by any path ending in `Box`, with a single type argument.
+fn crush_box(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(inner) = single_type_argument(source_code, node, "Box") else {
+        return;
+    };
+    let inner_text = node_text(inner, source_code);
+    let text = node_text(node, source_code);
+    for (template, ty) in [(STATIC, inner_text), (UNWRAPPED, inner_text), (BOXED, text)] {
+        acc.push(Mutation::new(node, template.instantiate(&[("type", ty)])));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ReferenceCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn references_keep_their_lifetime() {
+        assert_eq!(
+            crushed("type T<'a> = &'a mut u8;"),
+            [
+                "type T<'a> = &'a u8;",
+                "type T<'a> = *mut u8;",
+                "type T<'a> = Box<u8>;",
+                "type T<'a> = u8;",
+                "type T<'a> = &&'a mut u8;",
+            ]
+        );
+        assert_eq!(
+            crushed("type T<'a> = &'a u8;")[0],
+            "type T<'a> = &'a mut u8;"
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        assert_eq!(
+            crushed("type P = *const u8;"),
+            [
+                "type P = *mut u8;",
+                "type P = &u8;",
+                "type P = u8;",
+                "type P = *const *const u8;",
+            ]
+        );
+        assert_eq!(
+            crushed("type P = *mut u8;")[..2],
+            ["type P = *const u8;", "type P = &mut u8;"]
+        );
+    }
+
+    #[test]
+    fn boxes_but_no_box_paths() {
+        assert_eq!(
+            crushed("type B = std::boxed::Box</* c */ u8>;"),
+            [
+                "type B = &'static u8;",
+                "type B = u8;",
+                "type B = Box<std::boxed::Box</* c */ u8>>;",
+            ]
+        );
+        assert!(crushed("fn f() { Box::<u8>::new(1); }").is_empty());
+        assert!(crushed("type B = Box<u8, A>; type C = Box<'a>;").is_empty());
+    }
+}
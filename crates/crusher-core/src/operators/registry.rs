@@ -0,0 +1,56 @@
+use super::{all, Mutator};
+
+/// The operators a front end can select by name: the built-in ones and
+/// whatever a downstream crate registers next to them.
+pub struct Registry {
+    mutators: Vec<Box<dyn Mutator>>,
+}
+
+impl Registry {
+    /// No operators at all.
+    pub fn empty() -> Registry {
+        Registry {
+            mutators: Vec::new(),
+        }
+    }
+
+    /// The operators of [`all`].
+    pub fn builtin() -> Registry {
+        Registry { mutators: all() }
+    }
+
+    /// Add `mutator` after the operators registered so far.
+    ///
+    /// # Panics
+    ///
+    /// If an operator of the same name is registered already: names are
+    /// what the command line selects operators by.
+    pub fn register(&mut self, mutator: Box<dyn Mutator>) -> &mut Registry {
+        assert!(
+            self.get(mutator.name()).is_none(),
+            "operator {:?} is registered twice",
+            mutator.name()
+        );
+        self.mutators.push(mutator);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Mutator> {
+        self.iter().find(|m| m.name() == name)
+    }
+
+    /// In registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Mutator> {
+        self.mutators.iter().map(Box::as_ref)
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.iter().map(|m| m.name()).collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Registry {
+        Registry::builtin()
+    }
+}
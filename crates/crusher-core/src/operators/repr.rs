@@ -0,0 +1,161 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::ITEM, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+/// Representations tried on every ADT: valid ones, odd or oversized
+/// alignments, and combinations rustc rejects.
+const REPRS: [&str; 12] = [
+    "C",
+    "packed",
+    "packed(3)",
+    "transparent",
+    "u8",
+    "i128",
+    "align(3)",
+    "align(0)",
+    "align(1073741824)",
+    "C, packed",
+    "packed, align(8)",
+    "transparent, C",
+];
+
+const ARGUMENTS: &str = "#[repr(@@)]\nstruct S;";
+const INSERT: Template = Template::new("@@struct S;", "#[repr($repr)]\n");
+const DELETE: Template = Template::new(ITEM, "");
+const REPLACE: Template = Template::new(ARGUMENTS, "$repr");
+static TEMPLATES: [Template; 3] = [INSERT, DELETE, REPLACE];
+
+/// Mutates the layout of structs, enums and unions: adds `#[repr(..)]`
+/// attributes, deletes the existing ones and swaps their arguments.
+pub struct ReprCrusher;
+
+impl Mutator for ReprCrusher {
+    fn name(&self) -> &'static str {
+        "repr"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Adds `#[repr(..)]` to structs, enums and unions, with odd alignments and conflicting hints, deletes existing ones and swaps their arguments.",
+            node_kinds: &["struct_item", "enum_item", "union_item", "attribute_item"],
+            example: "#[repr(C)]\nstruct S { a: u8, b: u32 }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if matches!(node.kind(), "struct_item" | "enum_item" | "union_item") {
+                crush_repr(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_repr(source_code: &str, item: Node, acc: &mut Vec<Mutation>) {
+    // the item keeps its indentation on the line after the attribute
+    let line = &source_code[..item.start_byte()];
+    let indent = &line[line.rfind('\n').map_or(0, |i| i + 1)..];
+    let indent = if indent.trim().is_empty() { indent } else { "" };
+    for repr in REPRS {
+        acc.push(Mutation {
+            start: item.start_byte(),
+            end: item.start_byte(),
+            replacement: INSERT.instantiate(&[("repr", repr)]) + indent,
+            kind: item.kind(),
+        });
+    }
+
+    // the outer attributes are siblings before the item
+    let mut sibling = item.prev_sibling();
+    while let Some(attribute_item) = sibling {
+        match attribute_item.kind() {
+            "attribute_item" => crush_attribute(source_code, attribute_item, acc),
+            "line_comment" | "block_comment" => {}
+            _ => break,
+        }
+        sibling = attribute_item.prev_sibling();
+    }
+}
+
+fn crush_attribute(source_code: &str, item: Node, acc: &mut Vec<Mutation>) {
+    let Some(attribute) = item.named_child(0).filter(|a| a.kind() == "attribute") else {
+        return;
+    };
+    let is_repr = attribute
+        .named_child(0)
+        .is_some_and(|n| node_text(n, source_code) == "repr");
+    let Some(arguments) = attribute.child_by_field_name("arguments") else {
+        return;
+    };
+    if !is_repr || arguments.child_count() < 2 {
+        return;
+    }
+    acc.push(Mutation::new(item, DELETE.instantiate(&[])));
+    // inside the parentheses
+    let start = arguments.child(0).unwrap().end_byte();
+    let end = arguments
+        .child(arguments.child_count() - 1)
+        .unwrap()
+        .start_byte();
+    let current = source_code[start..end].trim();
+    for repr in REPRS.into_iter().filter(|&repr| repr != current) {
+        acc.push(Mutation {
+            start,
+            end,
+            replacement: REPLACE.instantiate(&[("repr", repr)]),
+            kind: "token_tree",
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        ReprCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn inserted_at_the_item_indentation() {
+        let mutants = crushed("mod m {\n    #[derive(Debug)]\n    pub union U { a: u8 }\n}\n");
+        assert_eq!(mutants.len(), REPRS.len());
+        assert_eq!(
+            mutants[0],
+            "mod m {\n    #[derive(Debug)]\n    #[repr(C)]\n    pub union U { a: u8 }\n}\n"
+        );
+        assert_eq!(crushed("enum E { A }")[1], "#[repr(packed)]\nenum E { A }");
+    }
+
+    #[test]
+    fn existing_reprs_swapped() {
+        let source_code = "#[repr(C)]\n/* c */\n#[repr(align(8))]\nstruct S;\n";
+        let mutants = crushed(source_code);
+        // the closest attribute first, each repr but the one already there
+        let swapped = &mutants[REPRS.len()..];
+        assert_eq!(swapped.len(), (1 + REPRS.len()) + REPRS.len());
+        assert_eq!(swapped[0], "#[repr(C)]\n/* c */\n\nstruct S;\n");
+        assert_eq!(swapped[1], "#[repr(C)]\n/* c */\n#[repr(C)]\nstruct S;\n");
+        let swapped = &swapped[1 + REPRS.len()..];
+        assert_eq!(swapped[0], "\n/* c */\n#[repr(align(8))]\nstruct S;\n");
+        assert_eq!(
+            swapped[1],
+            "#[repr(packed)]\n/* c */\n#[repr(align(8))]\nstruct S;\n"
+        );
+        assert!(!mutants.contains(&source_code.to_string()));
+    }
+}
@@ -0,0 +1,206 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::ITEM, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const TO_ENUM: Template = Template::new(ITEM, "enum $name$generics$where { $name$fields }");
+const TO_STRUCT: Template = Template::new(ITEM, "struct $name$generics$where$fields");
+const TO_TUPLE_STRUCT: Template = Template::new(ITEM, "struct $name$generics$tuple$where;");
+const TO_UNIT_STRUCT: Template = Template::new(ITEM, "struct $name$generics$where;");
+static TEMPLATES: [Template; 4] = [TO_ENUM, TO_STRUCT, TO_TUPLE_STRUCT, TO_UNIT_STRUCT];
+
+/// Turns every struct into a single-variant enum of the same name, and
+/// every enum into a struct with the fields of one of its variants.
+pub struct StructEnumCrusher;
+
+impl Mutator for StructEnumCrusher {
+    fn name(&self) -> &'static str {
+        "struct-enum"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Turns every struct into a single-variant enum of the same name, and every enum into a struct with the fields of one of its variants.",
+            node_kinds: &["struct_item", "enum_item"],
+            example: "pub struct S<T> { pub t: T }\nenum E { A(u8), B { b: bool }, C }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "struct_item" => struct_to_enum(source_code, node, &mut acc),
+            "enum_item" => enum_to_structs(source_code, node, &mut acc),
+            _ => {}
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// What the conversions keep of an item, as template arguments.
+struct Parts<'s> {
+    /// From the `struct` or `enum` keyword on; visibility and attributes
+    /// before it stay as they are.
+    start: usize,
+    name: &'s str,
+    generics: &'s str,
+    /// With a leading space, or empty.
+    where_clause: String,
+}
+
+fn parts<'s>(source_code: &'s str, node: Node, keyword: &str) -> Option<Parts<'s>> {
+    let mut cursor = node.walk();
+    let start = node
+        .children(&mut cursor)
+        .find(|c| c.kind() == keyword)?
+        .start_byte();
+    let mut cursor = node.walk();
+    let where_clause = node
+        .children(&mut cursor)
+        .find(|c| c.kind() == "where_clause")
+        .map(|w| format!(" {}", node_text(w, source_code)))
+        .unwrap_or_default();
+    Some(Parts {
+        start,
+        name: node_text(node.child_by_field_name("name")?, source_code),
+        generics: node
+            .child_by_field_name("type_parameters")
+            .map_or("", |g| node_text(g, source_code)),
+        where_clause,
+    })
+}
+
+fn struct_to_enum(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let Some(parts) = parts(source_code, node, "struct") else {
+        return;
+    };
+    let fields = match node.child_by_field_name("body") {
+        Some(body) if body.kind() == "field_declaration_list" => {
+            format!(" {}", private_fields(source_code, body))
+        }
+        Some(body) => private_fields(source_code, body),
+        None => String::new(),
+    };
+    let replacement = TO_ENUM.instantiate(&[
+        ("name", parts.name),
+        ("generics", parts.generics),
+        ("where", &parts.where_clause),
+        ("fields", &fields),
+    ]);
+    acc.push(Mutation {
+        start: parts.start,
+        end: node.end_byte(),
+        replacement,
+        kind: node.kind(),
+    });
+}
+
+/// A struct of the fields of every variant in turn.
+fn enum_to_structs(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let (Some(parts), Some(body)) = (
+        parts(source_code, node, "enum"),
+        node.child_by_field_name("body"),
+    ) else {
+        return;
+    };
+    let mut cursor = body.walk();
+    for variant in body.named_children(&mut cursor) {
+        if variant.kind() != "enum_variant" {
+            continue;
+        }
+        let mut args = vec![
+            ("name", parts.name),
+            ("generics", parts.generics),
+            ("where", parts.where_clause.as_str()),
+        ];
+        let (template, fields) = match variant.child_by_field_name("body") {
+            Some(fields) if fields.kind() == "field_declaration_list" => {
+                (TO_STRUCT, format!(" {}", node_text(fields, source_code)))
+            }
+            Some(fields) => (TO_TUPLE_STRUCT, node_text(fields, source_code).to_string()),
+            None => (TO_UNIT_STRUCT, String::new()),
+        };
+        args.push(("fields", &fields));
+        args.push(("tuple", &fields));
+        acc.push(Mutation {
+            start: parts.start,
+            end: node.end_byte(),
+            replacement: template.instantiate(&args),
+            kind: node.kind(),
+        });
+    }
+}
+
+/// The field list `body` without the visibility of its fields, which enum
+/// variants do not take.
+fn private_fields(source_code: &str, body: Node) -> String {
+    let mut out = String::new();
+    let mut at = body.start_byte();
+    let mut cursor = body.walk();
+    for field in body.named_children(&mut cursor) {
+        // tuple fields are no nodes of their own
+        let mut cursor = field.walk();
+        let visibility = match field.kind() {
+            "visibility_modifier" => Some(field),
+            _ => field
+                .children(&mut cursor)
+                .find(|c| c.kind() == "visibility_modifier"),
+        };
+        if let Some(visibility) = visibility {
+            out.push_str(&source_code[at..visibility.start_byte()]);
+            at = visibility.end_byte();
+            // with the blank after it
+            at += source_code[at..].len() - source_code[at..].trim_start().len();
+        }
+    }
+    out.push_str(&source_code[at..body.end_byte()]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        StructEnumCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn structs_lose_field_visibility() {
+        assert_eq!(
+            crushed("pub struct T<X>(pub(crate) X, u8) where X: Copy;"),
+            ["pub enum T<X> where X: Copy { T(X, u8) }"]
+        );
+        assert_eq!(
+            crushed(
+                "#[derive(Debug)] struct F { #[doc = \"x\"] pub a: u8, /* c */ pub(crate) b: u8 }"
+            ),
+            ["#[derive(Debug)] enum F { F { #[doc = \"x\"] a: u8, /* c */ b: u8 } }"]
+        );
+        assert_eq!(crushed("struct U;"), ["enum U { U }"]);
+    }
+
+    #[test]
+    fn one_struct_per_variant() {
+        assert_eq!(
+            crushed("enum E<T> where T: Copy { A(T), B { pub b: bool } = 3, C = 1 }"),
+            [
+                "struct E<T>(T) where T: Copy;",
+                "struct E<T> where T: Copy { pub b: bool }",
+                "struct E<T> where T: Copy;",
+            ]
+        );
+        assert!(crushed("enum Never {}").is_empty());
+    }
+}
@@ -0,0 +1,1356 @@
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use tree_sitter::{Node, Tree, TreeCursor};
+
+use super::{
+    context::{EXPRESSION, ITEM, STATEMENT},
+    for_each_node, node_text, Mutation, MutationSite, Mutator, OperatorDoc, Template,
+};
+use crate::higher_order;
+
+/// The form of a struct, and that of its crushed declaration: `struct S;`,
+/// `struct S();` or `struct S {}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructForm {
+    /// `struct S;`
+    Unit,
+    /// `struct S();`
+    Tuple,
+    /// `struct S {}`
+    Struct,
+}
+
+impl StructForm {
+    /// What a struct of this form is crushed into when no forms are chosen:
+    /// a unit struct gets parentheses, a tuple struct loses its fields and a
+    /// struct with named fields becomes a unit struct.
+    pub fn crushed(self) -> StructForm {
+        match self {
+            StructForm::Unit | StructForm::Tuple => StructForm::Tuple,
+            StructForm::Struct => StructForm::Unit,
+        }
+    }
+
+    fn declaration(self) -> Template {
+        match self {
+            StructForm::Unit => UNIT,
+            StructForm::Tuple => TUPLE,
+            StructForm::Struct => BRACED,
+        }
+    }
+
+    /// How a struct of this form is constructed and matched without fields.
+    fn empty_use(self) -> Template {
+        match self {
+            StructForm::Unit => UNIT_USE,
+            StructForm::Tuple => TUPLE_USE,
+            StructForm::Struct => BRACED_USE,
+        }
+    }
+}
+
+/// What a struct is crushed into: an empty struct of one of the forms, or
+/// one with half or twice its fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Crush {
+    /// `struct S;`
+    Unit,
+    /// `struct S();`
+    Tuple,
+    /// `struct S {}`
+    Braced,
+    /// The first half of the fields, for structs with two or more
+    Halved,
+    /// Every field twice, the copies of named ones renamed `<name>_2`
+    Duplicated,
+}
+
+impl Crush {
+    /// The empty form of the struct after the crush, `None` for the crushes
+    /// keeping fields.
+    pub fn form(self) -> Option<StructForm> {
+        match self {
+            Crush::Unit => Some(StructForm::Unit),
+            Crush::Tuple => Some(StructForm::Tuple),
+            Crush::Braced => Some(StructForm::Struct),
+            Crush::Halved | Crush::Duplicated => None,
+        }
+    }
+}
+
+impl From<StructForm> for Crush {
+    fn from(form: StructForm) -> Crush {
+        match form {
+            StructForm::Unit => Crush::Unit,
+            StructForm::Tuple => Crush::Tuple,
+            StructForm::Struct => Crush::Braced,
+        }
+    }
+}
+
+/// A struct of the file: where it is, its form and its name.
+pub type StructInfo = (MutationSite, StructForm, String);
+
+/// Every struct of the file, in source order.
+pub fn find_all_structs(source_code: &str, tree: &Tree) -> Vec<StructInfo> {
+    let mut found = Vec::new();
+    visit_vertical(source_code, &mut tree.walk(), &mut found);
+    found
+}
+
+fn visit_vertical(source_code: &str, cursor: &mut TreeCursor, acc: &mut Vec<StructInfo>) {
+    if cursor.goto_first_child() {
+        visit_horizontal(source_code, cursor, acc);
+        cursor.goto_parent();
+    }
+}
+
+fn visit_horizontal(source_code: &str, cursor: &mut TreeCursor, acc: &mut Vec<StructInfo>) {
+    loop {
+        find_structs(source_code, cursor, acc);
+
+        visit_vertical(source_code, cursor, acc);
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+pub fn find_structs(source_code: &str, cursor: &mut TreeCursor, acc: &mut Vec<StructInfo>) {
+    let node = cursor.node();
+    if node.kind() == "struct_item" {
+        let struct_name = node
+            .child_by_field_name("name")
+            .map(|n| n.utf8_text(source_code.as_bytes()).unwrap().to_string())
+            .unwrap_or_default();
+
+        let struct_form = determine_struct_form(cursor);
+
+        let struct_info: StructInfo = (MutationSite::of(node), struct_form, struct_name);
+        tracing::trace!(?struct_info, "struct");
+        acc.push(struct_info);
+    }
+}
+
+/// The form of the `struct_item` under `cursor`, from the kind of its body.
+pub fn determine_struct_form(cursor: &mut TreeCursor) -> StructForm {
+    let node = cursor.node();
+    match node.child_by_field_name("body").map(|body| body.kind()) {
+        Some("field_declaration_list") => StructForm::Struct,
+        Some("ordered_field_declaration_list") => StructForm::Tuple,
+        _ => StructForm::Unit,
+    }
+}
+
+const UNIT: Template = Template::new(ITEM, "struct $name;");
+const TUPLE: Template = Template::new(ITEM, "struct $name();");
+const BRACED: Template = Template::new(ITEM, "struct $name {}");
+const UNIT_USE: Template = Template::new(EXPRESSION, "$name");
+const TUPLE_USE: Template = Template::new(EXPRESSION, "$name()");
+const BRACED_USE: Template = Template::new(EXPRESSION, "$name {}");
+const FIELD_USE: Template = Template::new(EXPRESSION, "todo!()");
+const BINDING: Template = Template::new(STATEMENT, "let $binding = todo!();");
+const TYPED_BINDING: Template = Template::new(STATEMENT, "let $binding: $type = todo!();");
+static TEMPLATES: [Template; 9] = [
+    UNIT,
+    TUPLE,
+    BRACED,
+    UNIT_USE,
+    TUPLE_USE,
+    BRACED_USE,
+    FIELD_USE,
+    BINDING,
+    TYPED_BINDING,
+];
+
+/// Every struct crushed in each of the ways of `crushes`, or into
+/// [`StructForm::crushed`] of its form when there are none, with the struct
+/// and the crush; the empty forms with or without the generic parameters of
+/// the struct as `generics` has it. Halving and duplicating skip structs
+/// without the fields for them.
+pub fn modify_structs<'i>(
+    source_code: &str,
+    tree: &Tree,
+    structs: &'i [StructInfo],
+    crushes: &[Crush],
+    generics: KeepGenerics,
+) -> Vec<(&'i StructInfo, Crush, Mutation)> {
+    let mut mutations = Vec::new();
+    for info in structs {
+        let crushed = [Crush::from(info.1.crushed())];
+        let crushes = match crushes.is_empty() {
+            true => &crushed[..],
+            false => crushes,
+        };
+        let parameters = type_parameters(source_code, tree, info);
+        let kept: &[&str] = match (generics, parameters) {
+            (KeepGenerics::Drop, _) | (_, "") => &[""],
+            (KeepGenerics::Keep, _) => &[parameters],
+            (KeepGenerics::Both, _) => &["", parameters],
+        };
+        for &crush in crushes {
+            match crush.form() {
+                Some(form) => mutations.extend(
+                    kept.iter()
+                        .map(|generics| (info, crush, crush_struct(info, form, generics))),
+                ),
+                None => mutations.extend(
+                    resize_fields(source_code, tree, info, crush).map(|m| (info, crush, m)),
+                ),
+            }
+        }
+    }
+    mutations
+}
+
+/// The declaration of the struct of `info` replaced by an empty one of
+/// `form`, with the generic parameter list `generics` after its name.
+pub fn crush_struct((site, _, name): &StructInfo, form: StructForm, generics: &str) -> Mutation {
+    Mutation {
+        start: site.start_byte,
+        end: site.end_byte,
+        replacement: form
+            .declaration()
+            .instantiate(&[("name", &format!("{}{}", name, generics))]),
+        kind: "struct_item",
+    }
+}
+
+/// The generic parameter list of the struct of `info`, as written, or an
+/// empty string.
+fn type_parameters<'s>(source_code: &'s str, tree: &Tree, (site, _, _): &StructInfo) -> &'s str {
+    tree.root_node()
+        .descendant_for_byte_range(site.start_byte, site.end_byte)
+        .and_then(|item| item.child_by_field_name("type_parameters"))
+        .map_or("", |parameters| node_text(parameters, source_code))
+}
+
+/// The declaration of the struct of `info` with the first half of its
+/// fields under [`Crush::Halved`], or every field twice under
+/// [`Crush::Duplicated`]; `None` when it has too few fields for it.
+fn resize_fields(
+    source_code: &str,
+    tree: &Tree,
+    (site, _, _): &StructInfo,
+    crush: Crush,
+) -> Option<Mutation> {
+    let item = tree
+        .root_node()
+        .descendant_for_byte_range(site.start_byte, site.end_byte)?;
+    let fields = fields(item.child_by_field_name("body")?);
+    let (last, _, last_comma) = fields.last()?;
+    let edits = match crush {
+        Crush::Halved if fields.len() >= 2 => {
+            let kept = fields.len() / 2;
+            let mut edits: Vec<Mutation> = fields[kept..]
+                .iter()
+                .map(|(span, _, comma)| {
+                    let end = comma.as_ref().map_or(span.end, |comma| comma.end);
+                    removal(source_code, span.start, end)
+                })
+                .collect();
+            // without a trailing comma, the one after the last kept field goes
+            if last_comma.is_none() {
+                if let Some(comma) = &fields[kept - 1].2 {
+                    edits.push(removal(source_code, comma.start, comma.end));
+                }
+            }
+            edits
+        }
+        Crush::Duplicated => vec![Mutation {
+            start: last.end,
+            end: last.end,
+            replacement: fields
+                .iter()
+                .map(|(span, name, _)| match name {
+                    Some(name) => format!(
+                        ", {}_2{}",
+                        &source_code[span.start..name.end_byte()],
+                        &source_code[name.end_byte()..span.end]
+                    ),
+                    None => format!(", {}", &source_code[span.clone()]),
+                })
+                .collect(),
+            kind: "field_declaration",
+        }],
+        _ => return None,
+    };
+    Some(Mutation {
+        start: site.start_byte,
+        end: site.end_byte,
+        replacement: higher_order::apply_within(
+            source_code,
+            &edits.iter().collect::<Vec<_>>(),
+            site.start_byte..site.end_byte,
+        ),
+        kind: "struct_item",
+    })
+}
+
+/// The deletion of `start..end` with the blanks in front of it, and with
+/// its line when nothing else is on it; comments around it stay.
+fn removal(source_code: &str, start: usize, end: usize) -> Mutation {
+    let before = &source_code[..start];
+    let blank = before.trim_end_matches([' ', '\t']).len();
+    let rest = &source_code[end..];
+    let line_rest = rest.trim_start_matches([' ', '\t']);
+    let start = match before[..blank].ends_with('\n') {
+        true if line_rest.starts_with('\n') || line_rest.starts_with("\r\n") => before[..blank - 1]
+            .strip_suffix('\r')
+            .unwrap_or(&before[..blank - 1])
+            .len(),
+        // the indentation of a line that keeps a comment stays
+        true => start,
+        false => blank,
+    };
+    Mutation {
+        start,
+        end,
+        replacement: String::new(),
+        kind: "field_declaration",
+    }
+}
+
+/// A field of a struct body: its span with its attributes and visibility,
+/// its name if it has one and the comma after it.
+type FieldSpan<'tree> = (Range<usize>, Option<Node<'tree>>, Option<Range<usize>>);
+
+/// The fields of the struct body `body`; comments between fields are part
+/// of none of them.
+fn fields(body: Node) -> Vec<FieldSpan> {
+    let mut fields: Vec<FieldSpan> = Vec::new();
+    // whether the last field still takes the children that follow
+    let mut open = false;
+    let mut cursor = body.walk();
+    for child in body.children(&mut cursor) {
+        match child.kind() {
+            "{" | "}" | "(" | ")" => open = false,
+            "," => {
+                if let Some(field) = fields.last_mut().filter(|_| open) {
+                    field.2 = Some(child.byte_range());
+                }
+                open = false;
+            }
+            "line_comment" | "block_comment" => {}
+            _ => {
+                if !open {
+                    fields.push((child.byte_range(), None, None));
+                    open = true;
+                }
+                let (span, name, _) = fields.last_mut().unwrap();
+                span.end = child.end_byte();
+                if child.kind() == "field_declaration" {
+                    *name = child.child_by_field_name("name");
+                }
+            }
+        }
+    }
+    fields
+}
+
+/// What becomes of the attributes and doc comments before a crushed struct;
+/// those of its fields go with the fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum KeepAttrs {
+    /// They stay on the crushed struct
+    #[default]
+    Keep,
+    /// They are deleted with the struct
+    Drop,
+    /// They stay, and every `#[derive(..)]` is repeated so the derived
+    /// impls conflict
+    Conflict,
+}
+
+/// Whether the empty declaration of a crushed generic struct keeps its
+/// generic parameters, `struct S<T: Copy>;`, leaving them unused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum KeepGenerics {
+    /// They are deleted with the fields
+    #[default]
+    Drop,
+    /// They stay, with their bounds and defaults
+    Keep,
+    /// One mutant without them and one with them
+    Both,
+}
+
+/// Crushes every struct definition into an empty unit or tuple struct.
+#[derive(Default)]
+pub struct StructCrusher {
+    pub attrs: KeepAttrs,
+    pub generics: KeepGenerics,
+    /// Also rewrites the uses of the struct in the file to its new form.
+    pub fix_uses: bool,
+    /// The crushes of every struct, one mutant each but those leaving it as
+    /// it is; [`StructForm::crushed`] of its form when empty.
+    pub forms: Vec<Crush>,
+}
+
+impl Mutator for StructCrusher {
+    fn name(&self) -> &'static str {
+        "structs"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Crushes every struct definition into an empty unit or tuple struct.",
+            node_kinds: &["struct_item"],
+            example: "struct S { t: i32 }\nstruct DropMe(&'static str);\n",
+            flags: &[
+                (
+                    "--keep-attrs drop",
+                    "delete the attributes and doc comments of the struct with it",
+                ),
+                (
+                    "--keep-attrs conflict",
+                    "keep them and repeat every `#[derive(..)]` on the struct",
+                ),
+                (
+                    "--keep-generics both",
+                    "one mutant of a generic struct without its generic parameters and one keeping them unused",
+                ),
+                (
+                    "--fix-uses",
+                    "also rewrite construction sites, patterns and named field accesses to fit",
+                ),
+                (
+                    "--forms unit,tuple,braced",
+                    "crush every struct into each of these forms, `struct S;`, `struct S();` and `struct S {}`",
+                ),
+                (
+                    "--forms halved,duplicated",
+                    "also keep the first half of the fields of every struct, or repeat them all",
+                ),
+            ],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let found_structs = find_all_structs(source_code, tree);
+
+        let uses = self.fix_uses.then(|| Uses::of(source_code, tree));
+        let mut mutations = Vec::new();
+        for (info, crush, mut declaration) in modify_structs(
+            source_code,
+            tree,
+            &found_structs,
+            &self.forms,
+            self.generics,
+        ) {
+            if self.attrs != KeepAttrs::Keep {
+                crush_attrs(source_code, tree, self.attrs, &mut declaration);
+            }
+            // an empty struct of the form already
+            if declaration.is_noop(source_code) {
+                continue;
+            }
+            // the uses of halved and duplicated structs are left alone
+            mutations.push(match (&uses, crush.form()) {
+                (Some(uses), Some(form)) => {
+                    fix_uses(source_code, tree, uses, info, form, declaration)
+                }
+                _ => declaration,
+            });
+        }
+        mutations
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// Extends `declaration` over the attributes and doc comments before the
+/// struct under [`KeepAttrs::Drop`], or repeats its derives in front of it
+/// under [`KeepAttrs::Conflict`].
+fn crush_attrs(source_code: &str, tree: &Tree, attrs: KeepAttrs, declaration: &mut Mutation) {
+    let Some(item) = tree
+        .root_node()
+        .descendant_for_byte_range(declaration.start, declaration.end)
+    else {
+        return;
+    };
+    let mut derives = Vec::new();
+    let mut sibling = item.prev_sibling();
+    while let Some(node) = sibling {
+        sibling = node.prev_sibling();
+        let text = node_text(node, source_code);
+        let is_doc = match node.kind() {
+            "attribute_item" => false,
+            "line_comment" => text.starts_with("///"),
+            "block_comment" => text.starts_with("/**"),
+            _ => break,
+        };
+        // plain comments stay unless attributes before them go
+        if !is_doc && node.kind() != "attribute_item" {
+            continue;
+        }
+        if attrs == KeepAttrs::Drop {
+            declaration.start = node.start_byte();
+        } else if text.starts_with("#[derive") {
+            derives.insert(0, text);
+        }
+    }
+    let repeated: String = derives.iter().map(|d| format!("{}\n", d)).collect();
+    declaration.replacement.insert_str(0, &repeated);
+}
+
+/// The nodes [`fix_uses`] may rewrite, found in one walk of the file and
+/// filed under the name they use, so that fixing the uses of every struct
+/// does not walk the whole file again.
+#[derive(Default)]
+struct Uses<'t, 's> {
+    /// Struct expressions and patterns, calls and tuple struct patterns, by
+    /// the last segment of their path.
+    constructions: HashMap<&'s str, Vec<Node<'t>>>,
+    identifiers: HashMap<&'s str, Vec<Node<'t>>>,
+    /// Field expressions, by the field.
+    accesses: HashMap<&'s str, Vec<Node<'t>>>,
+    /// How many structs of the file declare every named field.
+    declared: HashMap<&'s str, usize>,
+}
+
+impl<'t, 's> Uses<'t, 's> {
+    fn of(source_code: &'s str, tree: &'t Tree) -> Uses<'t, 's> {
+        let mut uses = Uses::default();
+        for_each_node(tree.root_node(), &mut |node| {
+            let path = match node.kind() {
+                "struct_expression" => node.child_by_field_name("name"),
+                "call_expression" => node.child_by_field_name("function"),
+                "struct_pattern" | "tuple_struct_pattern" => node.child_by_field_name("type"),
+                "identifier" => {
+                    let name = node_text(node, source_code);
+                    uses.identifiers.entry(name).or_default().push(node);
+                    None
+                }
+                "field_expression" => {
+                    if let Some(field) = node.child_by_field_name("field") {
+                        let field = node_text(field, source_code);
+                        uses.accesses.entry(field).or_default().push(node);
+                    }
+                    None
+                }
+                "struct_item" => {
+                    let fields: HashSet<&str> =
+                        field_names(source_code, node).into_iter().collect();
+                    for field in fields {
+                        *uses.declared.entry(field).or_default() += 1;
+                    }
+                    None
+                }
+                _ => None,
+            };
+            if let Some(name) = path.and_then(path_name) {
+                let name = node_text(name, source_code);
+                uses.constructions.entry(name).or_default().push(node);
+            }
+        });
+        uses
+    }
+
+    /// The nodes filed under `name` or `Self` in `map`.
+    fn named<'m>(
+        map: &'m HashMap<&'s str, Vec<Node<'t>>>,
+        name: &str,
+    ) -> impl Iterator<Item = Node<'t>> + 'm {
+        let nodes = |key| map.get(key).into_iter().flatten().copied();
+        nodes(name).chain(nodes("Self"))
+    }
+}
+
+/// `declaration` together with the rewrites that keep the uses of the
+/// struct in the file fitting its new form, as one edit: constructions and
+/// patterns lose their fields, the bindings of the patterns are bound to
+/// `todo!()` where their scope begins, unit values become calls, and
+/// accesses of fields no other struct of the file declares become
+/// `todo!()`, or `_` when assigned to. Tuple field accesses are left alone, they cannot be told
+/// apart from those of tuples. The replacement only spans the edits, the
+/// rest of the file is not copied.
+fn fix_uses(
+    source_code: &str,
+    tree: &Tree,
+    uses: &Uses,
+    (site, form, name): &StructInfo,
+    crushed: StructForm,
+    declaration: Mutation,
+) -> Mutation {
+    let root = tree.root_node();
+    let Some(item) = root.descendant_for_byte_range(site.start_byte, site.end_byte) else {
+        return declaration;
+    };
+    // what constructs and matches the struct after the rewrite
+    let suffix = crushed.empty_use();
+    let is_struct = |node: Node| {
+        let text = node_text(node, source_code);
+        text == name || (text == "Self" && in_impl_of(source_code, node, name))
+    };
+
+    let mut edits = vec![declaration];
+    for node in Uses::named(&uses.constructions, name) {
+        let target = match node.kind() {
+            "struct_expression" => node.child_by_field_name("name"),
+            "struct_pattern" => node.child_by_field_name("type"),
+            "call_expression" if *form == StructForm::Tuple => node.child_by_field_name("function"),
+            "tuple_struct_pattern" if *form == StructForm::Tuple => {
+                node.child_by_field_name("type")
+            }
+            _ => None,
+        };
+        if let Some(target) = target.filter(|t| path_name(*t).is_some_and(is_struct)) {
+            edits.push(Mutation::new(
+                node,
+                suffix.instantiate(&[("name", node_text(target, source_code))]),
+            ));
+            if node.kind().ends_with("pattern") {
+                edits.extend(rebind(source_code, item, node));
+            }
+        }
+    }
+    if *form == StructForm::Unit {
+        for node in Uses::named(&uses.identifiers, name).filter(|n| is_struct(*n)) {
+            let is_value = node.parent().is_some_and(|parent| {
+                !matches!(
+                    parent.kind(),
+                    "scoped_identifier"
+                        | "scoped_use_list"
+                        | "use_declaration"
+                        | "use_as_clause"
+                        | "use_list"
+                )
+            });
+            if is_value {
+                edits.push(Mutation::new(
+                    node,
+                    suffix.instantiate(&[("name", node_text(node, source_code))]),
+                ));
+            }
+        }
+    }
+    // the named fields no other struct of the file has
+    for field in field_names(source_code, item) {
+        if uses.declared.get(field) == Some(&1) {
+            let accesses = uses.accesses.get(field).into_iter().flatten();
+            edits.extend(accesses.map(|node| field_use(*node)));
+        }
+    }
+
+    // the outermost of nested edits wins, `Foo { x: 1 }.x` is one `todo!()`;
+    // insertions go before the edits at their offset
+    edits.sort_by_key(|m| (m.start, m.start != m.end, std::cmp::Reverse(m.end)));
+    let mut kept: Vec<&Mutation> = Vec::new();
+    for edit in &edits {
+        if kept.last().is_none_or(|last| edit.start >= last.end) {
+            kept.push(edit);
+        }
+    }
+    let start = kept.first().map_or(site.start_byte, |m| m.start);
+    let end = kept.iter().map(|m| m.end).max().unwrap_or(site.end_byte);
+    Mutation {
+        start,
+        end,
+        replacement: higher_order::apply_within(source_code, &kept, start..end),
+        kind: "struct_item",
+    }
+}
+
+/// The patterns a binding of a struct pattern may be nested in.
+const PATTERNS: [&str; 11] = [
+    "captured_pattern",
+    "field_pattern",
+    "match_pattern",
+    "mut_pattern",
+    "or_pattern",
+    "ref_pattern",
+    "reference_pattern",
+    "slice_pattern",
+    "struct_pattern",
+    "tuple_pattern",
+    "tuple_struct_pattern",
+];
+
+/// Insertions binding what `pattern`, a pattern of the struct `item` that
+/// loses its fields, binds to `todo!()`, at the start of the scope of the
+/// bindings: after their `let`, or first in the body of their match arm,
+/// `if let`, `while let`, `for`, function or closure. The bindings keep the
+/// types of their fields, or references to them, where it is plain whether
+/// the pattern matches a value or a reference, unless the struct is generic;
+/// elsewhere their type is left to inference.
+fn rebind(source_code: &str, item: Node, pattern: Node) -> Vec<Mutation> {
+    let name = item
+        .child_by_field_name("name")
+        .map(|n| node_text(n, source_code));
+    let mut node = pattern;
+    let mut dereferenced = false;
+    // where the statements go, and what the pattern matches
+    let (place, matched) = loop {
+        let Some(parent) = node.parent() else {
+            return Vec::new();
+        };
+        let scrutinee = |field| {
+            let value = parent.child_by_field_name(field)?;
+            matched_by(source_code, value, name)
+        };
+        break match parent.kind() {
+            "let_declaration" => {
+                let annotated = parent
+                    .child_by_field_name("type")
+                    .map(|ty| match ty.kind() {
+                        "reference_type" => reference_kind(ty),
+                        _ => Matched::Value,
+                    });
+                (
+                    Place::After(parent),
+                    annotated.or_else(|| scrutinee("value")),
+                )
+            }
+            "match_arm" => {
+                let matched = parent
+                    .parent()
+                    .and_then(|block| block.parent())
+                    .and_then(|m| m.child_by_field_name("value"))
+                    .and_then(|value| matched_by(source_code, value, name));
+                (Place::Body(parent.child_by_field_name("value")), matched)
+            }
+            "for_expression" => (
+                Place::Body(parent.child_by_field_name("body")),
+                scrutinee("value"),
+            ),
+            "closure_parameters" => {
+                let body = parent.parent().and_then(|c| c.child_by_field_name("body"));
+                (Place::Body(body), None)
+            }
+            "let_condition" | "let_chain" => {
+                let matched = scrutinee("value");
+                let mut ancestor = parent.parent();
+                while let Some(a) = ancestor.filter(|a| a.kind() == "let_chain") {
+                    ancestor = a.parent();
+                }
+                let body = ancestor.and_then(|a| match a.kind() {
+                    "if_expression" => a.child_by_field_name("consequence"),
+                    "while_expression" => a.child_by_field_name("body"),
+                    _ => None,
+                });
+                (Place::Body(body), matched)
+            }
+            "parameter" => {
+                let function = parent.parent().and_then(|p| p.parent());
+                let body = function.and_then(|f| f.child_by_field_name("body"));
+                let matched = parent
+                    .child_by_field_name("type")
+                    .map(|ty| match ty.kind() {
+                        "reference_type" => reference_kind(ty),
+                        _ => Matched::Value,
+                    });
+                (Place::Body(body), matched)
+            }
+            kind if PATTERNS.contains(&kind) => {
+                dereferenced |= kind == "reference_pattern";
+                node = parent;
+                continue;
+            }
+            _ => return Vec::new(),
+        };
+    };
+    // `&S { a }` matches a reference and binds the field itself
+    let matched = match (matched, dereferenced) {
+        (Some(Matched::Value) | None, true) => None,
+        (Some(_), true) => Some(Matched::Value),
+        (matched, false) => matched,
+    };
+
+    let statements: Vec<String> = bindings(source_code, item, pattern, matched.is_some())
+        .into_iter()
+        .map(|(binding, ty)| match (ty, matched) {
+            (Some(ty), Some(matched)) => {
+                let ty = format!("{}{}", matched.prefix(), ty);
+                TYPED_BINDING.instantiate(&[("binding", &binding), ("type", &ty)])
+            }
+            _ => BINDING.instantiate(&[("binding", &binding)]),
+        })
+        .collect();
+    if statements.is_empty() {
+        return Vec::new();
+    }
+    let statements = format!(" {}", statements.join(" "));
+    let insert = |at: usize, replacement: String| Mutation {
+        start: at,
+        end: at,
+        replacement,
+        kind: pattern.kind(),
+    };
+    match place {
+        Place::After(statement) => vec![insert(statement.end_byte(), statements)],
+        // a block gets the statements first, another expression goes in one
+        Place::Body(Some(body)) if body.kind() == "block" => {
+            vec![insert(body.start_byte() + 1, statements)]
+        }
+        Place::Body(Some(body)) => vec![
+            insert(body.start_byte(), format!("{{{} ", statements)),
+            insert(body.end_byte(), " }".to_string()),
+        ],
+        Place::Body(None) => Vec::new(),
+    }
+}
+
+/// Where [`rebind`] puts the bindings of a pattern.
+enum Place<'t> {
+    /// After this statement.
+    After(Node<'t>),
+    /// First in this body.
+    Body(Option<Node<'t>>),
+}
+
+/// What a pattern matches, and so what its bindings of fields are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Matched {
+    Value,
+    Shared,
+    Unique,
+}
+
+impl Matched {
+    fn prefix(self) -> &'static str {
+        match self {
+            Matched::Value => "",
+            Matched::Shared => "&",
+            Matched::Unique => "&mut ",
+        }
+    }
+}
+
+fn reference_kind(node: Node) -> Matched {
+    let mut cursor = node.walk();
+    let unique = node
+        .children(&mut cursor)
+        .any(|c| c.kind() == "mutable_specifier");
+    match unique {
+        true => Matched::Unique,
+        false => Matched::Shared,
+    }
+}
+
+/// What the scrutinee `value` surely is, if that is plain from the text: a
+/// construction of the struct `name`, a borrow, or `self` or a parameter of
+/// the enclosing function.
+fn matched_by(source_code: &str, value: Node, name: Option<&str>) -> Option<Matched> {
+    match value.kind() {
+        "struct_expression" | "call_expression" => {
+            let path = value
+                .child_by_field_name("name")
+                .or_else(|| value.child_by_field_name("function"))?;
+            let constructed = path_name(path).map(|n| node_text(n, source_code));
+            (constructed.is_some() && constructed == name).then_some(Matched::Value)
+        }
+        "reference_expression" => Some(reference_kind(value)),
+        "self" | "identifier" => {
+            let text = node_text(value, source_code);
+            let mut function = value.parent();
+            while let Some(f) = function.filter(|f| f.kind() != "function_item") {
+                function = f.parent();
+            }
+            let parameters = function?.child_by_field_name("parameters")?;
+            let mut cursor = parameters.walk();
+            let parameter = parameters
+                .named_children(&mut cursor)
+                .find(|p| match p.kind() {
+                    "self_parameter" => text == "self",
+                    "parameter" => p
+                        .child_by_field_name("pattern")
+                        .is_some_and(|pattern| node_text(pattern, source_code) == text),
+                    _ => false,
+                })?;
+            if parameter.kind() == "self_parameter" {
+                let receiver = node_text(parameter, source_code);
+                return Some(
+                    match (receiver.starts_with('&'), receiver.contains("mut")) {
+                        (false, _) => Matched::Value,
+                        (true, false) => Matched::Shared,
+                        (true, true) => Matched::Unique,
+                    },
+                );
+            }
+            match parameter.child_by_field_name("type")? {
+                ty if ty.kind() == "reference_type" => Some(reference_kind(ty)),
+                _ => Some(Matched::Value),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The bindings of `pattern`, a pattern of the struct `item`, as bound by
+/// `let`, with the type of their field where `typed` and they bind a whole
+/// field of a struct without type parameters.
+fn bindings<'s>(
+    source_code: &'s str,
+    item: Node,
+    pattern: Node,
+    typed: bool,
+) -> Vec<(String, Option<&'s str>)> {
+    let typed = typed && item.child_by_field_name("type_parameters").is_none();
+    let body = item.child_by_field_name("body");
+    let field_type = |field: Field| {
+        let body = body.filter(|_| typed)?;
+        let mut cursor = body.walk();
+        let ty = match field {
+            Field::Named(name) => body
+                .named_children(&mut cursor)
+                .filter(|f| f.kind() == "field_declaration")
+                .find(|f| {
+                    f.child_by_field_name("name")
+                        .is_some_and(|n| node_text(n, source_code) == name)
+                })?
+                .child_by_field_name("type")?,
+            Field::Positional(i) => body.children_by_field_name("type", &mut cursor).nth(i)?,
+        };
+        Some(node_text(ty, source_code))
+    };
+
+    let mut acc = Vec::new();
+    let mut cursor = pattern.walk();
+    let subpatterns = pattern
+        .named_children(&mut cursor)
+        .filter(|p| pattern.child_by_field_name("type") != Some(*p));
+    for (i, subpattern) in subpatterns.enumerate() {
+        let (field, subpattern) = match subpattern.kind() {
+            "field_pattern" => {
+                let Some(name) = subpattern.child_by_field_name("name") else {
+                    continue;
+                };
+                let field = Field::Named(node_text(name, source_code));
+                match subpattern.child_by_field_name("pattern") {
+                    Some(inner) => (field, inner),
+                    None => {
+                        let mut cursor = subpattern.walk();
+                        let kinds = subpattern.children(&mut cursor).map(|c| c.kind());
+                        let (by_ref, by_mut) = kinds.fold((false, false), |(r, m), kind| {
+                            (r || kind == "ref", m || kind == "mutable_specifier")
+                        });
+                        let binding = match by_mut {
+                            true => format!("mut {}", field.name()),
+                            false => field.name().to_string(),
+                        };
+                        acc.push((binding, field_type(field).filter(|_| !by_ref)));
+                        continue;
+                    }
+                }
+            }
+            _ => (Field::Positional(i), subpattern),
+        };
+        let (binding, by_mut) = match subpattern.kind() {
+            "mut_pattern" => (subpattern.named_child(1), true),
+            _ => (Some(subpattern), false),
+        };
+        match binding.filter(|b| is_binding(source_code, *b)) {
+            Some(binding) => {
+                let name = node_text(binding, source_code);
+                let binding = match by_mut {
+                    true => format!("mut {}", name),
+                    false => name.to_string(),
+                };
+                acc.push((binding, field_type(field)));
+            }
+            None => for_each_node(subpattern, &mut |node| {
+                if is_binding(source_code, node) {
+                    let mutable = node.parent().is_some_and(|p| p.kind() == "mut_pattern");
+                    let name = node_text(node, source_code);
+                    acc.push((
+                        match mutable {
+                            true => format!("mut {}", name),
+                            false => name.to_string(),
+                        },
+                        None,
+                    ));
+                }
+            }),
+        }
+    }
+    acc
+}
+
+/// A field of a struct, by name or position.
+#[derive(Clone, Copy)]
+enum Field<'s> {
+    Named(&'s str),
+    Positional(usize),
+}
+
+impl Field<'_> {
+    fn name(&self) -> &str {
+        match self {
+            Field::Named(name) => name,
+            Field::Positional(_) => "",
+        }
+    }
+}
+
+/// Whether the identifier `node` of a pattern binds a name, rather than
+/// naming a constant, a unit struct or a variant.
+fn is_binding(source_code: &str, node: Node) -> bool {
+    node.kind() == "identifier"
+        && node_text(node, source_code).starts_with(|c: char| c.is_lowercase() || c == '_')
+        && node.parent().is_some_and(|parent| {
+            parent.kind() != "scoped_identifier" && parent.child_by_field_name("type") != Some(node)
+        })
+}
+
+/// The last segment of a path such as `crate::Foo`.
+fn path_name(node: Node) -> Option<Node> {
+    match node.kind() {
+        "scoped_type_identifier" | "scoped_identifier" => node.child_by_field_name("name"),
+        _ => Some(node),
+    }
+}
+
+/// The names of the named fields of the struct `item`.
+fn field_names<'s>(source_code: &'s str, item: Node) -> Vec<&'s str> {
+    let Some(body) = item
+        .child_by_field_name("body")
+        .filter(|b| b.kind() == "field_declaration_list")
+    else {
+        return Vec::new();
+    };
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .filter_map(|field| field.child_by_field_name("name"))
+        .map(|n| node_text(n, source_code))
+        .collect()
+}
+
+/// `todo!()` for the field access `node`, or `_ =` for its assignment.
+fn field_use(node: Node) -> Mutation {
+    let assignment = node.parent().filter(|parent| {
+        matches!(
+            parent.kind(),
+            "assignment_expression" | "compound_assignment_expr"
+        ) && parent.child_by_field_name("left") == Some(node)
+    });
+    let Some(assignment) = assignment else {
+        return Mutation::new(node, FIELD_USE.instantiate(&[]));
+    };
+    // `a.x += 1` becomes `_ = 1`, the right-hand side is still evaluated
+    let operator_end = match assignment.child_by_field_name("operator") {
+        Some(operator) => operator.end_byte(),
+        None => assignment
+            .child(1)
+            .map_or(node.end_byte(), |eq| eq.end_byte()),
+    };
+    Mutation {
+        start: node.start_byte(),
+        end: operator_end,
+        replacement: "_ =".to_string(),
+        kind: assignment.kind(),
+    }
+}
+
+/// Whether `node` is inside an `impl` block of the type `name`.
+fn in_impl_of(source_code: &str, node: Node, name: &str) -> bool {
+    let mut ancestor = node.parent();
+    while let Some(parent) = ancestor {
+        if parent.kind() == "impl_item" {
+            let Some(ty) = parent.child_by_field_name("type") else {
+                return false;
+            };
+            let ty = match ty.kind() {
+                "generic_type" => ty.child_by_field_name("type").unwrap_or(ty),
+                _ => ty,
+            };
+            return node_text(ty, source_code) == name;
+        }
+        ancestor = parent.parent();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn forms(source_code: &str) -> Vec<StructForm> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        find_all_structs(source_code, &tree)
+            .into_iter()
+            .map(|(_, form, _)| form)
+            .collect()
+    }
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        let found = find_all_structs(source_code, &tree);
+        modify_structs(source_code, &tree, &found, &[], KeepGenerics::Drop)
+            .iter()
+            .map(|(_, _, mutation)| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn one_mutant_per_struct() {
+        assert_eq!(
+            crushed("struct U;\nstruct T(u8);\nstruct S { t: i32 }\n"),
+            [
+                "struct U();\nstruct T(u8);\nstruct S { t: i32 }\n",
+                "struct U;\nstruct T();\nstruct S { t: i32 }\n",
+                "struct U;\nstruct T(u8);\nstruct S;\n",
+            ]
+        );
+        assert!(crushed("fn main() {}\n").is_empty());
+    }
+
+    #[test]
+    fn many_structs() {
+        let source_code: String = (0..300).map(|i| format!("struct S{}(u8);\n", i)).collect();
+        let mutants = crushed(&source_code);
+        assert_eq!(mutants.len(), 300);
+        for (i, mutant) in mutants.iter().enumerate() {
+            let crushed = format!("struct S{}();\n", i);
+            let expected = source_code.replacen(&format!("struct S{}(u8);\n", i), &crushed, 1);
+            assert_eq!(*mutant, expected);
+        }
+    }
+
+    fn mutants(source_code: &str, crusher: &StructCrusher) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        crusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    fn crushed_into(source_code: &str, forms: &[Crush], fix_uses: bool) -> Vec<String> {
+        let crusher = StructCrusher {
+            fix_uses,
+            forms: forms.to_vec(),
+            ..StructCrusher::default()
+        };
+        mutants(source_code, &crusher)
+    }
+
+    #[test]
+    fn chosen_forms() {
+        use Crush::*;
+        assert_eq!(
+            crushed_into("struct T(u8);", &[Unit, Tuple, Braced], false),
+            ["struct T;", "struct T();", "struct T {}"]
+        );
+        // none for the form a struct has, empty already
+        assert_eq!(
+            crushed_into("struct U;", &[Unit, Braced], false),
+            ["struct U {}"]
+        );
+        assert_eq!(
+            crushed_into("struct S { t: i32 }", &[Braced], false),
+            ["struct S {}"]
+        );
+        assert!(crushed_into("struct S {}", &[Braced], false).is_empty());
+    }
+
+    #[test]
+    fn halved_and_duplicated() {
+        use Crush::*;
+        assert_eq!(
+            crushed_into("struct T(u8, pub u16, u32);", &[Halved, Duplicated], false),
+            [
+                "struct T(u8);",
+                "struct T(u8, pub u16, u32, u8, pub u16, u32);"
+            ]
+        );
+        assert_eq!(
+            crushed_into(
+                "struct S {\n    #[a] a: u8,\n    b: Vec<u8>,\n}",
+                &[Halved, Duplicated],
+                false
+            ),
+            [
+                "struct S {\n    #[a] a: u8,\n}",
+                "struct S {\n    #[a] a: u8,\n    b: Vec<u8>, #[a] a_2: u8, b_2: Vec<u8>,\n}"
+            ]
+        );
+        // one field cannot be halved, no fields not duplicated
+        assert_eq!(
+            crushed_into("struct T(u8);\nstruct U;", &[Halved, Duplicated], false),
+            ["struct T(u8, u8);\nstruct U;"]
+        );
+    }
+
+    #[test]
+    fn halved_and_duplicated_around_comments() {
+        use Crush::*;
+        // copies go in front of a trailing comment, not into it
+        assert_eq!(
+            crushed_into(
+                "struct S { a: u8, b: u16, // c\n}",
+                &[Halved, Duplicated],
+                false
+            ),
+            [
+                "struct S { a: u8, // c\n}",
+                "struct S { a: u8, b: u16, a_2: u8, b_2: u16, // c\n}"
+            ]
+        );
+        assert_eq!(
+            crushed_into(
+                "struct S {\n    a: u8, // a\n    /* x */ b: u16, // b\n    // c\n    c: u32,\n    d: u64\n}",
+                &[Halved, Duplicated],
+                false
+            ),
+            [
+                "struct S {\n    a: u8, // a\n    /* x */ b: u16 // b\n    // c\n}",
+                "struct S {\n    a: u8, // a\n    /* x */ b: u16, // b\n    // c\n    c: u32,\n    d: u64, a_2: u8, b_2: u16, c_2: u32, d_2: u64\n}"
+            ]
+        );
+        assert_eq!(
+            crushed_into(
+                "struct T(u8, /* y */ pub u16, // z\n);",
+                &[Halved, Duplicated],
+                false
+            ),
+            [
+                "struct T(u8, /* y */ // z\n);",
+                "struct T(u8, /* y */ pub u16, u8, pub u16, // z\n);"
+            ]
+        );
+    }
+
+    #[test]
+    fn kept_generics() {
+        let kept = |generics, forms: &[Crush]| {
+            let crusher = StructCrusher {
+                generics,
+                forms: forms.to_vec(),
+                ..StructCrusher::default()
+            };
+            mutants("struct S<'a, T: Copy = u8> { t: &'a T }", &crusher)
+        };
+        assert_eq!(kept(KeepGenerics::Drop, &[]), ["struct S;"]);
+        assert_eq!(
+            kept(KeepGenerics::Keep, &[Crush::Tuple]),
+            ["struct S<'a, T: Copy = u8>();"]
+        );
+        assert_eq!(
+            kept(KeepGenerics::Both, &[Crush::Unit, Crush::Halved]),
+            ["struct S;", "struct S<'a, T: Copy = u8>;"]
+        );
+        // nothing to keep on a struct without them
+        let crusher = StructCrusher {
+            generics: KeepGenerics::Both,
+            ..StructCrusher::default()
+        };
+        assert_eq!(mutants("struct T(u8);", &crusher), ["struct T();"]);
+    }
+
+    #[test]
+    fn braced_uses() {
+        assert_eq!(
+            crushed_into(
+                "struct T(u8);\nfn f() -> T { let T(x) = T(1); T(x) }\n",
+                &[Crush::Braced],
+                true
+            ),
+            ["struct T {}\nfn f() -> T { let T {} = T {}; let x: u8 = todo!(); T {} }\n"]
+        );
+        assert_eq!(
+            crushed_into("struct U;\nfn f() -> U { U }\n", &[Crush::Braced], true),
+            ["struct U {}\nfn f() -> U { U {} }\n"]
+        );
+    }
+
+    #[test]
+    fn plain_forms() {
+        assert_eq!(
+            forms("struct U;\nstruct T(u8);\nstruct S { t: i32 }\n"),
+            [StructForm::Unit, StructForm::Tuple, StructForm::Struct]
+        );
+    }
+
+    #[test]
+    fn comment_before_semicolon() {
+        assert_eq!(forms("struct T(u8) /* x */;"), [StructForm::Tuple]);
+        assert_eq!(forms("struct U /* () */;"), [StructForm::Unit]);
+    }
+
+    #[test]
+    fn where_clauses() {
+        assert_eq!(forms("struct T<X>(X) where X: Copy;"), [StructForm::Tuple]);
+        assert_eq!(
+            forms("struct S<X> where X: Fn() -> () { x: X }"),
+            [StructForm::Struct]
+        );
+        assert_eq!(forms("struct U<X> where X: Copy;"), [StructForm::Unit]);
+    }
+
+    #[test]
+    fn whitespace_inside() {
+        assert_eq!(forms("struct S\n{\n}\n"), [StructForm::Struct]);
+        assert_eq!(forms("struct T ( ) ;"), [StructForm::Tuple]);
+        assert_eq!(forms("struct U ;"), [StructForm::Unit]);
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        assert_eq!(
+            forms("struct S\r\n{\r\n    a: u8,\r\n}\r\nstruct T(u8)\r\n;\r\nstruct U\r\n;\r\n"),
+            [StructForm::Struct, StructForm::Tuple, StructForm::Unit]
+        );
+    }
+
+    #[test]
+    fn fixed_uses() {
+        let source_code = "struct S { a: u8 }\nimpl S { fn new() -> Self { Self { a: 0 } } }\n\
+                           fn f(mut s: S) -> u8 { let S { a } = S { a: 1 }; s.a += a; s.a }\n";
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        let crusher = StructCrusher {
+            fix_uses: true,
+            forms: vec![Crush::Tuple],
+            ..StructCrusher::default()
+        };
+        let mutations = crusher.mutations(source_code, &tree);
+        assert_eq!(
+            mutations[0].apply(source_code),
+            "struct S();\nimpl S { fn new() -> Self { Self() } }\n\
+             fn f(mut s: S) -> u8 { let S() = S(); let a: u8 = todo!(); _ = a; todo!() }\n"
+        );
+    }
+
+    #[test]
+    fn rebound_bindings() {
+        let crushed = |source_code| crushed_into(source_code, &[Crush::Unit], true);
+        assert_eq!(
+            crushed("struct S { a: u8 }\nfn f(s: &S) -> u8 { match s { S { a } => *a } }\n"),
+            ["struct S;\nfn f(s: &S) -> u8 { match s { S => { let a: &u8 = todo!(); *a } } }\n"]
+        );
+        assert_eq!(
+            crushed("struct S { a: u8 }\nfn f(S { mut a }: S) { a += 1; }\n"),
+            ["struct S;\nfn f(S: S) { let mut a: u8 = todo!(); a += 1; }\n"]
+        );
+        // no telling what `s.get()` is
+        assert_eq!(
+            crushed("struct S { a: u8 }\nfn f() { if let Some(S { a }) = s.get() { a; } }\n"),
+            ["struct S;\nfn f() { if let Some(S) = s.get() { let a = todo!(); a; } }\n"]
+        );
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert_eq!(
+            forms("// ünïcödé\nstruct Ä(u8);\nstruct Ö { é: () }"),
+            [StructForm::Tuple, StructForm::Struct]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Replacement templates, and the dry-run check that they produce Rust the
+//! grammar accepts.
+
+/// Whole-file snippets a template instantiation is spliced into at `@@`.
+pub mod context {
+    pub const ITEM: &str = "@@";
+    pub const TYPE: &str = "type _T = @@;";
+    pub const STATEMENT: &str = "fn _f() { @@ }";
+    pub const EXPRESSION: &str = "fn _f() { let _ = @@; }";
+}
+
+/// Replacement text with `$placeholder`s, together with the syntactic
+/// context it is meant to be spliced into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Template {
+    pub text: &'static str,
+    pub context: &'static str,
+    /// The operator wants unparseable output here; skipped by validation.
+    pub malformed: bool,
+}
+
+impl Template {
+    pub const fn new(context: &'static str, text: &'static str) -> Template {
+        Template {
+            text,
+            context,
+            malformed: false,
+        }
+    }
+
+    pub const fn malformed(context: &'static str, text: &'static str) -> Template {
+        Template {
+            text,
+            context,
+            malformed: true,
+        }
+    }
+
+    /// Substitute every `$key` by its value; unknown placeholders are left as is.
+    pub fn instantiate(&self, args: &[(&str, &str)]) -> String {
+        substitute(self.text, |key| {
+            args.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+        })
+    }
+
+    /// The template filled with stand-in values and spliced into its context.
+    pub fn synthetic_file(&self) -> String {
+        let text = substitute(self.text, |key| Some(synthetic_value(key)));
+        self.context.replace("@@", &text)
+    }
+}
+
+fn synthetic_value(placeholder: &str) -> &'static str {
+    match placeholder {
+        "params" => "a: u8",
+        "signature" => "fn crushed()",
+        "label" => "'crushed",
+        "expr" => "0",
+        "generics" => "<T>",
+        // `struct U<T> where T: Copy;` is Rust, but not to the grammar
+        "where" => "",
+        "fields" => " { a: T }",
+        "tuple" => "(T)",
+        "item" => "struct Crushed;",
+        _ => "Crushed",
+    }
+}
+
+fn substitute<'a>(text: &str, mut lookup: impl FnMut(&str) -> Option<&'a str>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(dollar) = rest.find('$') {
+        out.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let key_len = after
+            .find(|c: char| !(c.is_ascii_lowercase() || c == '_'))
+            .unwrap_or(after.len());
+        let key = &after[..key_len];
+        match lookup(key).filter(|_| !key.is_empty()) {
+            Some(value) => out.push_str(value),
+            None => {
+                out.push('$');
+                out.push_str(key);
+            }
+        }
+        rest = &after[key_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse a synthetic instantiation of every template of an operator and
+/// describe the ones that come out with ERROR or MISSING nodes.
+pub fn validate(operator: &str, templates: &[Template]) -> Vec<String> {
+    let mut parser = tree_sitter::Parser::new();
+    parser.set_language(&tree_sitter_rust::language()).unwrap();
+
+    templates
+        .iter()
+        .filter(|t| !t.malformed)
+        .filter_map(|t| {
+            let file = t.synthetic_file();
+            let tree = parser.parse(&file, None).unwrap();
+            tree.root_node().has_error().then(|| {
+                format!(
+                    "operator {:?}: template {:?} does not parse, e.g. {:?}",
+                    operator, t.text, file
+                )
+            })
+        })
+        .collect()
+}
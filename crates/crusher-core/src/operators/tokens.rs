@@ -0,0 +1,172 @@
+use tree_sitter::Tree;
+
+use super::{
+    context::ITEM, for_each_node, in_comment_or_string, node_text, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+use crate::rng::Rng;
+
+/// Token edits made in every file.
+const EDITS: usize = 24;
+
+/// Tokens go in with spaces around them, so they do not merge with their
+/// neighbours into other tokens.
+const INSERT: Template = Template::malformed(ITEM, " $token ");
+const DUPLICATE: Template = Template::malformed(ITEM, " $token ");
+const DELETE: Template = Template::malformed(ITEM, "");
+static TEMPLATES: [Template; 3] = [INSERT, DUPLICATE, DELETE];
+
+/// Lexical fuzzing: inserts tokens of the file in front of others, deletes
+/// them and duplicates them, at positions picked per file by the seed.
+#[derive(Default)]
+pub struct TokenCrusher {
+    pub seed: u64,
+}
+
+impl Mutator for TokenCrusher {
+    fn name(&self) -> &'static str {
+        "tokens"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Inserts a token of the file in front of another, deletes one or duplicates one, 24 times per file at positions picked by the seed, for the parser's error recovery.",
+            node_kinds: &["source_file"],
+            example: "fn f(a: u8) -> u8 { a + 1 }\n",
+            flags: &[("--seed", "picks the tokens and positions of every file")],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut tokens = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if node.child_count() == 0
+                && node.end_byte() > node.start_byte()
+                && !in_comment_or_string(node)
+            {
+                tokens.push(node);
+            }
+        });
+        if tokens.is_empty() {
+            return Vec::new();
+        }
+
+        let mut rng = Rng::derive(self.seed, source_code);
+        let mut acc = Vec::new();
+        for _ in 0..EDITS {
+            let token = tokens[rng.below(tokens.len())];
+            let text = node_text(token, source_code);
+            let (at, mutation) = match rng.below(3) {
+                0 => {
+                    let other = node_text(tokens[rng.below(tokens.len())], source_code);
+                    let at = token.start_byte();
+                    (at..at, INSERT.instantiate(&[("token", other)]))
+                }
+                1 => (
+                    token.start_byte()..token.end_byte(),
+                    DELETE.instantiate(&[]),
+                ),
+                _ => {
+                    let at = token.end_byte();
+                    (at..at, DUPLICATE.instantiate(&[("token", text)]))
+                }
+            };
+            let mutation = Mutation {
+                start: at.start,
+                end: at.end,
+                replacement: mutation,
+                kind: token.kind(),
+            };
+            if !acc.contains(&mutation) {
+                acc.push(mutation);
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(source_code: &str) -> Tree {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        parser.parse(source_code, None).unwrap()
+    }
+
+    fn crushed(source_code: &str, seed: u64) -> Vec<Mutation> {
+        TokenCrusher { seed }.mutations(source_code, &parse(source_code))
+    }
+
+    #[test]
+    fn one_token() {
+        let mut mutants: Vec<String> = crushed(";", 0)
+            .iter()
+            .map(|mutation| mutation.apply(";"))
+            .collect();
+        mutants.sort();
+        assert_eq!(mutants, ["", " ; ;", "; ; "]);
+    }
+
+    #[test]
+    fn inserted_tokens_stand_alone() {
+        // `u8)`, `a/*`: no space between a token and the next
+        let source_code = "fn f(a: u8) -> u8 { a/*c*/+1/2 }\n";
+        for seed in 0..16 {
+            for mutation in crushed(source_code, seed) {
+                let token = mutation.replacement.trim();
+                if token.is_empty() {
+                    continue;
+                }
+                let mutant = mutation.apply(source_code);
+                let (start, end) = (mutation.start + 1, mutation.start + 1 + token.len());
+                // no token of the mutant runs across either end of it
+                let mut straddling = Vec::new();
+                for_each_node(parse(&mutant).root_node(), &mut |node| {
+                    let inside = |at: usize| node.start_byte() < at && at < node.end_byte();
+                    if node.child_count() == 0 && (inside(start) || inside(end)) {
+                        straddling.push(node.kind());
+                    }
+                });
+                assert!(straddling.is_empty(), "{:?} in {:?}", token, mutant);
+            }
+        }
+    }
+
+    #[test]
+    fn comments_and_strings_stay() {
+        let source_code = "fn f() -> &'static str { /* a b */ \"c d\" }\n";
+        let comment = source_code.find("/*").unwrap()..source_code.find("*/").unwrap() + 2;
+        let string = source_code.find('"').unwrap()..source_code.rfind('"').unwrap() + 1;
+        let mutations = crushed(source_code, 3);
+        assert!(!mutations.is_empty());
+        for mutation in mutations {
+            for inside in [&comment, &string] {
+                let within = |at: usize| inside.start < at && at < inside.end;
+                assert!(
+                    !within(mutation.start) && !within(mutation.end),
+                    "{:?}",
+                    mutation
+                );
+            }
+            assert_ne!(mutation.replacement.trim(), "a");
+            assert_ne!(mutation.replacement.trim(), "d");
+        }
+        assert!(crushed("// a comment\n/* and another */\n", 0).is_empty());
+    }
+
+    #[test]
+    fn seeded() {
+        let source_code = "fn f(a: u8) -> u8 { a + 1 }\n";
+        assert_eq!(crushed(source_code, 7), crushed(source_code, 7));
+        assert_ne!(crushed(source_code, 7), crushed(source_code, 8));
+        // the same seed still picks other positions in another file
+        let other = "fn g(a: u8) -> u8 { a + 1 }\n";
+        assert_ne!(crushed(source_code, 7), crushed(other, 7));
+    }
+}
@@ -0,0 +1,218 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::ITEM, for_each_node, in_comment_or_string, node_text, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+use crate::rng::{self, Rng};
+
+/// Places between tokens every insertion goes to, per file.
+const PLACES: usize = 3;
+/// Bytes of the line [`LONG_LINE`] inserts.
+const LONG_LINE_BYTES: usize = 16384;
+
+const COMMENT: Template = Template::new(ITEM, "/* crushed */");
+const NESTED_COMMENT: Template = Template::new(ITEM, "/* /* crushed */ */");
+const EMPTY_COMMENT: Template = Template::new(ITEM, "/**/");
+const FORM_FEED: Template = Template::malformed(ITEM, "\x0c");
+const VERTICAL_TAB: Template = Template::malformed(ITEM, "\x0b");
+const BOM: Template = Template::malformed(ITEM, "\u{feff}");
+const LINE_SEPARATOR: Template = Template::malformed(ITEM, "\u{2028}");
+const LEFT_TO_RIGHT_MARK: Template = Template::malformed(ITEM, "\u{200e}");
+const LONG_LINE: Template = Template::malformed(ITEM, "$spaces");
+const DOC: Template = Template::new("@@\nstruct S;", "///$text");
+const INNER_DOC: Template = Template::new("@@\nstruct S;", "//!$text");
+const BLOCK_DOC: Template = Template::new("@@\nstruct S;", "/**$text*/");
+const INNER_BLOCK_DOC: Template = Template::new("@@\nstruct S;", "/*!$text*/");
+static TEMPLATES: [Template; 13] = [
+    COMMENT,
+    NESTED_COMMENT,
+    EMPTY_COMMENT,
+    FORM_FEED,
+    VERTICAL_TAB,
+    BOM,
+    LINE_SEPARATOR,
+    LEFT_TO_RIGHT_MARK,
+    LONG_LINE,
+    DOC,
+    INNER_DOC,
+    BLOCK_DOC,
+    INNER_BLOCK_DOC,
+];
+
+/// Insertions between tokens: comments, exotic whitespace and a very long
+/// line.
+const INSERTED: [Template; 9] = [
+    COMMENT,
+    NESTED_COMMENT,
+    EMPTY_COMMENT,
+    FORM_FEED,
+    VERTICAL_TAB,
+    BOM,
+    LINE_SEPARATOR,
+    LEFT_TO_RIGHT_MARK,
+    LONG_LINE,
+];
+
+/// Perturbs the trivia between tokens for the lexer and the rendering of
+/// diagnostic spans: inserts block comments, exotic whitespace and a very
+/// long line between tokens, and turns comments into doc comments.
+#[derive(Default)]
+pub struct TriviaCrusher {
+    /// Picks the places of the insertions of every file.
+    pub seed: u64,
+}
+
+impl Mutator for TriviaCrusher {
+    fn name(&self) -> &'static str {
+        "trivia"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Inserts block comments, form feeds, vertical tabs, a BOM, U+2028, U+200E and a 16 KiB line between tokens, 3 places each per file, and turns comments into outer and inner doc comments.",
+            node_kinds: &["line_comment", "block_comment"],
+            example: "// a comment\nfn f(a: u8) -> u8 { a /* one */ + 1 }\n",
+            flags: &[("--seed", "picks the places of the insertions of every file")],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut places = Vec::new();
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| match node.kind() {
+            "line_comment" | "block_comment" => crush_comment(source_code, node, &mut acc),
+            _ if node.child_count() == 0
+                && node.end_byte() > node.start_byte()
+                && !in_comment_or_string(node) =>
+            {
+                places.push(node.end_byte())
+            }
+            _ => {}
+        });
+        // the end of the last token is the end of the file
+        places.pop();
+
+        let mut rng = Rng::derive(self.seed, source_code);
+        let spaces = " ".repeat(LONG_LINE_BYTES);
+        for template in INSERTED {
+            let text = template.instantiate(&[("spaces", &spaces)]);
+            for i in rng::sample_indices(&mut rng, places.len(), PLACES) {
+                acc.push(Mutation {
+                    start: places[i],
+                    end: places[i],
+                    replacement: text.clone(),
+                    kind: "source_file",
+                });
+            }
+        }
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// A plain comment as an outer and an inner doc comment; doc comments stay.
+/// As in rustc, `///` and `/**` start doc comments but `////`, `/***` and
+/// `/**/` do not; the extra `/` or `*` of those go.
+fn crush_comment(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let text = node_text(node, source_code);
+    let fourth = text.as_bytes().get(3).copied();
+    let (body, docs) = if let Some(body) = text.strip_prefix("//") {
+        let outer = body.starts_with('/') && fourth != Some(b'/');
+        if outer || body.starts_with('!') {
+            return;
+        }
+        (body.trim_start_matches('/'), [DOC, INNER_DOC])
+    } else if let Some(body) = text.strip_prefix("/*").and_then(|t| t.strip_suffix("*/")) {
+        let outer = text.starts_with("/**") && !matches!(fourth, Some(b'*' | b'/'));
+        if outer || body.starts_with('!') {
+            return;
+        }
+        // `/***/` is no doc comment, `/** */` is
+        match body.trim_start_matches('*') {
+            "" => (" ", [BLOCK_DOC, INNER_BLOCK_DOC]),
+            body => (body, [BLOCK_DOC, INNER_BLOCK_DOC]),
+        }
+    } else {
+        return;
+    };
+    for doc in docs {
+        acc.push(Mutation::new(node, doc.instantiate(&[("text", body)])));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        TriviaCrusher { seed: 0 }
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    /// The doc comments a lone comment in front of `struct S;` becomes.
+    fn docs(comment: &str) -> Vec<String> {
+        let source_code = format!("{}\nstruct S;", comment);
+        crushed(&source_code)
+            .into_iter()
+            .filter(|mutant| mutant.ends_with("\nstruct S;"))
+            .map(|mutant| mutant.trim_end_matches("\nstruct S;").to_string())
+            .filter(|doc| doc != comment)
+            .collect()
+    }
+
+    #[test]
+    fn doc_comments() {
+        assert_eq!(docs("// a"), ["/// a", "//! a"]);
+        assert_eq!(docs("/* a */"), ["/** a */", "/*! a */"]);
+        // more slashes or stars than a doc comment has make a plain comment
+        assert_eq!(docs("//// a"), ["/// a", "//! a"]);
+        assert_eq!(docs("/*** a */"), ["/** a */", "/*! a */"]);
+        assert_eq!(docs("/**/"), ["/** */", "/*! */"]);
+        assert_eq!(docs("/***/"), ["/** */", "/*! */"]);
+        for doc in ["/// a", "//! a", "/** a */", "/*! a */", "///", "/**a*/"] {
+            assert!(docs(doc).is_empty(), "{}", doc);
+        }
+    }
+
+    #[test]
+    fn inserted_between_tokens() {
+        let mut mutants = crushed("struct S;\n");
+        mutants.sort();
+        let spaces = " ".repeat(LONG_LINE_BYTES);
+        let mut expected: Vec<String> = ["/* crushed */", "/* /* crushed */ */", "/**/"]
+            .into_iter()
+            .chain(["\x0c", "\x0b", "\u{feff}", "\u{2028}", "\u{200e}", &spaces])
+            .flat_map(|text| {
+                [
+                    format!("struct{} S;\n", text),
+                    format!("struct S{};\n", text),
+                ]
+            })
+            .collect();
+        expected.sort();
+        assert_eq!(mutants, expected);
+        // the end of the last token is the end of the file
+        assert!(crushed("S\n").is_empty());
+    }
+
+    #[test]
+    fn strings_and_comments_stay_whole() {
+        let source_code = "const C: &str = \"a b c d\"; /* e f g */\n";
+        let mutants = crushed(source_code);
+        assert!(!mutants.is_empty());
+        for mutant in mutants {
+            assert!(mutant.contains("\"a b c d\""), "{:?}", mutant);
+            assert!(mutant.contains(" e f g */"), "{:?}", mutant);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use tree_sitter::{Node, Tree};
+
+use super::{context::TYPE, for_each_node, node_text, Mutation, Mutator, OperatorDoc, Template};
+
+const DROP: Template = Template::new("type _T = (u8, @@);", "");
+const DUPLICATE: Template = Template::new("type _T = (u8@@);", ", $element");
+const SINGLE: Template = Template::new(TYPE, "($element,)");
+const BARE: Template = Template::new(TYPE, "$element");
+const UNIT: Template = Template::new(TYPE, "()");
+static TEMPLATES: [Template; 5] = [DROP, DUPLICATE, SINGLE, BARE, UNIT];
+
+/// Changes the arity of tuple types and expressions: drops an element,
+/// duplicates one, and turns 1-tuples into the bare element.
+pub struct TupleCrusher;
+
+impl Mutator for TupleCrusher {
+    fn name(&self) -> &'static str {
+        "tuples"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Drops each element of tuple types and expressions, duplicates each, and turns `(T,)` into `T`.",
+            node_kinds: &["tuple_type", "tuple_expression"],
+            example: "fn f(t: (u8, i32)) -> (u8,) { (t.0,) }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if matches!(node.kind(), "tuple_type" | "tuple_expression") {
+                crush_tuple(source_code, node, &mut acc);
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+fn crush_tuple(source_code: &str, node: Node, acc: &mut Vec<Mutation>) {
+    let mut cursor = node.walk();
+    let elements: Vec<Node> = node
+        .named_children(&mut cursor)
+        .filter(|child| !matches!(child.kind(), "line_comment" | "block_comment"))
+        .collect();
+    // tree-sitter parses the parenthesized type `(T)` as a tuple type
+    let mut cursor = node.walk();
+    if elements.len() == 1 && !node.children(&mut cursor).any(|c| c.kind() == ",") {
+        return;
+    }
+    let text = |element: Node| node_text(element, source_code);
+    let edit = |start, end, replacement| Mutation {
+        start,
+        end,
+        replacement,
+        kind: node.kind(),
+    };
+    for (i, &element) in elements.iter().enumerate() {
+        // what is left of a pair needs the comma to stay a tuple
+        acc.push(match (elements.len(), elements.get(i + 1)) {
+            (2, _) => Mutation::new(
+                node,
+                SINGLE.instantiate(&[("element", text(elements[1 - i]))]),
+            ),
+            (_, Some(next)) => edit(
+                element.start_byte(),
+                next.start_byte(),
+                DROP.instantiate(&[]),
+            ),
+            (_, None) if i > 0 => edit(
+                elements[i - 1].end_byte(),
+                element.end_byte(),
+                DROP.instantiate(&[]),
+            ),
+            (_, None) => Mutation::new(node, UNIT.instantiate(&[])),
+        });
+        acc.push(edit(
+            element.end_byte(),
+            element.end_byte(),
+            DUPLICATE.instantiate(&[("element", text(element))]),
+        ));
+    }
+    if let [element] = elements[..] {
+        acc.push(Mutation::new(
+            node,
+            BARE.instantiate(&[("element", text(element))]),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        TupleCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn elements_dropped_and_duplicated() {
+        assert_eq!(
+            crushed("fn f() { (1, /* c */ 2, 3,); }"),
+            [
+                "fn f() { (2, 3,); }",
+                "fn f() { (1, 1, /* c */ 2, 3,); }",
+                "fn f() { (1, /* c */ 3,); }",
+                "fn f() { (1, /* c */ 2, 2, 3,); }",
+                "fn f() { (1, /* c */ 2,); }",
+                "fn f() { (1, /* c */ 2, 3, 3,); }",
+            ]
+        );
+    }
+
+    #[test]
+    fn pairs_and_singles() {
+        assert_eq!(
+            crushed("type P = (u8, i32);"),
+            [
+                "type P = (i32,);",
+                "type P = (u8, u8, i32);",
+                "type P = (u8,);",
+                "type P = (u8, i32, i32);",
+            ]
+        );
+        assert_eq!(
+            crushed("type S = (u8,);"),
+            ["type S = ();", "type S = (u8, u8,);", "type S = u8;"]
+        );
+        // no tuple
+        assert!(crushed("type A = (u8); fn f() { (1); }").is_empty());
+    }
+}
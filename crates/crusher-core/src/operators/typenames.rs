@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+
+use tree_sitter::{Node, Tree, TreeCursor};
+
+use super::{
+    context::TYPE, for_each_node, node_text, Mutation, MutationSite, Mutator, OperatorDoc, Template,
+};
+use crate::rng::{self, Rng};
+
+type TypePosInfo = (MutationSite, &'static str);
+
+// https://github.com/tree-sitter/tree-sitter-rust/blob/b77c0d8ac28a7c143224e6ed9b4f9e4bd044ff5b/src/node-types.json#L393-L464
+const TYPE_KINDS: [&str; 16] = [
+    "abstract_type",
+    "array_type",
+    "bounded_type",
+    "dynamic_type",
+    "function_type",
+    "generic_type",
+    "macro_invocation",
+    "metavariable",
+    "never_type",
+    "pointer_type",
+    "reference_type",
+    "removed_trait_bound",
+    "scoped_type_identifier",
+    "tuple_type",
+    "type_identifier",
+    "unit_type",
+];
+
+/// Items and parameters whose `name` field introduces a type name.
+const DEFINING_KINDS: [&str; 7] = [
+    "associated_type",
+    "enum_item",
+    "optional_type_parameter",
+    "struct_item",
+    "trait_item",
+    "type_item",
+    "union_item",
+];
+
+/// Whether `node` is the name a type is defined under, as in `struct Foo`,
+/// `trait Foo`, `type Foo = ...` or the `T` of `<T: Copy>`.
+pub fn is_definition(node: Node) -> bool {
+    if node.kind() != "type_identifier" {
+        return false;
+    }
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "type_parameters" => true,
+        "constrained_type_parameter" => parent.child_by_field_name("left") == Some(node),
+        kind => DEFINING_KINDS.contains(&kind) && parent.child_by_field_name("name") == Some(node),
+    }
+}
+
+/// Which occurrences of a type are mutated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TypePositions {
+    /// Only the names types are defined under
+    Defs,
+    /// Only the places types are used
+    Uses,
+    #[default]
+    Both,
+}
+
+impl TypePositions {
+    pub fn admits(self, node: Node) -> bool {
+        match self {
+            TypePositions::Defs => is_definition(node),
+            TypePositions::Uses => !is_definition(node),
+            TypePositions::Both => true,
+        }
+    }
+}
+
+/// The signatures and headers a type occurs in that [`TypenameCrusher`] can
+/// be restricted to, each reaching different compiler phases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TypeContext {
+    /// The types of struct, union and enum variant fields
+    Fields,
+    /// The types of function and closure parameters
+    Params,
+    /// Return types of functions, function pointers and closures
+    Returns,
+    /// Trait bounds, on generic parameters, in where clauses and on
+    /// associated types
+    Bounds,
+    /// The trait, self type and generic parameters of impl blocks
+    ImplHeaders,
+}
+
+impl TypeContext {
+    /// The context of the type `node`, from its nearest ancestor deciding
+    /// one; `None` for types in bodies, aliases and elsewhere.
+    pub fn of(node: Node) -> Option<TypeContext> {
+        let mut child = node;
+        while let Some(parent) = child.parent() {
+            let is_field = |field| parent.child_by_field_name(field) == Some(child);
+            match parent.kind() {
+                "field_declaration" | "ordered_field_declaration_list" => {
+                    return Some(TypeContext::Fields)
+                }
+                "parameters" | "closure_parameters" => return Some(TypeContext::Params),
+                "function_item"
+                | "function_signature_item"
+                | "function_type"
+                | "closure_expression"
+                    if is_field("return_type") =>
+                {
+                    return Some(TypeContext::Returns)
+                }
+                "trait_bounds" => return Some(TypeContext::Bounds),
+                "impl_item" if !is_field("body") => return Some(TypeContext::ImplHeaders),
+                "block" | "declaration_list" => return None,
+                _ => {}
+            }
+            child = parent;
+        }
+        None
+    }
+}
+
+fn visit_vertical(
+    cursor: &mut TreeCursor,
+    admits: &dyn Fn(Node) -> bool,
+    acc: &mut Vec<TypePosInfo>,
+) {
+    if cursor.goto_first_child() {
+        visit_horizontal(cursor, admits, acc);
+        cursor.goto_parent();
+    }
+}
+
+fn visit_horizontal(
+    cursor: &mut TreeCursor,
+    admits: &dyn Fn(Node) -> bool,
+    acc: &mut Vec<TypePosInfo>,
+) {
+    loop {
+        find_type(cursor, admits, acc);
+
+        visit_vertical(cursor, admits, acc);
+
+        if !cursor.goto_next_sibling() {
+            break;
+        }
+    }
+}
+
+pub fn find_type(
+    cursor: &mut TreeCursor,
+    admits: &dyn Fn(Node) -> bool,
+    acc: &mut Vec<TypePosInfo>,
+) {
+    let node = cursor.node();
+    match node.kind() {
+        kind if TYPE_KINDS.contains(&kind) && admits(node) => {
+            let kind = node.kind();
+
+            let type_info: TypePosInfo = (MutationSite::of(node), kind);
+            tracing::trace!(?type_info, "type");
+            acc.push(type_info);
+        }
+        _ => {} // Other node kinds can be handled as needed
+    }
+}
+
+static NEW_EXPRS: [Template; 4] = [
+    Template::malformed(TYPE, ""),
+    Template::new(TYPE, "i32"),
+    Template::new(TYPE, "str"),
+    Template::new(TYPE, "Copy"),
+];
+
+pub fn modify_types(structs: &[TypePosInfo], types: &[Template]) -> Vec<Mutation> {
+    let mut modified_versions = Vec::with_capacity(structs.len() * types.len());
+    for &(site, kind) in structs.iter() {
+        for n in types.iter() {
+            modified_versions.push(Mutation {
+                start: site.start_byte,
+                end: site.end_byte,
+                replacement: n.text.to_string(),
+                kind,
+            });
+        }
+    }
+
+    modified_versions
+}
+
+/// Where replacement types come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum TypePool {
+    /// Nothing, `i32`, `str` and `Copy`
+    #[default]
+    Fixed,
+    /// The other type names defined or used in the same file
+    Harvested,
+}
+
+/// Replacements tried per type name under [`TypePool::Harvested`].
+const HARVESTED_PER_SITE: usize = 8;
+
+/// Every `type_identifier` swapped for up to [`HARVESTED_PER_SITE`] other
+/// type names of the file, sampled per file.
+fn harvested_types(source_code: &str, tree: &Tree, admits: &dyn Fn(Node) -> bool) -> Vec<Mutation> {
+    let mut sites = Vec::new();
+    let mut names: Vec<&str> = Vec::new();
+    let mut seen = HashSet::new();
+    for_each_node(tree.root_node(), &mut |node| {
+        if node.kind() == "type_identifier" {
+            let name = node_text(node, source_code);
+            if seen.insert(name) {
+                names.push(name);
+            }
+            if admits(node) {
+                sites.push(node);
+            }
+        }
+    });
+
+    let mut rng = Rng::from_text(source_code);
+    let mut acc = Vec::new();
+    for site in sites {
+        let own = node_text(site, source_code);
+        let others: Vec<&str> = names.iter().copied().filter(|&n| n != own).collect();
+        for i in rng::sample_indices(&mut rng, others.len(), HARVESTED_PER_SITE) {
+            acc.push(Mutation::new(site, others[i].to_string()));
+        }
+    }
+    acc
+}
+
+/// Replaces every type with nothing, `i32`, `str` and `Copy`, or with the
+/// other type names of the file.
+#[derive(Default)]
+pub struct TypenameCrusher {
+    pub pool: TypePool,
+    pub positions: TypePositions,
+    /// The contexts types are mutated in, all of them when empty.
+    pub contexts: Vec<TypeContext>,
+    /// Replacements of the fixed pool in place of the built-in ones, when
+    /// not empty.
+    pub types: &'static [Template],
+}
+
+impl TypenameCrusher {
+    /// Whether the type `node` is in the positions and contexts mutated.
+    fn admits(&self, node: Node) -> bool {
+        self.positions.admits(node)
+            && (self.contexts.is_empty()
+                || TypeContext::of(node).is_some_and(|c| self.contexts.contains(&c)))
+    }
+}
+
+impl Mutator for TypenameCrusher {
+    fn name(&self) -> &'static str {
+        "typenames"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Replaces every type with nothing, `i32`, `str` and `Copy`, or with the other type names of the file.",
+            node_kinds: &TYPE_KINDS,
+            example: "fn foo(a: t) {}\n",
+            flags: &[
+                (
+                    "--type-pool harvested",
+                    "swap each type name for up to 8 others defined or used in the file",
+                ),
+                (
+                    "--type TYPE",
+                    "replace types with these instead of nothing, `i32`, `str` and `Copy`, may be repeated",
+                ),
+                (
+                    "--positions defs|uses|both",
+                    "mutate only where types are defined (`struct Foo`, `<T>`), only where they are used, or both",
+                ),
+                (
+                    "--contexts fields,params,returns,bounds,impl-headers",
+                    "mutate only the types of fields, parameters and returns, trait bounds and impl headers of these",
+                ),
+            ],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let admits = |node: Node<'_>| self.admits(node);
+        if self.pool == TypePool::Harvested {
+            return harvested_types(source_code, tree, &admits);
+        }
+        let mut found_structs: Vec<TypePosInfo> = Vec::new();
+        visit_vertical(&mut tree.walk(), &admits, &mut found_structs);
+
+        modify_types(&found_structs, self.templates())
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        match self.pool {
+            TypePool::Fixed if self.types.is_empty() => &NEW_EXPRS,
+            TypePool::Fixed => self.types,
+            TypePool::Harvested => &[],
+        }
+    }
+}
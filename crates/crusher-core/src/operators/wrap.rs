@@ -0,0 +1,123 @@
+use tree_sitter::{Node, Tree};
+
+use super::{
+    context::TYPE, for_each_node, node_text, single_type_argument, Mutation, Mutator, OperatorDoc,
+    Template,
+};
+
+const OPTION: Template = Template::new(TYPE, "Option<$type>");
+const RESULT: Template = Template::new(TYPE, "Result<$type, ()>");
+const UNWRAPPED: Template = Template::new(TYPE, "$type");
+static TEMPLATES: [Template; 3] = [OPTION, RESULT, UNWRAPPED];
+
+/// Wraps the types of fields and the return types of functions in `Option`
+/// and `Result`, and unwraps `Option<T>` into `T`: the types stay
+/// well-formed, the places using them break.
+pub struct WrapCrusher;
+
+impl Mutator for WrapCrusher {
+    fn name(&self) -> &'static str {
+        "wrap"
+    }
+
+    fn doc(&self) -> OperatorDoc {
+        OperatorDoc {
+            summary: "Wraps field and return types in `Option<..>` and `Result<.., ()>`, and unwraps every `Option<T>` into `T`.",
+            node_kinds: &["field_declaration", "ordered_field_declaration_list", "function_item", "function_signature_item", "generic_type"],
+            example: "struct S { a: u8, b: Option<u8> }\nfn f(s: S) -> u8 { s.a }\n",
+            flags: &[],
+        }
+    }
+
+    fn mutations(&self, source_code: &str, tree: &Tree) -> Vec<Mutation> {
+        let mut acc = Vec::new();
+        for_each_node(tree.root_node(), &mut |node| {
+            if is_wrapped(node) {
+                let text = node_text(node, source_code);
+                for template in [OPTION, RESULT] {
+                    acc.push(Mutation::new(node, template.instantiate(&[("type", text)])));
+                }
+            }
+            if let Some(inner) = single_type_argument(source_code, node, "Option") {
+                let inner = node_text(inner, source_code);
+                acc.push(Mutation::new(
+                    node,
+                    UNWRAPPED.instantiate(&[("type", inner)]),
+                ));
+            }
+        });
+        acc
+    }
+
+    fn templates(&self) -> &'static [Template] {
+        &TEMPLATES
+    }
+}
+
+/// Whether `node` is the whole type of a field or the return type of a
+/// function.
+fn is_wrapped(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    match parent.kind() {
+        "field_declaration" => parent.child_by_field_name("type") == Some(node),
+        "ordered_field_declaration_list" => {
+            let mut cursor = parent.walk();
+            let is_field = parent
+                .children_by_field_name("type", &mut cursor)
+                .any(|ty| ty == node);
+            is_field
+        }
+        "function_item" | "function_signature_item" => {
+            parent.child_by_field_name("return_type") == Some(node)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crushed(source_code: &str) -> Vec<String> {
+        let mut parser = tree_sitter::Parser::new();
+        parser.set_language(&tree_sitter_rust::language()).unwrap();
+        let tree = parser.parse(source_code, None).unwrap();
+        WrapCrusher
+            .mutations(source_code, &tree)
+            .iter()
+            .map(|mutation| mutation.apply(source_code))
+            .collect()
+    }
+
+    #[test]
+    fn fields_and_return_types() {
+        assert_eq!(
+            crushed("struct T(pub u8); trait Tr { fn f(&self, a: u8) -> Self; }"),
+            [
+                "struct T(pub Option<u8>); trait Tr { fn f(&self, a: u8) -> Self; }",
+                "struct T(pub Result<u8, ()>); trait Tr { fn f(&self, a: u8) -> Self; }",
+                "struct T(pub u8); trait Tr { fn f(&self, a: u8) -> Option<Self>; }",
+                "struct T(pub u8); trait Tr { fn f(&self, a: u8) -> Result<Self, ()>; }",
+            ]
+        );
+    }
+
+    #[test]
+    fn options_unwrapped_anywhere() {
+        assert_eq!(
+            crushed("fn f(a: core::option::Option</* c */ u8>) { Option::<u8>::None; }"),
+            ["fn f(a: u8) { Option::<u8>::None; }"]
+        );
+        // a field of `Option<T>` is wrapped as a whole and unwrapped
+        assert_eq!(
+            crushed("struct S { o: Option<u8> }"),
+            [
+                "struct S { o: Option<Option<u8>> }",
+                "struct S { o: Result<Option<u8>, ()> }",
+                "struct S { o: u8 }",
+            ]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Test-case reduction: delete syntax nodes for as long as a predicate on
+//! the source keeps holding, like a small built-in creduce.
+
+use crate::{
+    crusher::Crusher,
+    operators::{for_each_node, Mutation},
+};
+
+/// Shrink `source_code` by deleting whole syntax nodes, biggest first, as
+/// long as `interesting` holds for the result. `interesting` is called at
+/// most `budget` times; the returned source satisfies it (or is the input).
+pub fn minimize(
+    source_code: &str,
+    budget: usize,
+    mut interesting: impl FnMut(&str) -> bool,
+) -> String {
+    let mut crusher = Crusher::new();
+    let mut current = source_code.to_string();
+    let mut calls = 0;
+    'restart: loop {
+        for deletion in deletions(&mut crusher, &current) {
+            if calls == budget {
+                break 'restart;
+            }
+            calls += 1;
+            let candidate = deletion.apply(&current);
+            if interesting(&candidate) {
+                current = candidate;
+                continue 'restart;
+            }
+        }
+        break;
+    }
+    current
+}
+
+/// Deleting every named node below the root, biggest first.
+fn deletions(crusher: &mut Crusher, source_code: &str) -> Vec<Mutation> {
+    let tree = crusher.parse(source_code, None).unwrap();
+
+    let root = tree.root_node();
+    let mut acc = Vec::new();
+    for_each_node(root, &mut |node| {
+        if node != root && node.is_named() && node.start_byte() < node.end_byte() {
+            acc.push(Mutation::new(node, String::new()));
+        }
+    });
+    acc.sort_by_key(|m| (std::cmp::Reverse(m.end - m.start), m.start));
+    acc.dedup_by_key(|m| (m.start, m.end));
+    acc
+}
@@ -0,0 +1,135 @@
+//! Small deterministic generator for the sampling done by operators and the
+//! driver; reproducibility matters more here than statistical quality.
+
+/// splitmix64.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    /// Seeded from an FNV-1a hash of `text`, e.g. the file being mutated.
+    pub fn from_text(text: &str) -> Rng {
+        Rng::derive(0, text)
+    }
+
+    /// A stream for `text` under the run-wide `seed`, so every file gets its
+    /// own sequence and the same seed reproduces all of them.
+    pub fn derive(seed: u64, text: &str) -> Rng {
+        Rng::new(fnv1a(text) ^ seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform-ish index in `0..n`; `n` must not be zero.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// FNV-1a hash of `text`, the same on every platform and release.
+pub fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |h, b| {
+        (h ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// `count` distinct indices out of `0..len` in increasing order, or all of
+/// them when `count >= len`.
+pub fn sample_indices(rng: &mut Rng, len: usize, count: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..len).collect();
+    if count < len {
+        // partial Fisher-Yates
+        for i in 0..count {
+            let j = i + rng.below(len - i);
+            indices.swap(i, j);
+        }
+        indices.truncate(count);
+        indices.sort_unstable();
+    }
+    indices
+}
+
+/// All items of `groups` in a random order where, at every step, a group is
+/// picked in proportion to its weight among those with items left and one of
+/// its items at random; any prefix of the result then mixes the groups after
+/// their weights, as far as they last. Groups of weight zero are dropped.
+pub fn weighted_draw<T>(rng: &mut Rng, groups: Vec<(f64, Vec<T>)>) -> Vec<T> {
+    let mut groups: Vec<(f64, Vec<T>)> = groups
+        .into_iter()
+        .filter(|(weight, items)| *weight > 0.0 && !items.is_empty())
+        .collect();
+    let mut drawn = Vec::with_capacity(groups.iter().map(|(_, items)| items.len()).sum());
+    while !groups.is_empty() {
+        let total: f64 = groups.iter().map(|(weight, _)| weight).sum();
+        // 53 random bits, a uniform float in [0, total)
+        let mut point = (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * total;
+        let mut picked = groups.len() - 1;
+        for (i, (weight, _)) in groups.iter().enumerate() {
+            if point < *weight {
+                picked = i;
+                break;
+            }
+            point -= weight;
+        }
+        let items = &mut groups[picked].1;
+        let item = items.swap_remove(rng.below(items.len()));
+        drawn.push(item);
+        if items.is_empty() {
+            groups.remove(picked);
+        }
+    }
+    drawn
+}
+
+/// A uniform sample of at most `capacity` items out of a stream of unknown
+/// length (algorithm R), for sampling without holding the whole stream.
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: usize,
+    /// Sampled items with their position in the stream.
+    items: Vec<(usize, T)>,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize) -> Reservoir<T> {
+        Reservoir {
+            capacity,
+            seen: 0,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn offer(&mut self, rng: &mut Rng, item: T) {
+        if self.items.len() < self.capacity {
+            self.items.push((self.seen, item));
+        } else {
+            let j = rng.below(self.seen + 1);
+            if j < self.capacity {
+                self.items[j] = (self.seen, item);
+            }
+        }
+        self.seen += 1;
+    }
+
+    /// Number of items offered so far.
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The sample, in stream order.
+    pub fn into_items(mut self) -> Vec<T> {
+        self.items.sort_by_key(|(position, _)| *position);
+        self.items.into_iter().map(|(_, item)| item).collect()
+    }
+}
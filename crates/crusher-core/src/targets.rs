@@ -0,0 +1,259 @@
+//! What a mutation is aimed at, for letting users narrow a run down to some
+//! of the code in a file.
+
+use std::ops::Range;
+
+use tree_sitter::{Node, Tree};
+
+use crate::operators::{for_each_node, node_text, Mutation};
+
+/// Items whose `name` field names the type they define.
+const TYPE_ITEMS: [&str; 6] = [
+    "associated_type",
+    "enum_item",
+    "struct_item",
+    "trait_item",
+    "type_item",
+    "union_item",
+];
+
+/// Which of the mutations of a file a run keeps, by what they are aimed at.
+pub trait Targets: Sync {
+    /// Drop the mutations of `source_code` these targets do not admit.
+    fn retain(&self, tree: &Tree, source_code: &str, mutations: &mut Vec<Mutation>);
+}
+
+/// Every mutation.
+pub struct Everything;
+
+impl Targets for Everything {
+    fn retain(&self, _: &Tree, _: &str, _: &mut Vec<Mutation>) {}
+}
+
+/// Drop the mutations of `source_code` that leave it as it is, and those
+/// `targets` does not admit; what every run keeps of what the operators
+/// propose.
+pub fn retain_admitted(
+    tree: &Tree,
+    source_code: &str,
+    targets: &dyn Targets,
+    mutations: &mut Vec<Mutation>,
+) {
+    mutations.retain(|m| !m.is_noop(source_code));
+    targets.retain(tree, source_code, mutations);
+}
+
+/// The smallest node covering the text `mutation` rewrites, or the point it
+/// inserts at.
+pub fn target_node<'t>(tree: &'t Tree, mutation: &Mutation) -> Node<'t> {
+    node_for_span(tree, mutation.start, mutation.end)
+}
+
+fn node_for_span(tree: &Tree, start: usize, end: usize) -> Node<'_> {
+    tree.root_node()
+        .descendant_for_byte_range(start, end)
+        .unwrap_or(tree.root_node())
+}
+
+/// The pre-order number, counting from 0 at the root, of the node a
+/// mutation of `start..end` is aimed at: a position in the file that stays
+/// put across runs and operators.
+pub fn node_id(tree: &Tree, start: usize, end: usize) -> usize {
+    let target = node_for_span(tree, start, end);
+    let mut count = 0;
+    let mut id = 0;
+    for_each_node(tree.root_node(), &mut |node| {
+        if node == target {
+            id = count;
+        }
+        count += 1;
+    });
+    id
+}
+
+/// The struct or type name a mutation of `start..end` is aimed at: the type it rewrites, or
+/// else the innermost struct, enum, trait, type alias or `impl` around it.
+/// `None` for code outside of all of those, such as free functions.
+pub fn target_name<'s>(
+    tree: &Tree,
+    source_code: &'s str,
+    start: usize,
+    end: usize,
+) -> Option<&'s str> {
+    let mut node = Some(node_for_span(tree, start, end));
+    while let Some(n) = node {
+        if n.kind() == "type_identifier" {
+            return Some(node_text(n, source_code));
+        }
+        if n.kind().ends_with("_type") {
+            if let Some(name) = first_type_identifier(n) {
+                return Some(node_text(name, source_code));
+            }
+        }
+        let field = match n.kind() {
+            "scoped_type_identifier" => Some("name"),
+            "impl_item" => Some("type"),
+            kind if TYPE_ITEMS.contains(&kind) => Some("name"),
+            _ => None,
+        };
+        if let Some(name) = field.and_then(|field| n.child_by_field_name(field)) {
+            return target_name_of_type(name, source_code);
+        }
+        node = n.parent();
+    }
+    None
+}
+
+/// `Foo` for `Foo`, `Foo<T>`, `a::Foo` or `&Foo`.
+fn target_name_of_type<'s>(node: Node, source_code: &'s str) -> Option<&'s str> {
+    let name = if node.kind() == "type_identifier" {
+        node
+    } else if let Some(name) = node.child_by_field_name("name") {
+        name
+    } else {
+        first_type_identifier(node)?
+    };
+    Some(node_text(name, source_code))
+}
+
+fn first_type_identifier(node: Node) -> Option<Node> {
+    let mut found = None;
+    for_each_node(node, &mut |n| {
+        if found.is_none() && n.kind() == "type_identifier" {
+            found = Some(n);
+        }
+    });
+    found
+}
+
+/// Whether `mutation` lies in test code: a `#[test]` function (also
+/// `#[tokio::test]` and the like), a `#[cfg(test)]` item, or a module or
+/// file with `#![cfg(test)]`. Attributes of such items count as inside.
+pub fn in_test_code(tree: &Tree, source_code: &str, mutation: &Mutation) -> bool {
+    let mut node = Some(target_node(tree, mutation));
+    while let Some(n) = node {
+        let item = if n.kind() == "attribute_item" {
+            attached_item(n)
+        } else {
+            Some(n)
+        };
+        if item
+            .is_some_and(|item| outer_attributes(item).any(|a| is_test_attribute(a, source_code)))
+        {
+            return true;
+        }
+        if matches!(n.kind(), "source_file" | "declaration_list") {
+            let mut cursor = n.walk();
+            let mut children = n.children(&mut cursor);
+            if children
+                .any(|c| c.kind() == "inner_attribute_item" && is_test_attribute(c, source_code))
+            {
+                return true;
+            }
+        }
+        node = n.parent();
+    }
+    false
+}
+
+fn is_comment(node: Node) -> bool {
+    matches!(node.kind(), "line_comment" | "block_comment")
+}
+
+/// The attributes in front of `item`, nearest first.
+fn outer_attributes(item: Node) -> impl Iterator<Item = Node> {
+    std::iter::successors(item.prev_sibling(), |n| n.prev_sibling())
+        .filter(|n| !is_comment(*n))
+        .take_while(|n| n.kind() == "attribute_item")
+}
+
+/// The item an outer attribute belongs to.
+fn attached_item(attribute: Node) -> Option<Node> {
+    std::iter::successors(attribute.next_sibling(), |n| n.next_sibling())
+        .find(|n| n.kind() != "attribute_item" && !is_comment(*n))
+}
+
+/// `#[test]`, `#[cfg(test)]`, `#[tokio::test]` and the like, inner or outer.
+fn is_test_attribute(attribute: Node, source_code: &str) -> bool {
+    let Some(meta) = attribute.named_child(0) else {
+        return false;
+    };
+    let text: String = node_text(meta, source_code)
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    text == "test" || text == "cfg(test)" || text.ends_with("::test")
+}
+
+/// Items that carry a visibility of their own.
+const VISIBLE_ITEMS: [&str; 10] = [
+    "const_item",
+    "enum_item",
+    "function_item",
+    "function_signature_item",
+    "mod_item",
+    "static_item",
+    "struct_item",
+    "trait_item",
+    "type_item",
+    "union_item",
+];
+
+/// Whether `mutation` lies in the public API: in an item declared plain
+/// `pub` whose enclosing modules are `pub` as well. Members of traits and
+/// trait impls have no visibility of their own and go with the trait or
+/// impl. Mutations outside of every item, such as new crate attributes,
+/// are not part of any item's API.
+pub fn in_public_api(tree: &Tree, mutation: &Mutation) -> bool {
+    let mut in_item = false;
+    let mut node = Some(target_node(tree, mutation));
+    while let Some(n) = node {
+        let item = if n.kind() == "attribute_item" {
+            attached_item(n)
+        } else {
+            Some(n)
+        };
+        if let Some(item) = item.filter(|item| VISIBLE_ITEMS.contains(&item.kind())) {
+            if !in_trait_or_trait_impl(item) && !is_pub(item) {
+                return false;
+            }
+            in_item = true;
+        }
+        node = n.parent();
+    }
+    in_item
+}
+
+fn is_pub(item: Node) -> bool {
+    let mut cursor = item.walk();
+    let mut children = item.children(&mut cursor);
+    children.any(|c| c.kind() == "visibility_modifier" && c.child_count() == 1)
+}
+
+fn in_trait_or_trait_impl(item: Node) -> bool {
+    let Some(owner) = item.parent().and_then(|body| body.parent()) else {
+        return false;
+    };
+    owner.kind() == "trait_item"
+        || owner.kind() == "impl_item" && owner.child_by_field_name("trait").is_some()
+}
+
+/// The bytes of lines `first..=last` of `source_code`, counting from 1 and
+/// including the final newline.
+pub fn line_span(source_code: &str, first: usize, last: usize) -> Range<usize> {
+    let mut line_starts = std::iter::once(0)
+        .chain(source_code.match_indices('\n').map(|(i, _)| i + 1))
+        .chain(std::iter::once(source_code.len()));
+    let start = line_starts.nth(first - 1).unwrap_or(source_code.len());
+    let end = line_starts.nth(last - first).unwrap_or(source_code.len());
+    start..end.max(start)
+}
+
+/// Whether `mutation` rewrites text in `span`, or inserts within or at the
+/// edges of it.
+pub fn intersects(mutation: &Mutation, span: &Range<usize>) -> bool {
+    if mutation.start == mutation.end {
+        return span.start <= mutation.start && mutation.start <= span.end;
+    }
+    mutation.start < span.end && span.start < mutation.end
+}
@@ -0,0 +1,831 @@
+//! `--cargo-project`: apply every mutant inside a private copy of a Cargo
+//! project, or the project itself, and run its test suite against it like
+//! cargo-mutants does, or `cargo check` to see what the compiler makes of it.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use crusher_core::{
+    crusher::Crusher,
+    operators::{Mutation, Mutator},
+    targets::{self, Targets},
+};
+use serde_json::{json, Map, Value};
+use walkdir::WalkDir;
+
+use crate::{
+    rustc::{self, Checked, Verdict, VERDICT_LABELS},
+    subprocess::{self, Exit, Finished},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum CargoMode {
+    /// Run `cargo test` per mutant and report which tests kill it
+    Mutate,
+    /// Run `cargo check` per mutant and classify what the compiler did
+    Check,
+}
+
+impl CargoMode {
+    /// The cargo command run for the baseline and for every mutant.
+    fn args(self) -> &'static [&'static str] {
+        match self {
+            CargoMode::Mutate => &["test", "--workspace"],
+            CargoMode::Check => &["check", "--workspace", "--all-targets"],
+        }
+    }
+}
+
+pub struct CargoOptions<'a> {
+    pub project: PathBuf,
+    pub mode: CargoMode,
+    /// Mutate the project itself, restoring every file after its mutants,
+    /// instead of copies of it; needs `jobs` 1.
+    pub in_place: bool,
+    pub jobs: usize,
+    pub timeout: Option<Duration>,
+    pub output_dir: PathBuf,
+    /// Which of the mutations of every file are tried.
+    pub targets: &'a dyn Targets,
+}
+
+struct Candidate {
+    id: usize,
+    operator: &'static str,
+    /// Relative to the project root.
+    file: PathBuf,
+    mutation: Mutation,
+}
+
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// At least one test failed; holds the failing test names.
+    Killed(Vec<String>),
+    Survived,
+    /// The mutant does not build, so the tests never ran.
+    Unviable,
+    Timeout,
+}
+
+impl Outcome {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::Killed(_) => "killed",
+            Outcome::Survived => "survived",
+            Outcome::Unviable => "unviable",
+            Outcome::Timeout => "timeout",
+        }
+    }
+}
+
+fn is_skipped_dir(name: &str) -> bool {
+    matches!(name, "target" | "tests" | "benches" | "examples") || name.starts_with('.')
+}
+
+/// Library and binary sources of the project, relative to its root.
+fn project_sources(project: &Path) -> Vec<PathBuf> {
+    WalkDir::new(project)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !is_skipped_dir(&e.file_name().to_string_lossy()))
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && e.path().extension().is_some_and(|x| x == "rs"))
+        .filter_map(|e| Some(e.path().strip_prefix(project).ok()?.to_path_buf()))
+        .collect()
+}
+
+fn copy_tree(from: &Path, to: &Path, with_target: bool) -> Result<()> {
+    for entry in WalkDir::new(from).into_iter().filter_entry(|e| {
+        let name = e.file_name().to_string_lossy();
+        e.depth() == 0 || !(name == ".git" || (!with_target && name == "target"))
+    }) {
+        let entry = entry.with_context(|| format!("Cannot copy {:?}", from))?;
+        let Ok(relative) = entry.path().strip_prefix(from) else {
+            continue;
+        };
+        let dest = to.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest).with_context(|| format!("Cannot create {:?}", dest))?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &dest)
+                .with_context(|| format!("Cannot copy {:?} to {:?}", entry.path(), dest))?;
+        }
+    }
+    Ok(())
+}
+
+/// Set by the first Ctrl-C of an in-place run, which then stops once the
+/// files being judged are restored.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Have Ctrl-C set [`INTERRUPTED`] instead of ending the process; a
+/// second one ends it.
+fn catch_interrupt() {
+    #[cfg(unix)]
+    {
+        extern "C" fn on_interrupt(_: libc::c_int) {
+            INTERRUPTED.store(true, Ordering::Relaxed);
+            unsafe {
+                libc::signal(libc::SIGINT, libc::SIG_DFL);
+            }
+        }
+        unsafe {
+            libc::signal(
+                libc::SIGINT,
+                on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+}
+
+/// The directory of the copies of the project, removed with them however
+/// the run ends.
+struct Scratch(PathBuf);
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn cargo(workdir: &Path, args: &[&str]) -> Command {
+    let mut command = Command::new("cargo");
+    command
+        .args(args)
+        .current_dir(workdir)
+        .env("CARGO_TARGET_DIR", workdir.join("target"));
+    command
+}
+
+/// `(name, passed)` for every `test <name> ... <status>` line of libtest output.
+fn test_results(stdout: &str) -> Vec<(String, bool)> {
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let (name, status) = line.strip_prefix("test ")?.rsplit_once(" ... ")?;
+            match status.trim() {
+                "ok" => Some((name.to_string(), true)),
+                "FAILED" => Some((name.to_string(), false)),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Runs `cargo` in `workdir`, failing only when it cannot be started.
+fn run_cargo(workdir: &Path, args: &[&str], timeout: Option<Duration>) -> Result<Finished> {
+    subprocess::run_with_timeout(cargo(workdir, args), timeout)
+        .with_context(|| format!("Cannot run `cargo {}` in {:?}", args.join(" "), workdir))
+}
+
+fn test(workdir: &Path, timeout: Duration) -> Result<Outcome> {
+    let build = run_cargo(workdir, &["test", "--workspace", "--no-run"], Some(timeout))?;
+    Ok(match build.exit {
+        Exit::TimedOut => Outcome::Timeout,
+        _ if !build.success() => Outcome::Unviable,
+        _ => {
+            let test = run_cargo(workdir, CargoMode::Mutate.args(), Some(timeout))?;
+            match test.exit {
+                Exit::TimedOut => Outcome::Timeout,
+                _ if test.success() => Outcome::Survived,
+                _ => Outcome::Killed(
+                    test_results(&test.stdout)
+                        .into_iter()
+                        .filter(|(_, passed)| !passed)
+                        .map(|(name, _)| name)
+                        .collect(),
+                ),
+            }
+        }
+    })
+}
+
+/// Apply `candidate` in `workdir`, run `judge` on the result and restore the
+/// original, also when writing the mutant or `judge` fails or panics.
+fn with_mutant<R>(
+    workdir: &Path,
+    original: &str,
+    candidate: &Candidate,
+    judge: impl FnOnce() -> Result<R>,
+) -> Result<R> {
+    let path = workdir.join(&candidate.file);
+    let restore = Restore {
+        path: &path,
+        original,
+        done: false,
+    };
+    fs::write(&path, candidate.mutation.apply(original))
+        .with_context(|| format!("Cannot write a mutant to {:?}", path))?;
+    let result = judge();
+    restore.restore()?;
+    result
+}
+
+/// Puts `original` back into `path` when dropped, unless
+/// [`Restore::restore`] did already.
+struct Restore<'a> {
+    path: &'a Path,
+    original: &'a str,
+    done: bool,
+}
+
+impl Restore<'_> {
+    fn restore(mut self) -> Result<()> {
+        self.done = true;
+        fs::write(self.path, self.original)
+            .with_context(|| format!("Cannot restore {:?}", self.path))
+    }
+}
+
+impl Drop for Restore<'_> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = fs::write(self.path, self.original);
+        }
+    }
+}
+
+fn check(workdir: &Path, timeout: Duration) -> Result<Verdict> {
+    let finished = run_cargo(workdir, CargoMode::Check.args(), Some(timeout))?;
+    let verdict = rustc::classify(&finished.exit, &finished.stderr);
+    // cargo reports a rustc killed by a signal rather than dying of it
+    let signal = finished
+        .stderr
+        .split_once("(signal: ")
+        .and_then(|(_, rest)| {
+            rest.split(|c: char| !c.is_ascii_digit())
+                .next()?
+                .parse()
+                .ok()
+        });
+    Ok(match (verdict, signal) {
+        (Verdict::Error(_), Some(signal)) => Verdict::Crash(signal),
+        (verdict, _) => verdict,
+    })
+}
+
+pub fn run(mutators: &[&dyn Mutator], options: &CargoOptions<'_>) -> Result<()> {
+    let project = options
+        .project
+        .canonicalize()
+        .with_context(|| format!("Cannot open the Cargo project {:?}", options.project))?;
+
+    let mut originals: HashMap<PathBuf, String> = HashMap::new();
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let mut crusher = Crusher::new();
+    for file in project_sources(&project) {
+        let path = project.join(&file);
+        let source_code =
+            fs::read_to_string(&path).with_context(|| format!("Cannot read {:?}", path))?;
+        let tree = crusher
+            .parse(&source_code, None)
+            .with_context(|| format!("Cannot parse {:?}", path))?;
+        for &mutator in mutators {
+            let mut found = mutator.mutations(&source_code, &tree);
+            targets::retain_admitted(&tree, &source_code, options.targets, &mut found);
+            for mutation in found {
+                candidates.push(Candidate {
+                    id: candidates.len(),
+                    operator: mutator.name(),
+                    file: file.clone(),
+                    mutation,
+                });
+            }
+        }
+        originals.insert(file, source_code);
+    }
+    println!("Number of mutants: {}", candidates.len());
+
+    let scratch = Scratch(std::env::temp_dir().join(format!("crusher-cargo-{}", process::id())));
+    let workdirs: Vec<PathBuf> = if options.in_place {
+        catch_interrupt();
+        vec![project.clone()]
+    } else {
+        (0..options.jobs.max(1))
+            .map(|i| scratch.0.join(format!("worker_{}", i)))
+            .collect()
+    };
+    if !options.in_place {
+        copy_tree(&project, &workdirs[0], false)?;
+    }
+
+    // the baseline must pass, and gives the list of tests and a timeout scale
+    let baseline = run_cargo(&workdirs[0], options.mode.args(), None)?;
+    if !baseline.success() {
+        bail!(
+            "Baseline `cargo {}` fails in {:?}:\n{}",
+            options.mode.args()[0],
+            project,
+            baseline.stderr
+        );
+    }
+    let mut tests: Vec<String> = test_results(&baseline.stdout)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    tests.sort();
+    tests.dedup();
+    let timeout = options
+        .timeout
+        .unwrap_or_else(|| (baseline.elapsed * 5).max(Duration::from_secs(30)));
+    match options.mode {
+        CargoMode::Mutate => println!(
+            "Baseline passed with {} tests in {:.1}s, timeout per mutant {:.1}s",
+            tests.len(),
+            baseline.elapsed.as_secs_f64(),
+            timeout.as_secs_f64()
+        ),
+        CargoMode::Check => println!(
+            "Baseline checked in {:.1}s, timeout per mutant {:.1}s",
+            baseline.elapsed.as_secs_f64(),
+            timeout.as_secs_f64()
+        ),
+    }
+    for workdir in &workdirs[1..] {
+        copy_tree(&workdirs[0], workdir, true)?;
+    }
+
+    match options.mode {
+        CargoMode::Mutate => {
+            let outcomes = evaluate_all(&workdirs, &candidates, |workdir, candidate| {
+                let original = &originals[&candidate.file];
+                let outcome = with_mutant(workdir, original, candidate, || test(workdir, timeout))?;
+                let label = outcome.label();
+                Ok((outcome, label))
+            });
+            drop(scratch);
+            write_reports(
+                &options.output_dir,
+                mutators,
+                &originals,
+                &candidates,
+                &outcomes?,
+                &tests,
+            )
+        }
+        CargoMode::Check => {
+            let verdicts = evaluate_all(&workdirs, &candidates, |workdir, candidate| {
+                let original = &originals[&candidate.file];
+                let verdict =
+                    with_mutant(workdir, original, candidate, || check(workdir, timeout))?;
+                let label = verdict.label();
+                Ok((verdict, label))
+            });
+            drop(scratch);
+            write_check_report(
+                &options.output_dir,
+                mutators,
+                &originals,
+                &candidates,
+                &verdicts?,
+            )
+        }
+    }
+}
+
+/// Run `judge` on every candidate, one worker per workdir, printing the
+/// label of every result as it comes in; the first error stops the workers
+/// and is returned.
+fn evaluate_all<R: Send>(
+    workdirs: &[PathBuf],
+    candidates: &[Candidate],
+    judge: impl Fn(&Path, &Candidate) -> Result<(R, &'static str)> + Sync,
+) -> Result<Vec<R>> {
+    let total = candidates.len();
+    let queue = Mutex::new(candidates.iter());
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    let mut results: Vec<Option<R>> = (0..total).map(|_| None).collect();
+    let mut error = None;
+    thread::scope(|scope| {
+        for workdir in workdirs {
+            let sender = sender.clone();
+            let (queue, failed) = (&queue, &failed);
+            let judge = &judge;
+            scope.spawn(move || loop {
+                if failed.load(Ordering::Relaxed) || INTERRUPTED.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Some(candidate) = queue.lock().unwrap().next() else {
+                    break;
+                };
+                let result = judge(workdir, candidate);
+                if result.is_err() {
+                    failed.store(true, Ordering::Relaxed);
+                }
+                // the receiver only goes away once every worker is done
+                let _ = sender.send((candidate.id, result));
+            });
+        }
+        drop(sender);
+        for (done, (id, result)) in receiver.iter().enumerate() {
+            let (result, label) = match result {
+                Ok(result) => result,
+                Err(e) => {
+                    error.get_or_insert(e);
+                    continue;
+                }
+            };
+            tracing::info!(
+                "[{}/{}] {} {}..{}: {}",
+                done + 1,
+                total,
+                candidates[id].file.display(),
+                candidates[id].mutation.start,
+                candidates[id].mutation.end,
+                label
+            );
+            results[id] = Some(result);
+        }
+    });
+    if error.is_none() && INTERRUPTED.load(Ordering::Relaxed) {
+        bail!("Interrupted, with every mutated file restored");
+    }
+    match error {
+        Some(e) => Err(e),
+        // without an error every candidate has its result
+        None => Ok(results.into_iter().flatten().collect()),
+    }
+}
+
+/// Outcome counts of a group of mutants.
+#[derive(Debug, Clone, Copy, Default)]
+struct Tally {
+    killed: usize,
+    survived: usize,
+    unviable: usize,
+    timeout: usize,
+}
+
+impl Tally {
+    fn add(&mut self, outcome: &Outcome) {
+        match outcome {
+            Outcome::Killed(_) => self.killed += 1,
+            Outcome::Survived => self.survived += 1,
+            Outcome::Unviable => self.unviable += 1,
+            Outcome::Timeout => self.timeout += 1,
+        }
+    }
+
+    fn mutants(&self) -> usize {
+        self.killed + self.survived + self.unviable + self.timeout
+    }
+
+    /// Killed among the mutants the tests ran to the end on.
+    fn score(&self) -> f64 {
+        if self.killed + self.survived == 0 {
+            0.0
+        } else {
+            self.killed as f64 / (self.killed + self.survived) as f64
+        }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "mutants": self.mutants(),
+            "killed": self.killed,
+            "survived": self.survived,
+            "unviable": self.unviable,
+            "timeout": self.timeout,
+            "score": four_places(self.score()),
+        })
+    }
+}
+
+/// `score` rounded to the four decimal places reports give it.
+fn four_places(score: f64) -> f64 {
+    (score * 1e4).round() / 1e4
+}
+
+/// A table of `rows` under a `group` heading, with a total line.
+fn tally_table(group: &str, rows: &[(String, Tally)], total: Tally) -> String {
+    let width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .fold("total".len().max(group.len()), usize::max);
+    let mut table = format!(
+        "{:<width$}  {:>7}  {:>6}  {:>8}  {:>8}  {:>7}  {:>6}\n",
+        group, "mutants", "killed", "survived", "unviable", "timeout", "score"
+    );
+    for (name, tally) in rows.iter().chain([&("total".to_string(), total)]) {
+        table.push_str(&format!(
+            "{:<width$}  {:>7}  {:>6}  {:>8}  {:>8}  {:>7}  {:>5.1}%\n",
+            name,
+            tally.mutants(),
+            tally.killed,
+            tally.survived,
+            tally.unviable,
+            tally.timeout,
+            tally.score() * 100.0
+        ));
+    }
+    table
+}
+
+/// 1-based `line:column` of byte `offset`.
+fn line_column(source_code: &str, offset: usize) -> (usize, usize) {
+    let before = &source_code[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+pub const SUMMARY_FILE_NAME: &str = "mutation_report.txt";
+
+fn write_reports(
+    output_dir: &Path,
+    mutators: &[&dyn Mutator],
+    originals: &HashMap<PathBuf, String>,
+    candidates: &[Candidate],
+    outcomes: &[Outcome],
+    tests: &[String],
+) -> Result<()> {
+    let count = |label: &str| outcomes.iter().filter(|o| o.label() == label).count();
+    let (killed, survived) = (count("killed"), count("survived"));
+    let mut total = Tally::default();
+    let mut by_operator: Vec<(String, Tally)> = mutators
+        .iter()
+        .map(|m| (m.name().to_string(), Tally::default()))
+        .collect();
+    let mut by_file: BTreeMap<String, Tally> = BTreeMap::new();
+    for (candidate, outcome) in candidates.iter().zip(outcomes) {
+        total.add(outcome);
+        if let Some((_, tally)) = by_operator
+            .iter_mut()
+            .find(|(name, _)| name == candidate.operator)
+        {
+            tally.add(outcome);
+        }
+        by_file
+            .entry(candidate.file.display().to_string())
+            .or_default()
+            .add(outcome);
+    }
+    let by_file: Vec<(String, Tally)> = by_file.into_iter().collect();
+    let score = total.score();
+
+    // one row per mutant, one column per test, `x` where that test killed it
+    let mut matrix = String::from("id\toperator\tfile\tstart\tend\toutcome");
+    for test in tests {
+        matrix.push('\t');
+        matrix.push_str(test);
+    }
+    matrix.push('\n');
+    for (candidate, outcome) in candidates.iter().zip(outcomes) {
+        matrix.push_str(&format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            candidate.id,
+            candidate.operator,
+            candidate.file.display(),
+            candidate.mutation.start,
+            candidate.mutation.end,
+            outcome.label()
+        ));
+        for test in tests {
+            let hit = matches!(outcome, Outcome::Killed(failed) if failed.contains(test));
+            matrix.push_str(if hit { "\tx" } else { "\t" });
+        }
+        matrix.push('\n');
+    }
+    write_report_file(&output_dir.join("kill_matrix.tsv"), &matrix)?;
+
+    let results: Vec<Value> = candidates
+        .iter()
+        .zip(outcomes)
+        .map(|(candidate, outcome)| {
+            let failed = match outcome {
+                Outcome::Killed(failed) => failed.clone(),
+                _ => Vec::new(),
+            };
+            json!({
+                "id": candidate.id,
+                "operator": candidate.operator,
+                "file": candidate.file.display().to_string(),
+                "start": candidate.mutation.start,
+                "end": candidate.mutation.end,
+                "kind": candidate.mutation.kind,
+                "original": candidate.mutation.original(&originals[&candidate.file]),
+                "replacement": candidate.mutation.replacement,
+                "outcome": outcome.label(),
+                "killed_by": failed,
+            })
+        })
+        .collect();
+    let tallies = |rows: &[(String, Tally)]| -> Map<String, Value> {
+        rows.iter()
+            .map(|(name, tally)| (name.clone(), tally.to_json()))
+            .collect()
+    };
+    let operators: Vec<&str> = mutators.iter().map(|m| m.name()).collect();
+    let report = json!({
+        "operators": operators,
+        "mutants": candidates.len(),
+        "killed": killed,
+        "survived": survived,
+        "unviable": count("unviable"),
+        "timeout": count("timeout"),
+        "score": four_places(score),
+        "by_operator": tallies(&by_operator),
+        "by_file": tallies(&by_file),
+        "results": results,
+    });
+    write_report_file(
+        &output_dir.join("mutation_report.json"),
+        &format!("{}\n", report),
+    )?;
+
+    let mut summary = tally_table("operator", &by_operator, total);
+    summary.push('\n');
+    summary.push_str(&tally_table("file", &by_file, total));
+    let missed: Vec<String> = candidates
+        .iter()
+        .zip(outcomes)
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Survived))
+        .map(|(candidate, _)| {
+            let original = &originals[&candidate.file];
+            let (line, column) = line_column(original, candidate.mutation.start);
+            format!(
+                "  {}:{}:{} {}: {:?} -> {:?}\n",
+                candidate.file.display(),
+                line,
+                column,
+                candidate.operator,
+                candidate.mutation.original(original),
+                candidate.mutation.replacement
+            )
+        })
+        .collect();
+    if !missed.is_empty() {
+        summary.push_str("\nSurvived:\n");
+        summary.push_str(&missed.concat());
+    }
+    write_report_file(&output_dir.join(SUMMARY_FILE_NAME), &summary)?;
+    print!("\n{}\n", summary);
+
+    println!(
+        "Killed: {}, survived: {}, unviable: {}, timeout: {}",
+        killed,
+        survived,
+        count("unviable"),
+        count("timeout")
+    );
+    println!("Mutation score: {:.1}%", score * 100.0);
+    Ok(())
+}
+
+fn write_report_file(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Cannot write {:?}", path))
+}
+
+pub const CHECK_REPORT_FILE_NAME: &str = "check_report.json";
+
+/// Write [`CHECK_REPORT_FILE_NAME`] with a count per verdict, the ICE
+/// buckets and one result per mutant.
+fn write_check_report(
+    output_dir: &Path,
+    mutators: &[&dyn Mutator],
+    originals: &HashMap<PathBuf, String>,
+    candidates: &[Candidate],
+    verdicts: &[Verdict],
+) -> Result<()> {
+    let counts = VERDICT_LABELS.map(|label| {
+        let n = verdicts.iter().filter(|v| v.label() == label).count();
+        (label.to_string(), Value::from(n))
+    });
+    let results: Vec<Value> = candidates
+        .iter()
+        .zip(verdicts)
+        .map(|(candidate, verdict)| {
+            let mut fields = verdict.fields();
+            fields.extend([
+                ("id".into(), candidate.id.into()),
+                ("operator".into(), candidate.operator.into()),
+                ("file".into(), candidate.file.display().to_string().into()),
+                ("start".into(), candidate.mutation.start.into()),
+                ("end".into(), candidate.mutation.end.into()),
+                ("kind".into(), candidate.mutation.kind.into()),
+                (
+                    "original".into(),
+                    candidate
+                        .mutation
+                        .original(&originals[&candidate.file])
+                        .into(),
+                ),
+                (
+                    "replacement".into(),
+                    candidate.mutation.replacement.as_str().into(),
+                ),
+            ]);
+            Value::Object(fields)
+        })
+        .collect();
+    let checked: Vec<Checked> = candidates
+        .iter()
+        .zip(verdicts)
+        .map(|(candidate, verdict)| Checked {
+            id: candidate.id,
+            file: candidate.file.display().to_string(),
+            verdict: verdict.clone(),
+            minimized: None,
+        })
+        .collect();
+    let buckets: Vec<Value> = rustc::ice_buckets(&checked)
+        .into_iter()
+        .map(|bucket| {
+            json!({
+                "signature": bucket.signature,
+                "count": bucket.ids.len(),
+                "ids": bucket.ids,
+            })
+        })
+        .collect();
+    let operators: Vec<&str> = mutators.iter().map(|m| m.name()).collect();
+    let report = json!({
+        "operators": operators,
+        "mutants": candidates.len(),
+        "counts": Map::from_iter(counts),
+        "ice_buckets": buckets,
+        "results": results,
+    });
+    write_report_file(
+        &output_dir.join(CHECK_REPORT_FILE_NAME),
+        &format!("{}\n", report),
+    )?;
+
+    let summary: Vec<String> = VERDICT_LABELS
+        .iter()
+        .map(|label| {
+            let n = verdicts.iter().filter(|v| v.label() == *label).count();
+            format!("{} {}", label, n)
+        })
+        .collect();
+    println!("Verdicts: {}", summary.join(", "));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn candidate() -> Candidate {
+        Candidate {
+            id: 0,
+            operator: "structs",
+            file: PathBuf::from("lib.rs"),
+            mutation: Mutation {
+                start: 0,
+                end: 13,
+                replacement: "struct S;".to_string(),
+                kind: "struct_item",
+            },
+        }
+    }
+
+    #[test]
+    fn mutant_restored_after_judge() {
+        let workdir = std::env::temp_dir().join(format!("crusher-restore-{}", process::id()));
+        fs::create_dir_all(&workdir).unwrap();
+        let path = workdir.join("lib.rs");
+        let original = "struct S(u8);\n";
+        fs::write(&path, original).unwrap();
+
+        let seen = with_mutant(&workdir, original, &candidate(), || {
+            Ok(fs::read_to_string(&path)?)
+        });
+        assert_eq!(seen.unwrap(), "struct S;\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        let failed = with_mutant(&workdir, original, &candidate(), || {
+            Err::<(), _>(anyhow!("judge failed"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        let panicked = panic::catch_unwind(|| {
+            with_mutant(&workdir, original, &candidate(), || -> Result<()> {
+                panic!("judge panicked")
+            })
+        });
+        assert!(panicked.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+
+        fs::remove_dir_all(&workdir).unwrap();
+    }
+}
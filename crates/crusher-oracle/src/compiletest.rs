@@ -0,0 +1,101 @@
+//! The `//@` directives of the rustc test suite (`tests/ui` and friends),
+//! as far as they decide how compiletest invokes rustc on a file.
+
+/// Directives naming auxiliary crates to build before the test.
+const AUX_DIRECTIVES: [&str; 4] = ["aux-build", "aux-crate", "aux-bin", "proc-macro"];
+
+/// What the directives of one file ask of rustc.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directives {
+    /// `edition`, the lowest one of a range.
+    pub edition: Option<String>,
+    /// `compile-flags` of all revisions, in file order.
+    pub compile_flags: Vec<String>,
+    /// `revisions`, compiled one at a time with `--cfg <revision>`.
+    pub revisions: Vec<String>,
+    /// `compile-flags` given for some revisions only, `//@[a,b] ...`.
+    pub revision_flags: Vec<(Vec<String>, Vec<String>)>,
+    /// Auxiliary crates the test builds first.
+    pub aux: Vec<String>,
+}
+
+impl Directives {
+    /// The directives of `source_code`; lines that are none are ignored.
+    pub fn parse(source_code: &str) -> Directives {
+        let mut directives = Directives::default();
+        for line in source_code.lines() {
+            let Some(rest) = line.trim_start().strip_prefix("//@") else {
+                continue;
+            };
+            let (revisions, rest) = match rest.strip_prefix('[') {
+                Some(rest) => match rest.split_once(']') {
+                    Some((revisions, rest)) => (
+                        revisions
+                            .split(',')
+                            .map(|r| r.trim().to_string())
+                            .collect::<Vec<_>>(),
+                        rest,
+                    ),
+                    None => continue,
+                },
+                None => (Vec::new(), rest),
+            };
+            let (name, value) = match rest.split_once(':') {
+                Some((name, value)) => (name.trim(), value.trim()),
+                None => (rest.trim(), ""),
+            };
+            let words = || value.split_whitespace().map(str::to_string);
+            match name {
+                "compile-flags" if revisions.is_empty() => directives.compile_flags.extend(words()),
+                "compile-flags" => directives
+                    .revision_flags
+                    .push((revisions, words().collect())),
+                "revisions" => directives.revisions.extend(words()),
+                // `2018..`, `..2021` and `2015..2021` are ranges
+                "edition" if revisions.is_empty() => {
+                    let lowest = value.split("..").next().unwrap_or("").trim();
+                    directives.edition = Some(match lowest {
+                        "" => "2015".to_string(),
+                        edition => edition.to_string(),
+                    });
+                }
+                name if AUX_DIRECTIVES.contains(&name) => directives.aux.push(value.to_string()),
+                _ => {}
+            }
+        }
+        directives
+    }
+
+    /// Whether the test needs auxiliary crates this crate cannot build.
+    pub fn needs_aux(&self) -> bool {
+        !self.aux.is_empty()
+    }
+
+    /// The revision a single rustc command compiles, the first one.
+    pub fn revision(&self) -> Option<&str> {
+        self.revisions.first().map(String::as_str)
+    }
+
+    /// Flags compiletest passes to rustc for [`Directives::revision`],
+    /// after the ones of every revision.
+    pub fn rustc_args(&self) -> Vec<String> {
+        let mut args = self.compile_flags.clone();
+        if let Some(revision) = self.revision() {
+            for (revisions, flags) in &self.revision_flags {
+                if revisions.iter().any(|r| r == revision) {
+                    args.extend(flags.iter().cloned());
+                }
+            }
+            args.push("--cfg".to_string());
+            args.push(revision.to_string());
+        }
+        args
+    }
+
+    /// Whether the flags choose a crate type themselves.
+    pub fn sets_crate_type(&self) -> bool {
+        self.rustc_args()
+            .iter()
+            .any(|arg| arg == "--crate-type" || arg.starts_with("--crate-type="))
+    }
+}
@@ -0,0 +1,88 @@
+//! Experimental filter dropping variants that pretty-print to the same
+//! program as their original (`rustc -Zunpretty=hir` or `=expanded`).
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{self, Command, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum PrettyForm {
+    /// Compare `-Zunpretty=hir` output
+    Hir,
+    /// Compare macro-expanded source, like `cargo expand`
+    Expanded,
+}
+
+impl PrettyForm {
+    fn flag(self) -> &'static str {
+        match self {
+            PrettyForm::Hir => "-Zunpretty=hir",
+            PrettyForm::Expanded => "-Zunpretty=expanded",
+        }
+    }
+}
+
+pub struct EquivalenceFilter {
+    pub rustc: String,
+    pub edition: String,
+    pub crate_type: String,
+    pub form: PrettyForm,
+}
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn temp_source_path() -> PathBuf {
+    let n = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!("crusher-equiv-{}-{}.rs", process::id(), n))
+}
+
+impl EquivalenceFilter {
+    /// Normalized pretty-printed form of `source_code`, or `None` when rustc
+    /// rejects it (then it cannot be proven equivalent to anything).
+    pub fn pretty(&self, source_code: &str) -> Option<String> {
+        let path = temp_source_path();
+        fs::write(&path, source_code).ok()?;
+        let output = Command::new(&self.rustc)
+            // -Z flags are nightly-only, this lets a stable toolchain print them too
+            .env("RUSTC_BOOTSTRAP", "1")
+            .arg(self.form.flag())
+            .args(["--edition", &self.edition, "--crate-type", &self.crate_type])
+            .arg(&path)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output();
+        let _ = fs::remove_file(&path);
+
+        let output = output.ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let text = String::from_utf8_lossy(&output.stdout);
+        Some(text.split_whitespace().collect::<Vec<_>>().join(" "))
+    }
+
+    /// Keep only the variants that differ semantically from `original`,
+    /// returning them with the number of dropped ones; `text` gives the
+    /// source of a variant.
+    pub fn retain_distinct<T>(
+        &self,
+        original: &str,
+        variants: Vec<T>,
+        text: impl Fn(&T) -> &str,
+    ) -> (Vec<T>, usize) {
+        let Some(original_form) = self.pretty(original) else {
+            return (variants, 0);
+        };
+        let before = variants.len();
+        let kept: Vec<T> = variants
+            .into_iter()
+            .filter(|v| self.pretty(text(v)).as_ref() != Some(&original_form))
+            .collect();
+        let dropped = before - kept.len();
+        (kept, dropped)
+    }
+}
@@ -0,0 +1,11 @@
+//! Oracles judging crusher mutants by running external tools on them:
+//! rustc itself, its pretty printer, `cargo test` and a timeout-aware process
+//! runner.
+//!
+//! Everything `pub` here is the supported API and follows semver.
+
+pub mod cargo_mode;
+pub mod compiletest;
+pub mod equivalence;
+pub mod rustc;
+pub mod subprocess;
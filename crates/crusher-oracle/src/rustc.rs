@@ -0,0 +1,353 @@
+//! `--check-with rustc`: compile every variant and classify what rustc did.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process::{self, Command},
+    time::Duration,
+};
+
+use serde_json::{json, Map, Value};
+
+use crate::subprocess::{self, Exit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum Checker {
+    /// Compile with the `--rustc` toolchain and flags
+    Rustc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Compiles,
+    /// Ordinary diagnostics; holds the `E....` codes reported, in order.
+    Error(Vec<String>),
+    /// rustc panicked; holds its stderr.
+    Ice(String),
+    Timeout,
+    /// Ran out of the `--memory-limit`.
+    OutOfMemory,
+    /// Killed by a signal such as SIGSEGV (stack overflow) or SIGABRT.
+    Crash(i32),
+}
+
+impl Verdict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Compiles => "compiles",
+            Verdict::Error(_) => "error",
+            Verdict::Ice(_) => "ice",
+            Verdict::Timeout => "timeout",
+            Verdict::OutOfMemory => "oom",
+            Verdict::Crash(_) => "crash",
+        }
+    }
+}
+
+impl Verdict {
+    /// Whether two toolchains behaved differently: another verdict, other
+    /// error codes or another signal. Two ICEs count as the same behavior.
+    pub fn differs_from(&self, other: &Verdict) -> bool {
+        match (self, other) {
+            (Verdict::Error(a), Verdict::Error(b)) => {
+                let (mut a, mut b) = (a.clone(), b.clone());
+                a.sort();
+                a.dedup();
+                b.sort();
+                b.dedup();
+                a != b
+            }
+            (Verdict::Crash(a), Verdict::Crash(b)) => a != b,
+            _ => self.label() != other.label(),
+        }
+    }
+
+    /// `verdict` plus the error codes or signal, for reports.
+    pub(crate) fn fields(&self) -> Map<String, Value> {
+        let mut fields = Map::new();
+        fields.insert("verdict".into(), self.label().into());
+        match self {
+            Verdict::Error(codes) => fields.insert("codes".into(), codes.clone().into()),
+            Verdict::Crash(signal) => fields.insert("signal".into(), (*signal).into()),
+            Verdict::Ice(stderr) => fields.insert("signature".into(), ice_signature(stderr).into()),
+            _ => None,
+        };
+        fields
+    }
+}
+
+pub const VERDICT_LABELS: [&str; 6] = ["compiles", "error", "ice", "timeout", "oom", "crash"];
+
+pub struct RustcOracle {
+    pub timeout: Duration,
+    /// Where build artifacts go, so they do not pile up next to the variants.
+    pub out_dir: PathBuf,
+    /// Address space limit for every rustc run, in bytes.
+    pub memory_limit: Option<u64>,
+}
+
+impl RustcOracle {
+    pub fn new(timeout: Duration) -> RustcOracle {
+        RustcOracle {
+            timeout,
+            out_dir: std::env::temp_dir().join(format!("crusher-rustc-{}", process::id())),
+            memory_limit: None,
+        }
+    }
+
+    /// An oracle with the same limits and its own scratch directory, for
+    /// compiling concurrently with this one.
+    pub fn worker(&self, number: usize) -> RustcOracle {
+        RustcOracle {
+            timeout: self.timeout,
+            out_dir: self.out_dir.join(format!("worker_{}", number)),
+            memory_limit: self.memory_limit,
+        }
+    }
+
+    /// Run a recorded rustc `command` from `cwd` and classify the result;
+    /// fails only when rustc cannot be run at all.
+    pub fn check(&self, command: &[String], cwd: &Path) -> io::Result<Verdict> {
+        self.check_as(&command[0], command, cwd)
+    }
+
+    /// Compile `source_code` instead of the file the recorded `command` names
+    /// as its last argument.
+    pub fn check_source(&self, command: &[String], source_code: &str) -> io::Result<Verdict> {
+        fs::create_dir_all(&self.out_dir)?;
+        let path = self.out_dir.join("candidate.rs");
+        fs::write(&path, source_code)?;
+        let mut command = command.to_vec();
+        *command.last_mut().unwrap() = path.to_string_lossy().into_owned();
+        self.check(&command, &self.out_dir)
+    }
+
+    /// Like [`RustcOracle::check`], with `rustc` in place of the recorded toolchain.
+    pub fn check_as(&self, rustc: &str, command: &[String], cwd: &Path) -> io::Result<Verdict> {
+        fs::create_dir_all(&self.out_dir)?;
+        let mut rustc = Command::new(rustc);
+        rustc
+            .args(&command[1..])
+            .arg("--out-dir")
+            .arg(&self.out_dir)
+            .current_dir(cwd);
+        if let Some(bytes) = self.memory_limit {
+            subprocess::limit_memory(&mut rustc, bytes);
+        }
+        let finished = subprocess::run_with_timeout(rustc, Some(self.timeout))?;
+        Ok(classify(&finished.exit, &finished.stderr))
+    }
+}
+
+impl Drop for RustcOracle {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.out_dir);
+    }
+}
+
+pub fn classify(exit: &Exit, stderr: &str) -> Verdict {
+    let status = match exit {
+        Exit::TimedOut => return Verdict::Timeout,
+        Exit::Status(status) => status,
+    };
+    // the allocation error handler aborts, so check before the signal
+    if OUT_OF_MEMORY_MESSAGES.iter().any(|m| stderr.contains(m)) {
+        return Verdict::OutOfMemory;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return Verdict::Crash(signal);
+        }
+    }
+    if stderr.contains("error: internal compiler error") || stderr.contains("panicked at") {
+        return Verdict::Ice(stderr.to_string());
+    }
+    if status.success() {
+        return Verdict::Compiles;
+    }
+    Verdict::Error(error_codes(stderr))
+}
+
+/// What rustc prints when it hits the address space limit: an allocation
+/// failing, or with very low limits the loader failing to map rustc itself.
+const OUT_OF_MEMORY_MESSAGES: [&str; 2] = [
+    "memory allocation of ",
+    "failed to map segment from shared object",
+];
+
+/// `E0425` for every `error[E0425]: ...` line.
+fn error_codes(stderr: &str) -> Vec<String> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let code = line.strip_prefix("error[")?.split(']').next()?;
+            Some(code.to_string())
+        })
+        .collect()
+}
+
+pub const REPORT_FILE_NAME: &str = "rustc_report.json";
+
+/// One checked variant: manifest id, output file and verdict.
+pub struct Checked {
+    pub id: usize,
+    pub file: String,
+    pub verdict: Verdict,
+    /// Reduced reproducer written by `--minimize`, relative to the output directory.
+    pub minimized: Option<String>,
+}
+
+/// Write [`REPORT_FILE_NAME`] with a count per verdict, the ICE buckets and
+/// one result per variant.
+pub fn write_report(output_dir: &Path, results: &[Checked]) -> io::Result<()> {
+    let counts = VERDICT_LABELS.map(|label| {
+        let n = results
+            .iter()
+            .filter(|r| r.verdict.label() == label)
+            .count();
+        (label.to_string(), Value::from(n))
+    });
+    let results_json: Vec<Value> = results
+        .iter()
+        .map(|r| {
+            let mut fields = r.verdict.fields();
+            fields.insert("id".into(), r.id.into());
+            fields.insert("file".into(), r.file.as_str().into());
+            if let Some(minimized) = &r.minimized {
+                fields.insert("minimized".into(), minimized.as_str().into());
+            }
+            Value::Object(fields)
+        })
+        .collect();
+    let buckets: Vec<Value> = ice_buckets(results)
+        .into_iter()
+        .map(|bucket| {
+            json!({
+                "signature": bucket.signature,
+                "count": bucket.ids.len(),
+                "ids": bucket.ids,
+            })
+        })
+        .collect();
+    let report = json!({
+        "counts": Map::from_iter(counts),
+        "ice_buckets": buckets,
+        "results": results_json,
+    });
+    fs::write(output_dir.join(REPORT_FILE_NAME), format!("{}\n", report))
+}
+
+/// ICEs sharing one crash signature.
+pub struct IceBucket {
+    pub signature: String,
+    /// Manifest ids, the first one being the representative.
+    pub ids: Vec<usize>,
+}
+
+/// Group the ICEs among `results` by [`ice_signature`], in order of first occurrence.
+pub fn ice_buckets(results: &[Checked]) -> Vec<IceBucket> {
+    let mut buckets: Vec<IceBucket> = Vec::new();
+    for r in results {
+        let Verdict::Ice(stderr) = &r.verdict else {
+            continue;
+        };
+        let signature = ice_signature(stderr);
+        match buckets.iter_mut().find(|b| b.signature == signature) {
+            Some(bucket) => bucket.ids.push(r.id),
+            None => buckets.push(IceBucket {
+                signature,
+                ids: vec![r.id],
+            }),
+        }
+    }
+    buckets
+}
+
+/// What tells one ICE from another: where rustc panicked, the panic message
+/// with `identifiers` blanked out, and the innermost queries running.
+pub fn ice_signature(stderr: &str) -> String {
+    let mut lines = stderr.lines();
+    let mut parts: Vec<String> = Vec::new();
+    while let Some(line) = lines.next() {
+        if let Some(at) = line.split_once("panicked at ").map(|(_, at)| at) {
+            parts.push(at.trim_end_matches(':').to_string());
+            // the message follows on its own line since Rust 1.73
+            if let Some(message) = lines.next() {
+                parts.push(blank_identifiers(message));
+            }
+            break;
+        }
+        if let Some(message) = line.strip_prefix("error: internal compiler error: ") {
+            parts.push(blank_identifiers(message));
+            break;
+        }
+    }
+    let queries: Vec<&str> = stderr
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .filter_map(|line| line.split_once('[')?.1.split_once(']'))
+        .map(|(query, _)| query)
+        .take(ICE_SIGNATURE_QUERIES)
+        .collect();
+    if !queries.is_empty() {
+        parts.push(queries.join(" < "));
+    }
+    parts.join(" | ")
+}
+
+/// Query stack frames that go into a signature.
+const ICE_SIGNATURE_QUERIES: usize = 3;
+
+fn blank_identifiers(message: &str) -> String {
+    let mut out = String::with_capacity(message.len());
+    for (i, piece) in message.trim().split('`').enumerate() {
+        if i > 0 {
+            out.push('`');
+        }
+        // odd pieces are between backticks
+        out.push_str(if i % 2 == 1 { "_" } else { piece });
+    }
+    out
+}
+
+pub const DIFF_REPORT_FILE_NAME: &str = "diff_report.json";
+
+/// A variant two toolchains disagree on.
+pub struct Disagreement {
+    pub id: usize,
+    pub file: String,
+    pub verdicts: [Verdict; 2],
+}
+
+/// Write [`DIFF_REPORT_FILE_NAME`] listing the variants `compilers` disagree on.
+pub fn write_diff_report(
+    output_dir: &Path,
+    compilers: &[String],
+    checked: usize,
+    disagreements: &[Disagreement],
+) -> io::Result<()> {
+    let results: Vec<Value> = disagreements
+        .iter()
+        .map(|d| {
+            json!({
+                "id": d.id,
+                "file": d.file,
+                "a": d.verdicts[0].fields(),
+                "b": d.verdicts[1].fields(),
+            })
+        })
+        .collect();
+    let report = json!({
+        "compilers": compilers,
+        "checked": checked,
+        "differing": disagreements.len(),
+        "results": results,
+    });
+    fs::write(
+        output_dir.join(DIFF_REPORT_FILE_NAME),
+        format!("{}\n", report),
+    )
+}
@@ -0,0 +1,103 @@
+//! Running external tools (rustc, cargo) with a wall-clock and memory limit.
+
+use std::{
+    io::{self, Read},
+    process::{Child, Command, ExitStatus, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug)]
+pub enum Exit {
+    Status(ExitStatus),
+    TimedOut,
+}
+
+#[derive(Debug)]
+pub struct Finished {
+    pub exit: Exit,
+    pub stdout: String,
+    pub stderr: String,
+    pub elapsed: Duration,
+}
+
+impl Finished {
+    pub fn success(&self) -> bool {
+        matches!(&self.exit, Exit::Status(s) if s.success())
+    }
+}
+
+fn drain(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    })
+}
+
+/// Kill `child` together with everything it spawned (cargo -> rustc -> tests).
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+}
+
+/// Cap the address space of the process `command` starts at `bytes`, so a
+/// runaway compilation fails to allocate instead of swapping the machine.
+pub fn limit_memory(command: &mut Command, bytes: u64) {
+    #[cfg(unix)]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+        command.pre_exec(move || {
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+            if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    #[cfg(not(unix))]
+    let _ = (command, bytes);
+}
+
+/// Run `command` to completion, killing it once `timeout` has elapsed.
+pub fn run_with_timeout(mut command: Command, timeout: Option<Duration>) -> io::Result<Finished> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let started = Instant::now();
+    let mut child = command.spawn()?;
+    let stdout = drain(child.stdout.take().unwrap());
+    let stderr = drain(child.stderr.take().unwrap());
+
+    let exit = loop {
+        if let Some(status) = child.try_wait()? {
+            break Exit::Status(status);
+        }
+        if timeout.is_some_and(|t| started.elapsed() >= t) {
+            kill_tree(&mut child);
+            let _ = child.wait();
+            break Exit::TimedOut;
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    Ok(Finished {
+        exit,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+        elapsed: started.elapsed(),
+    })
+}
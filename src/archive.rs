@@ -0,0 +1,424 @@
+//! `--output-format`: variants streamed into one archive instead of loose files.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    process::{Child, ChildStdin, Command, Stdio},
+};
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// One file per variant in the output directory
+    Dir,
+    /// An uncompressed tar archive
+    Tar,
+    /// A tar archive compressed by the `zstd` program
+    #[value(name = "tar.zst")]
+    TarZst,
+    /// A zip archive, stored without compression
+    Zip,
+}
+
+impl OutputFormat {
+    /// Name of the archive in the output directory.
+    pub fn archive_name(self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Dir => None,
+            OutputFormat::Tar => Some("variants.tar"),
+            OutputFormat::TarZst => Some("variants.tar.zst"),
+            OutputFormat::Zip => Some("variants.zip"),
+        }
+    }
+}
+
+/// An archive being written; entries go out as they are added.
+pub enum Archive {
+    Tar(BufWriter<File>),
+    TarZst(BufWriter<ChildStdin>, Child),
+    Zip(ZipWriter),
+}
+
+impl Archive {
+    /// Start the archive `path` in `format`, which is not [`OutputFormat::Dir`].
+    pub fn create(path: &Path, format: OutputFormat) -> io::Result<Archive> {
+        let file = File::create(path)?;
+        Ok(match format {
+            OutputFormat::Dir => unreachable!("loose files are not an archive"),
+            OutputFormat::Tar => Archive::Tar(BufWriter::new(file)),
+            OutputFormat::TarZst => {
+                let mut zstd = spawn_zstd("zstd", file).inspect_err(|_| {
+                    // no empty archive is left behind
+                    let _ = fs::remove_file(path);
+                })?;
+                let stdin = zstd.stdin.take().unwrap();
+                Archive::TarZst(BufWriter::new(stdin), zstd)
+            }
+            OutputFormat::Zip => Archive::Zip(ZipWriter {
+                out: BufWriter::new(file),
+                offset: 0,
+                entries: Vec::new(),
+            }),
+        })
+    }
+
+    /// Add the file `name`, a relative path with `/` separators.
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> io::Result<()> {
+        match self {
+            Archive::Tar(out) => tar_entry(out, name, contents),
+            Archive::TarZst(out, _) => tar_entry(out, name, contents),
+            Archive::Zip(zip) => zip.add(name, contents),
+        }
+    }
+
+    /// Write the end of the archive.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Archive::Tar(mut out) => {
+                out.write_all(&[0; 2 * BLOCK])?;
+                out.flush()
+            }
+            Archive::TarZst(mut out, mut zstd) => {
+                out.write_all(&[0; 2 * BLOCK])?;
+                out.flush()?;
+                // closing its input lets zstd finish
+                drop(out);
+                let status = zstd.wait()?;
+                if !status.success() {
+                    return Err(io::Error::other(format!("zstd failed: {}", status)));
+                }
+                Ok(())
+            }
+            Archive::Zip(zip) => zip.finish(),
+        }
+    }
+}
+
+/// `program`, the `zstd` program, compressing its input into `file`.
+fn spawn_zstd(program: &str, file: File) -> io::Result<Child> {
+    Command::new(program)
+        .args(["-q", "-c"])
+        .stdin(Stdio::piped())
+        .stdout(file)
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => io::Error::new(
+                err.kind(),
+                format!(
+                    "--output-format tar.zst needs the {} program, which is not on the PATH",
+                    program
+                ),
+            ),
+            _ => io::Error::new(err.kind(), format!("cannot run {}: {}", program, err)),
+        })
+}
+
+const BLOCK: usize = 512;
+
+/// A ustar header and the contents. A `name` too long for the header is
+/// split at a `/` into its prefix field, or has a GNU long name entry in
+/// front when it does not split.
+fn tar_entry(out: &mut impl Write, name: &str, contents: &[u8]) -> io::Result<()> {
+    let (prefix, short) = match ustar_split(name) {
+        Some(split) => split,
+        None => {
+            let mut long_name = name.as_bytes().to_vec();
+            long_name.push(0);
+            out.write_all(&tar_header(b"", b"././@LongLink", long_name.len(), b'L'))?;
+            write_padded(out, &long_name)?;
+            (&b""[..], &name.as_bytes()[..100])
+        }
+    };
+    out.write_all(&tar_header(prefix, short, contents.len(), b'0'))?;
+    write_padded(out, contents)
+}
+
+/// `name` as the prefix and name fields of a ustar header, 155 and 100
+/// bytes at most, split at a `/` unless it fits the name field.
+fn ustar_split(name: &str) -> Option<(&[u8], &[u8])> {
+    let name = name.as_bytes();
+    if name.len() <= 100 {
+        return Some((b"", name));
+    }
+    // the shortest prefix leaves the longest name, the first one to fit
+    let slash = (0..name.len()).find(|&i| name[i] == b'/' && name.len() - i - 1 <= 100)?;
+    let (prefix, rest) = (&name[..slash], &name[slash + 1..]);
+    (prefix.len() <= 155 && !rest.is_empty()).then_some((prefix, rest))
+}
+
+fn tar_header(prefix: &[u8], name: &[u8], size: usize, kind: u8) -> [u8; BLOCK] {
+    let mut header = [0; BLOCK];
+    header[..name.len()].copy_from_slice(name);
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    // mtime 0 keeps archives of the same run identical
+    header[136..148].copy_from_slice(b"00000000000\0");
+    header[156] = kind;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix);
+    // the checksum is computed with its own field as spaces
+    header[148..156].copy_from_slice(b"        ");
+    let sum: u32 = header.iter().map(|&b| b as u32).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+    header
+}
+
+fn write_padded(out: &mut impl Write, data: &[u8]) -> io::Result<()> {
+    out.write_all(data)?;
+    let padding = (BLOCK - data.len() % BLOCK) % BLOCK;
+    out.write_all(&[0; BLOCK][..padding])
+}
+
+/// A zip archive of stored entries, using zip64 records once there are more
+/// than 65535 entries or 4 GiB.
+pub struct ZipWriter {
+    out: BufWriter<File>,
+    offset: u64,
+    /// Name, CRC-32, size and local header offset of every entry.
+    entries: Vec<(String, u32, u32, u64)>,
+}
+
+/// Set in the general purpose flags: names are UTF-8.
+const ZIP_UTF8: u16 = 1 << 11;
+
+/// 1980-01-01 00:00, the earliest DOS date, for reproducible archives.
+const ZIP_DATE: u16 = (1 << 5) | 1;
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, contents: &[u8]) -> io::Result<()> {
+        let size = u32::try_from(contents.len())
+            .map_err(|_| io::Error::other(format!("{} is too big for a zip entry", name)))?;
+        let crc = crc32(contents);
+        let mut header = Vec::with_capacity(30 + name.len());
+        header.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes());
+        header.extend_from_slice(&ZIP_UTF8.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&ZIP_DATE.to_le_bytes());
+        header.extend_from_slice(&crc.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&size.to_le_bytes());
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        self.out.write_all(&header)?;
+        self.out.write_all(contents)?;
+        self.entries
+            .push((name.to_string(), crc, size, self.offset));
+        self.offset += (header.len() + contents.len()) as u64;
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        let directory_offset = self.offset;
+        let mut directory_size = 0u64;
+        for (name, crc, size, offset) in &self.entries {
+            // a zip64 extra field holds offsets beyond 4 GiB
+            let extra: Vec<u8> = if *offset >= u32::MAX as u64 {
+                [
+                    &1u16.to_le_bytes()[..],
+                    &8u16.to_le_bytes(),
+                    &offset.to_le_bytes(),
+                ]
+                .concat()
+            } else {
+                Vec::new()
+            };
+            let mut record = Vec::with_capacity(46 + name.len() + extra.len());
+            record.extend_from_slice(&0x02014b50u32.to_le_bytes());
+            // made by and needed: 4.5 with zip64, 2.0 otherwise
+            let version: u16 = if extra.is_empty() { 20 } else { 45 };
+            record.extend_from_slice(&version.to_le_bytes());
+            record.extend_from_slice(&version.to_le_bytes());
+            record.extend_from_slice(&ZIP_UTF8.to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes());
+            record.extend_from_slice(&ZIP_DATE.to_le_bytes());
+            record.extend_from_slice(&crc.to_le_bytes());
+            record.extend_from_slice(&size.to_le_bytes());
+            record.extend_from_slice(&size.to_le_bytes());
+            record.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            record.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes());
+            record.extend_from_slice(&0u16.to_le_bytes());
+            record.extend_from_slice(&(0o100644u32 << 16).to_le_bytes());
+            record.extend_from_slice(&((*offset).min(u32::MAX as u64) as u32).to_le_bytes());
+            record.extend_from_slice(name.as_bytes());
+            record.extend_from_slice(&extra);
+            self.out.write_all(&record)?;
+            directory_size += record.len() as u64;
+        }
+
+        let count = self.entries.len() as u64;
+        let zip64 = count >= u16::MAX as u64
+            || directory_offset >= u32::MAX as u64
+            || directory_size >= u32::MAX as u64;
+        if zip64 {
+            let end64_offset = directory_offset + directory_size;
+            let mut end64 = Vec::with_capacity(56 + 20);
+            end64.extend_from_slice(&0x06064b50u32.to_le_bytes());
+            end64.extend_from_slice(&44u64.to_le_bytes());
+            end64.extend_from_slice(&45u16.to_le_bytes());
+            end64.extend_from_slice(&45u16.to_le_bytes());
+            end64.extend_from_slice(&0u32.to_le_bytes());
+            end64.extend_from_slice(&0u32.to_le_bytes());
+            end64.extend_from_slice(&count.to_le_bytes());
+            end64.extend_from_slice(&count.to_le_bytes());
+            end64.extend_from_slice(&directory_size.to_le_bytes());
+            end64.extend_from_slice(&directory_offset.to_le_bytes());
+            // locator
+            end64.extend_from_slice(&0x07064b50u32.to_le_bytes());
+            end64.extend_from_slice(&0u32.to_le_bytes());
+            end64.extend_from_slice(&end64_offset.to_le_bytes());
+            end64.extend_from_slice(&1u32.to_le_bytes());
+            self.out.write_all(&end64)?;
+        }
+        let mut end = Vec::with_capacity(22);
+        end.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes());
+        let count16 = count.min(u16::MAX as u64) as u16;
+        end.extend_from_slice(&count16.to_le_bytes());
+        end.extend_from_slice(&count16.to_le_bytes());
+        end.extend_from_slice(&(directory_size.min(u32::MAX as u64) as u32).to_le_bytes());
+        end.extend_from_slice(&(directory_offset.min(u32::MAX as u64) as u32).to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes());
+        self.out.write_all(&end)?;
+        self.out.flush()
+    }
+}
+
+/// CRC-32 as zip uses it (IEEE, reflected).
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+
+    fn field(header: &[u8], range: std::ops::Range<usize>) -> &[u8] {
+        let field = &header[range];
+        let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+        &field[..end]
+    }
+
+    fn checksum_ok(header: &[u8]) -> bool {
+        let mut blank = header[..BLOCK].to_vec();
+        blank[148..156].copy_from_slice(b"        ");
+        let sum: u32 = blank.iter().map(|&b| b as u32).sum();
+        field(header, 148..154) == format!("{:06o}", sum).as_bytes()
+    }
+
+    fn u16_at(bytes: &[u8], at: usize) -> u16 {
+        u16::from_le_bytes(bytes[at..at + 2].try_into().unwrap())
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn long_and_non_ascii_tar_names() {
+        let dir = "d".repeat(120);
+        let file = format!("{}.rs", "f".repeat(90));
+        let long = format!("{}/{}", dir, file);
+        let mut out = Vec::new();
+        tar_entry(&mut out, &long, b"fn main() {}\n").unwrap();
+        assert_eq!(out.len(), 2 * BLOCK);
+        assert_eq!(field(&out, 0..100), file.as_bytes());
+        assert_eq!(field(&out, 345..500), dir.as_bytes());
+        assert_eq!(field(&out, 124..135), b"00000000015");
+        assert_eq!(out[156], b'0');
+        assert!(checksum_ok(&out));
+        assert_eq!(&out[BLOCK..BLOCK + 13], b"fn main() {}\n");
+
+        // one component over 100 bytes does not split
+        let unsplit = format!("out/{}.rs", "x".repeat(150));
+        let mut out = Vec::new();
+        tar_entry(&mut out, &unsplit, b"").unwrap();
+        assert_eq!(out.len(), 3 * BLOCK);
+        assert_eq!(field(&out, 0..100), b"././@LongLink");
+        assert_eq!(out[156], b'L');
+        assert_eq!(field(&out[BLOCK..], 0..BLOCK), unsplit.as_bytes());
+        assert_eq!(out[2 * BLOCK + 156], b'0');
+        assert!(checksum_ok(&out[2 * BLOCK..]));
+
+        let name = "crushed/ünïcödé_变体.rs";
+        let mut out = Vec::new();
+        tar_entry(&mut out, name, b"").unwrap();
+        assert_eq!(out.len(), BLOCK);
+        assert_eq!(field(&out, 0..100), name.as_bytes());
+        assert!(checksum_ok(&out));
+    }
+
+    #[test]
+    fn zip_crc_and_central_directory() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(crc32(b""), 0);
+
+        let path = std::env::temp_dir().join(format!("crusher-archive-{}.zip", process::id()));
+        let mut archive = Archive::create(&path, OutputFormat::Zip).unwrap();
+        let entries: [(&str, &[u8]); 2] = [("a/1.rs", b"123456789"), ("变体/2.rs", b"")];
+        for (name, contents) in entries {
+            archive.add(name, contents).unwrap();
+        }
+        archive.finish().unwrap();
+        let zip = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let end = zip.len() - 22;
+        assert_eq!(u32_at(&zip, end), 0x06054b50);
+        assert_eq!(u16_at(&zip, end + 8), 2);
+        assert_eq!(u16_at(&zip, end + 10), 2);
+        let size = u32_at(&zip, end + 12) as usize;
+        let mut record = u32_at(&zip, end + 16) as usize;
+        assert_eq!(record + size, end);
+        for (name, contents) in entries {
+            assert_eq!(u32_at(&zip, record), 0x02014b50);
+            assert_eq!(u16_at(&zip, record + 8), ZIP_UTF8);
+            assert_eq!(u32_at(&zip, record + 16), crc32(contents));
+            assert_eq!(u32_at(&zip, record + 20), contents.len() as u32);
+            let name_len = u16_at(&zip, record + 28) as usize;
+            assert_eq!(&zip[record + 46..record + 46 + name_len], name.as_bytes());
+
+            // the local header the record points at agrees with it
+            let local = u32_at(&zip, record + 42) as usize;
+            assert_eq!(u32_at(&zip, local), 0x04034b50);
+            assert_eq!(u32_at(&zip, local + 14), crc32(contents));
+            assert_eq!(&zip[local + 30..local + 30 + name_len], name.as_bytes());
+            let data = local + 30 + name_len;
+            assert_eq!(&zip[data..data + contents.len()], contents);
+            record += 46 + name_len;
+        }
+    }
+
+    #[test]
+    fn missing_zstd() {
+        let path = std::env::temp_dir().join(format!("crusher-archive-{}.tar.zst", process::id()));
+        let file = File::create(&path).unwrap();
+        let err = spawn_zstd("crusher-no-such-zstd", file).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(
+            err.to_string(),
+            "--output-format tar.zst needs the crusher-no-such-zstd program, which is not on the PATH"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! `crusher_cache.jsonl`: what `--incremental` keeps in the output directory
+//! to pass over the input files that have not changed since its last run.
+//!
+//! The first line fingerprints the flags of the run, as the state file of
+//! `--resume` does; a run with other flags starts over. Every other line is
+//! an input file: the hash of its content, the manifest entries of its
+//! variants and their `--check-with` verdicts.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crusher_core::rng;
+use crusher_oracle::rustc::Verdict;
+use serde_json::{json, Value};
+
+use crate::{manifest::ManifestEntry, state};
+
+pub const CACHE_FILE_NAME: &str = "crusher_cache.jsonl";
+
+/// One input file as the last run left it.
+#[derive(Clone)]
+pub struct CachedFile {
+    pub source: String,
+    /// [`content_hash`] of the file.
+    pub hash: u64,
+    pub entries: Vec<ManifestEntry>,
+    /// Verdicts of the entries, by id, for the ones that were checked.
+    pub verdicts: Vec<(usize, Verdict)>,
+}
+
+impl CachedFile {
+    fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self.entries.iter().map(ManifestEntry::to_json).collect();
+        let verdicts: Vec<Value> = self
+            .verdicts
+            .iter()
+            .map(|(id, verdict)| state::verdict_to_json(*id, verdict))
+            .collect();
+        json!({
+            "source": self.source,
+            "hash": format!("{:016x}", self.hash),
+            "entries": entries,
+            "verdicts": verdicts,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<CachedFile> {
+        Some(CachedFile {
+            source: value.get("source")?.as_str()?.to_string(),
+            hash: u64::from_str_radix(value.get("hash")?.as_str()?, 16).ok()?,
+            entries: value
+                .get("entries")?
+                .as_array()?
+                .iter()
+                .map(ManifestEntry::from_json)
+                .collect::<Option<_>>()?,
+            verdicts: value
+                .get("verdicts")?
+                .as_array()?
+                .iter()
+                .map(state::verdict_from_json)
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// Hash of an input file, stable across runs and releases.
+pub fn content_hash(source_code: &str) -> u64 {
+    rng::fnv1a(source_code)
+}
+
+/// The files cached in `dir` by input file, none when there is no cache or
+/// it was written with flags other than `fingerprint`.
+pub fn read_cache(dir: &Path, fingerprint: &str) -> io::Result<HashMap<String, CachedFile>> {
+    let text = match fs::read_to_string(dir.join(CACHE_FILE_NAME)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    let mut lines = text.lines();
+    let header = lines
+        .next()
+        .and_then(|line| serde_json::from_str::<Value>(line).ok())
+        .and_then(|header| Some(header.get("cache")?.as_str()?.to_string()));
+    if header.as_deref() != Some(fingerprint) {
+        return Ok(HashMap::new());
+    }
+    Ok(lines
+        .filter_map(|line| CachedFile::from_json(&serde_json::from_str(line).ok()?))
+        .map(|file| (file.source.clone(), file))
+        .collect())
+}
+
+/// Replace the cache in `dir` by `files`.
+pub fn write_cache(dir: &Path, fingerprint: &str, files: &[CachedFile]) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(dir.join(CACHE_FILE_NAME))?);
+    writeln!(file, "{}", json!({ "cache": fingerprint }))?;
+    for cached in files {
+        writeln!(file, "{}", cached.to_json())?;
+    }
+    file.flush()
+}
+
+/// Drop the cache in `dir`, once a run not keeping it rewrote the manifest.
+pub fn remove(dir: &Path) -> io::Result<()> {
+    match fs::remove_file(dir.join(CACHE_FILE_NAME)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
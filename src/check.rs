@@ -0,0 +1,228 @@
+//! `--check-with` and `--diff-compilers`: compiling the written variants of
+//! a run and reporting on them.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use tracing::{debug, info};
+
+use crusher_core::reduce;
+use crusher_oracle::rustc::{self, Checked, Disagreement, RustcOracle};
+
+use crate::{
+    manifest::ManifestEntry, parallel::parallel_map, progress::ProgressFormat, state::StateFile,
+};
+
+/// `--timeout` default for `--check-with`, in seconds.
+pub const DEFAULT_RUSTC_TIMEOUT: u64 = 30;
+
+/// Most compilations spent on minimizing one ICE.
+const MINIMIZE_BUDGET: usize = 500;
+
+pub const MINIMIZED_DIR: &str = "minimized";
+
+/// How `--check-with` goes about the variants.
+pub struct CheckOptions<'a> {
+    pub jobs: usize,
+    pub keep: &'a [String],
+    pub dedup_ices: bool,
+    pub minimize: bool,
+    pub progress: Option<ProgressFormat>,
+    /// Verdicts a resumed run has from before, by id.
+    pub known: HashMap<usize, rustc::Verdict>,
+    /// Where new verdicts are recorded for `--resume`.
+    pub state: Option<&'a Mutex<StateFile>>,
+}
+
+/// Compile every written variant and report the verdicts; with `keep`,
+/// delete the variants whose verdict is not listed. The variants left and
+/// their verdicts by id are returned.
+pub fn check_variants(
+    output_dir: &Path,
+    entries: Vec<ManifestEntry>,
+    oracle: &RustcOracle,
+    options: &CheckOptions,
+) -> Result<(Vec<ManifestEntry>, HashMap<usize, rustc::Verdict>)> {
+    let CheckOptions {
+        jobs,
+        keep,
+        dedup_ices,
+        minimize,
+        progress,
+        ..
+    } = *options;
+    let mut results = parallel_map(
+        jobs,
+        &entries,
+        (progress, "check", "variants"),
+        |worker| oracle.worker(worker),
+        |oracle, entry| {
+            let verdict = match options.known.get(&entry.id) {
+                Some(verdict) => verdict.clone(),
+                None => {
+                    let verdict = oracle
+                        .check(&entry.command, output_dir)
+                        .with_context(|| format!("Cannot run {:?}", entry.command[0]))?;
+                    if let Some(state) = options.state {
+                        state
+                            .lock()
+                            .unwrap()
+                            .checked(entry.id, &verdict)
+                            .context("Cannot record the verdict for --resume")?;
+                    }
+                    debug!(file = %entry.file, verdict = verdict.label(), "checked");
+                    verdict
+                }
+            };
+            Ok(Checked {
+                id: entry.id,
+                file: entry.file.clone(),
+                verdict,
+                minimized: None,
+            })
+        },
+    )
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+
+    let summary: Vec<String> = rustc::VERDICT_LABELS
+        .iter()
+        .map(|label| {
+            let n = results
+                .iter()
+                .filter(|r| r.verdict.label() == *label)
+                .count();
+            format!("{} {}", label, n)
+        })
+        .collect();
+    println!("Verdicts: {}", summary.join(", "));
+
+    let buckets = rustc::ice_buckets(&results);
+    if !buckets.is_empty() {
+        println!("Distinct ICE signatures: {}", buckets.len());
+    }
+    // every ICE but the first of its bucket
+    let duplicates: HashSet<usize> = if dedup_ices {
+        buckets.iter().flat_map(|b| b.ids[1..].to_vec()).collect()
+    } else {
+        HashSet::new()
+    };
+
+    let kept: Vec<bool> = results
+        .iter()
+        .map(|checked| {
+            (keep.is_empty() || keep.iter().any(|k| k == checked.verdict.label()))
+                && !duplicates.contains(&checked.id)
+        })
+        .collect();
+
+    if minimize {
+        let ices: Vec<usize> = (0..results.len())
+            .filter(|&i| kept[i] && matches!(results[i].verdict, rustc::Verdict::Ice(_)))
+            .collect();
+        let minimized = parallel_map(
+            jobs,
+            &ices,
+            (progress, "minimize", "ICEs"),
+            |worker| oracle.worker(worker),
+            |oracle, &i| {
+                let rustc::Verdict::Ice(stderr) = &results[i].verdict else {
+                    unreachable!()
+                };
+                let signature = rustc::ice_signature(stderr);
+                let entry = &entries[i];
+                let source_code = fs::read_to_string(output_dir.join(&entry.file))?;
+                let reduced = reduce::minimize(&source_code, MINIMIZE_BUDGET, |candidate| {
+                    match oracle.check_source(&entry.command, candidate) {
+                        Ok(rustc::Verdict::Ice(stderr)) => {
+                            rustc::ice_signature(&stderr) == signature
+                        }
+                        _ => false,
+                    }
+                });
+                let file = Path::new(MINIMIZED_DIR).join(&entry.file);
+                let path = output_dir.join(&file);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, &reduced)?;
+                Ok((source_code.len(), reduced.len(), file))
+            },
+        )
+        .into_iter()
+        .collect::<Result<Vec<_>>>()
+        .context("Cannot write minimized variants")?;
+        for (i, (before, after, file)) in ices.into_iter().zip(minimized) {
+            info!(
+                "Minimized {} from {} to {} bytes",
+                entries[i].file, before, after
+            );
+            results[i].minimized = Some(file.to_string_lossy().into_owned());
+        }
+    }
+    rustc::write_report(output_dir, &results)
+        .with_context(|| format!("Cannot write {}", rustc::REPORT_FILE_NAME))?;
+
+    let mut retained = Vec::new();
+    let mut verdicts = HashMap::new();
+    for ((entry, kept), checked) in entries.into_iter().zip(kept).zip(results) {
+        if kept {
+            retained.push(entry);
+            verdicts.insert(checked.id, checked.verdict);
+        } else {
+            let path = output_dir.join(&entry.file);
+            fs::remove_file(&path).with_context(|| format!("Cannot delete {:?}", path))?;
+        }
+    }
+    Ok((retained, verdicts))
+}
+
+/// Compile every written variant with both `compilers` and report those
+/// they disagree on.
+pub fn diff_compilers(
+    output_dir: &Path,
+    entries: &[ManifestEntry],
+    oracle: &RustcOracle,
+    jobs: usize,
+    compilers: &[String],
+    progress: Option<ProgressFormat>,
+) -> Result<()> {
+    let verdicts = parallel_map(
+        jobs,
+        entries,
+        (progress, "diff-compilers", "variants"),
+        |worker| oracle.worker(worker),
+        |oracle, entry| -> Result<[rustc::Verdict; 2]> {
+            let [a, b] = [0, 1].map(|i| {
+                oracle
+                    .check_as(&compilers[i], &entry.command, output_dir)
+                    .with_context(|| format!("Cannot run {:?}", compilers[i]))
+            });
+            Ok([a?, b?])
+        },
+    )
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?;
+    let disagreements: Vec<Disagreement> = entries
+        .iter()
+        .zip(verdicts)
+        .filter(|(_, verdicts)| verdicts[0].differs_from(&verdicts[1]))
+        .map(|(entry, verdicts)| Disagreement {
+            id: entry.id,
+            file: entry.file.clone(),
+            verdicts,
+        })
+        .collect();
+    rustc::write_diff_report(output_dir, compilers, entries.len(), &disagreements)
+        .with_context(|| format!("Cannot write {}", rustc::DIFF_REPORT_FILE_NAME))?;
+    println!(
+        "Variants where {} and {} disagree: {}",
+        compilers[0],
+        compilers[1],
+        disagreements.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,451 @@
+//! `crusher.toml`: command-line flags kept in a file.
+//!
+//! Every key is the long name of a flag, `include-regex = "^Foo"` standing
+//! for `--include-regex=^Foo`. Tables only group keys: `skip-tests` means
+//! the same at the top and under `[filters]`.
+//!
+//! This is the subset of TOML it reads:
+//!
+//! - `# comments`, to the end of any line;
+//! - `key = value` lines and `[table]` headers, with bare keys of ASCII
+//!   letters, digits, `_` and `-`, or keys in `"..."` and `'...'`;
+//! - one-line basic strings with the escapes `\"`, `\\`, `\b`, `\t`, `\n`,
+//!   `\f`, `\r`, `\uXXXX` and `\UXXXXXXXX`, and one-line literal strings;
+//! - decimal integers and decimals like `1.5`, signed or not, with `_`
+//!   between digits;
+//! - `true` and `false`;
+//! - arrays of any of these, over several lines and with a trailing comma.
+//!
+//! Everything else is an error naming its line: dotted keys, arrays of
+//! tables, inline tables, nested arrays, multi-line strings, hexadecimal,
+//! octal and binary integers, exponents, `inf` and `nan`, dates and times,
+//! and a key set twice anywhere in the file.
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Result};
+
+/// Looked for in the current directory without `--config`.
+pub const CONFIG_FILE_NAME: &str = "crusher.toml";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    /// A string, or a number as written.
+    Scalar(String),
+    Array(Vec<Value>),
+}
+
+/// One `key = value` line.
+#[derive(Debug, Clone)]
+pub struct Setting {
+    pub key: String,
+    pub value: Value,
+    pub line: usize,
+}
+
+/// The settings of the config file `path`, in file order.
+pub fn read_config(path: &Path) -> Result<Vec<Setting>> {
+    let text = fs::read_to_string(path).with_context(|| format!("Cannot read {:?}", path))?;
+    match parse(&text) {
+        Ok(settings) => Ok(settings),
+        Err((line, message)) => bail!("{}:{}: {}", path.display(), line, message),
+    }
+}
+
+fn parse(text: &str) -> Result<Vec<Setting>, (usize, String)> {
+    let mut parser = Parser {
+        text: text.as_bytes(),
+        at: 0,
+        line: 1,
+    };
+    let mut settings: Vec<Setting> = Vec::new();
+    loop {
+        parser.skip_blank(true);
+        let Some(c) = parser.peek() else {
+            return Ok(settings);
+        };
+        if c == b'[' {
+            parser.at += 1;
+            if parser.peek() == Some(b'[') {
+                return Err(parser.error("arrays of tables are not supported"));
+            }
+            parser.skip_blank(false);
+            parser.key()?;
+            parser.skip_blank(false);
+            parser.expect(b']')?;
+        } else {
+            let line = parser.line;
+            let key = parser.key()?.replace('_', "-");
+            parser.skip_blank(false);
+            parser.expect(b'=')?;
+            parser.skip_blank(false);
+            let value = parser.value()?;
+            if let Some(earlier) = settings.iter().find(|s| s.key == key) {
+                return Err((
+                    line,
+                    format!("{} is set again, first on line {}", key, earlier.line),
+                ));
+            }
+            settings.push(Setting { key, value, line });
+        }
+        parser.skip_blank(false);
+        match parser.peek() {
+            None | Some(b'\n') => {}
+            Some(_) => return Err(parser.error("expected the end of the line")),
+        }
+    }
+}
+
+struct Parser<'t> {
+    text: &'t [u8],
+    at: usize,
+    line: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.at).copied()
+    }
+
+    fn error(&self, message: &str) -> (usize, String) {
+        (self.line, message.to_string())
+    }
+
+    fn expect(&mut self, c: u8) -> Result<(), (usize, String)> {
+        if self.peek() != Some(c) {
+            return Err(self.error(&format!("expected `{}`", c as char)));
+        }
+        self.at += 1;
+        Ok(())
+    }
+
+    /// Skip spaces and a comment, and line breaks too with `newlines`.
+    fn skip_blank(&mut self, newlines: bool) {
+        while let Some(c) = self.peek() {
+            match c {
+                b' ' | b'\t' | b'\r' => self.at += 1,
+                b'\n' if newlines => {
+                    self.at += 1;
+                    self.line += 1;
+                }
+                b'#' => {
+                    while self.peek().is_some_and(|c| c != b'\n') {
+                        self.at += 1;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, (usize, String)> {
+        let key = match self.peek() {
+            Some(b'"') => self.basic_string()?,
+            Some(b'\'') => self.literal_string()?,
+            _ => {
+                let start = self.at;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_' || c == b'-')
+                {
+                    self.at += 1;
+                }
+                if start == self.at {
+                    return Err(self.error("expected a key"));
+                }
+                String::from_utf8_lossy(&self.text[start..self.at]).into_owned()
+            }
+        };
+        if self.peek() == Some(b'.') {
+            return Err(self.error("dotted keys are not supported"));
+        }
+        Ok(key)
+    }
+
+    fn value(&mut self) -> Result<Value, (usize, String)> {
+        match self.peek() {
+            Some(quote @ (b'"' | b'\'')) if self.text[self.at..].starts_with(&[quote; 3]) => {
+                Err(self.error("multi-line strings are not supported"))
+            }
+            Some(b'"') => Ok(Value::Scalar(self.basic_string()?)),
+            Some(b'\'') => Ok(Value::Scalar(self.literal_string()?)),
+            Some(b'{') => Err(self.error("inline tables are not supported")),
+            Some(b'[') => {
+                self.at += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank(true);
+                    if self.peek() == Some(b']') {
+                        self.at += 1;
+                        return Ok(Value::Array(items));
+                    }
+                    let item = self.value()?;
+                    if matches!(item, Value::Array(_)) {
+                        return Err(self.error("nested arrays are not supported"));
+                    }
+                    items.push(item);
+                    self.skip_blank(true);
+                    match self.peek() {
+                        Some(b',') => self.at += 1,
+                        Some(b']') => {}
+                        _ => return Err(self.error("expected `,` or `]`")),
+                    }
+                }
+            }
+            _ => {
+                let start = self.at;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || b"+-._".contains(&c))
+                {
+                    self.at += 1;
+                }
+                let word = String::from_utf8_lossy(&self.text[start..self.at]).into_owned();
+                match word.as_str() {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    _ if word.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-') => {
+                        match is_decimal(&word) {
+                            true => Ok(Value::Scalar(word.replace('_', ""))),
+                            false => Err(self.error(&format!(
+                                "{} is no decimal integer or decimal number",
+                                word
+                            ))),
+                        }
+                    }
+                    _ => Err(self.error("expected a string, number, boolean or array")),
+                }
+            }
+        }
+    }
+
+    /// `"..."` with backslash escapes, on one line.
+    fn basic_string(&mut self) -> Result<String, (usize, String)> {
+        self.at += 1;
+        let mut out = Vec::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.at += 1;
+            match c {
+                b'"' => break,
+                b'\n' => return Err(self.error("unterminated string")),
+                b'\\' => {
+                    let Some(escape) = self.peek() else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.at += 1;
+                    match escape {
+                        b'"' | b'\\' => out.push(escape),
+                        b'b' => out.push(0x08),
+                        b'f' => out.push(0x0c),
+                        b'n' => out.push(b'\n'),
+                        b't' => out.push(b'\t'),
+                        b'r' => out.push(b'\r'),
+                        b'u' | b'U' => {
+                            let len = if escape == b'u' { 4 } else { 8 };
+                            let digits = self
+                                .text
+                                .get(self.at..self.at + len)
+                                .and_then(|d| std::str::from_utf8(d).ok())
+                                .and_then(|d| u32::from_str_radix(d, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error("bad unicode escape"))?;
+                            self.at += len;
+                            out.extend_from_slice(digits.to_string().as_bytes());
+                        }
+                        _ => return Err(self.error("unknown escape")),
+                    }
+                }
+                _ => out.push(c),
+            }
+        }
+        Ok(String::from_utf8_lossy(&out).into_owned())
+    }
+
+    /// `'...'`, taken as is, on one line.
+    fn literal_string(&mut self) -> Result<String, (usize, String)> {
+        self.at += 1;
+        let start = self.at;
+        while self.peek().is_some_and(|c| c != b'\'' && c != b'\n') {
+            self.at += 1;
+        }
+        if self.peek() != Some(b'\'') {
+            return Err(self.error("unterminated string"));
+        }
+        let text = String::from_utf8_lossy(&self.text[start..self.at]).into_owned();
+        self.at += 1;
+        Ok(text)
+    }
+}
+
+/// `-12`, `1_000` or `+0.5`: digits with single `_` between them, an
+/// optional sign and an optional `.` followed by more digits.
+fn is_decimal(word: &str) -> bool {
+    let digits = |part: &str| {
+        !part.is_empty()
+            && part
+                .split('_')
+                .all(|group| !group.is_empty() && group.bytes().all(|c| c.is_ascii_digit()))
+    };
+    let unsigned = word.strip_prefix(['+', '-']).unwrap_or(word);
+    match unsigned.split_once('.') {
+        Some((whole, fraction)) => digits(whole) && digits(fraction),
+        None => digits(unsigned),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(text: &str) -> Vec<(String, Value)> {
+        parse(text)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.key, s.value))
+            .collect()
+    }
+
+    fn scalar(text: &str) -> Value {
+        Value::Scalar(text.to_string())
+    }
+
+    fn error(text: &str) -> (usize, String) {
+        parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn strings_and_escapes() {
+        assert_eq!(
+            values(
+                "a = \"x \\\"y\\\" \\\\ \\t\\u00e9\\U0001F980\"\n\
+                 b = 'C:\\dir\\\"raw'\n\
+                 \"quoted key\" = \"\"\n"
+            ),
+            [
+                ("a".to_string(), scalar("x \"y\" \\ \t\u{e9}\u{1f980}")),
+                ("b".to_string(), scalar("C:\\dir\\\"raw")),
+                ("quoted key".to_string(), scalar("")),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers_booleans_and_arrays() {
+        assert_eq!(
+            values(
+                "max_total = 1_000\nseed = -3\nskip-tests = true\n\
+                 operators = [\n  \"structs\", # the first\n  'typenames',\n]\nempty = []\n"
+            ),
+            [
+                ("max-total".to_string(), scalar("1000")),
+                ("seed".to_string(), scalar("-3")),
+                ("skip-tests".to_string(), Value::Bool(true)),
+                (
+                    "operators".to_string(),
+                    Value::Array(vec![scalar("structs"), scalar("typenames")]),
+                ),
+                ("empty".to_string(), Value::Array(Vec::new())),
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_tables() {
+        let settings = parse(
+            "# a comment\n\n[filters] # tables only group keys\nskip-tests = false # off\n\
+             [output]\nemit = \"diff\"\n",
+        )
+        .unwrap();
+        let lines: Vec<(&str, usize)> = settings.iter().map(|s| (s.key.as_str(), s.line)).collect();
+        assert_eq!(lines, [("skip-tests", 4), ("emit", 6)]);
+        assert_eq!(settings[0].value, Value::Bool(false));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("a = \"open\n"),
+            (1, "unterminated string".to_string())
+        );
+        assert_eq!(error("a = 'open"), (1, "unterminated string".to_string()));
+        assert_eq!(error("\na = \"\\q\""), (2, "unknown escape".to_string()));
+        assert_eq!(
+            error("a = \"\\u12\""),
+            (1, "bad unicode escape".to_string())
+        );
+        assert_eq!(
+            error("a = [[1]]"),
+            (1, "nested arrays are not supported".to_string())
+        );
+        assert_eq!(error("a = [1 2]"), (1, "expected `,` or `]`".to_string()));
+        assert_eq!(
+            error("a.b = 1"),
+            (1, "dotted keys are not supported".to_string())
+        );
+        assert_eq!(
+            error("[[t]]"),
+            (1, "arrays of tables are not supported".to_string())
+        );
+        assert_eq!(error("= 1"), (1, "expected a key".to_string()));
+        assert_eq!(error("a 1"), (1, "expected `=`".to_string()));
+        assert_eq!(
+            error("a = yes"),
+            (1, "expected a string, number, boolean or array".to_string())
+        );
+        assert_eq!(
+            error("a = 1 2"),
+            (1, "expected the end of the line".to_string())
+        );
+        assert_eq!(
+            error("a = 1\n[t]\na = 2"),
+            (3, "a is set again, first on line 1".to_string())
+        );
+    }
+
+    #[test]
+    fn beyond_the_subset() {
+        assert_eq!(
+            error("a = 1\nb = \"\"\"\nx\"\"\""),
+            (2, "multi-line strings are not supported".to_string())
+        );
+        assert_eq!(
+            error("b = '''x'''"),
+            (1, "multi-line strings are not supported".to_string())
+        );
+        assert_eq!(
+            error("[t]\nb = { c = 1 }"),
+            (2, "inline tables are not supported".to_string())
+        );
+        for number in [
+            "0x1f",
+            "0o7",
+            "1e3",
+            "1.",
+            "1__0",
+            "1_",
+            "+inf",
+            "-nan",
+            "1979-05-27",
+        ] {
+            assert_eq!(
+                error(&format!("n = {}", number)),
+                (
+                    1,
+                    format!("{} is no decimal integer or decimal number", number)
+                )
+            );
+        }
+        assert_eq!(
+            values("n = +0.5\nm = 1_000_000\ns = \"\\b\\f\""),
+            [
+                ("n".to_string(), scalar("+0.5")),
+                ("m".to_string(), scalar("1000000")),
+                ("s".to_string(), scalar("\u{8}\u{c}")),
+            ]
+        );
+    }
+}
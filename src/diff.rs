@@ -0,0 +1,149 @@
+//! `--emit diff`: a variant as a unified diff against its input file.
+
+use crate::manifest::MutationRecord;
+
+/// Lines of context around every change, as `diff -u` has.
+const CONTEXT: usize = 3;
+
+/// A changed run of whole lines: `old` lines from `start` replaced by `new`.
+struct Chunk<'s> {
+    start: usize,
+    old: Vec<&'s str>,
+    new: Vec<String>,
+    /// `kind line:column-line:column` of every mutation in it.
+    sites: Vec<String>,
+}
+
+/// The diff turning `original` into `original` with `mutations` applied,
+/// for `patch -p1` from the input directory with `path` relative to it.
+pub fn unified_diff(path: &str, original: &str, mutations: &[MutationRecord]) -> String {
+    let lines: Vec<&str> = original.split_inclusive('\n').collect();
+    let line_starts: Vec<usize> = lines
+        .iter()
+        .scan(0, |at, line| {
+            let start = *at;
+            *at += line.len();
+            Some(start)
+        })
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset).max(1) - 1;
+
+    let mut sorted: Vec<&MutationRecord> = mutations.iter().collect();
+    sorted.sort_by_key(|m| m.start);
+    // mutations sharing a line are rewritten as one chunk
+    let mut groups: Vec<(usize, usize, Vec<&MutationRecord>)> = Vec::new();
+    for m in sorted {
+        let first = line_of(m.start);
+        let last = line_of(m.end.max(m.start + 1) - 1).max(first);
+        match groups.last_mut() {
+            Some((_, group_last, group)) if first <= *group_last => {
+                *group_last = (*group_last).max(last);
+                group.push(m);
+            }
+            _ => groups.push((first, last, vec![m])),
+        }
+    }
+    let chunks: Vec<Chunk> = groups
+        .into_iter()
+        .map(|(first, last, group)| {
+            let last = last.min(lines.len().saturating_sub(1));
+            let from = line_starts.get(first).copied().unwrap_or(original.len());
+            let to = line_starts.get(last + 1).copied().unwrap_or(original.len());
+            let mut text = String::new();
+            let mut at = from;
+            let sites = group
+                .iter()
+                .map(|m| format!("{} {}", m.kind, m.span()))
+                .collect();
+            for m in group {
+                text.push_str(&original[at..m.start]);
+                text.push_str(&m.replacement);
+                at = m.end;
+            }
+            text.push_str(&original[at..to]);
+            Chunk {
+                start: first,
+                old: lines
+                    .get(first..=last)
+                    .map(<[&str]>::to_vec)
+                    .unwrap_or_default(),
+                new: text.split_inclusive('\n').map(str::to_string).collect(),
+                sites,
+            }
+        })
+        .collect();
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+    // line count difference of the chunks written so far
+    let mut shift: isize = 0;
+    let mut i = 0;
+    while i < chunks.len() {
+        // merge chunks whose context would overlap into one hunk
+        let mut j = i;
+        while j + 1 < chunks.len()
+            && chunks[j + 1].start <= chunks[j].start + chunks[j].old.len() + 2 * CONTEXT
+        {
+            j += 1;
+        }
+        let hunk_start = chunks[i].start.saturating_sub(CONTEXT);
+        let hunk_end = (chunks[j].start + chunks[j].old.len() + CONTEXT).min(lines.len());
+        let mut body = String::new();
+        let (mut old_len, mut new_len) = (0, 0);
+        let mut at = hunk_start;
+        for chunk in &chunks[i..=j] {
+            for line in &lines[at..chunk.start] {
+                push_line(&mut body, ' ', line);
+            }
+            old_len += chunk.start - at;
+            new_len += chunk.start - at;
+            for line in &chunk.old {
+                push_line(&mut body, '-', line);
+            }
+            for line in &chunk.new {
+                push_line(&mut body, '+', line);
+            }
+            old_len += chunk.old.len();
+            new_len += chunk.new.len();
+            at = chunk.start + chunk.old.len();
+        }
+        for line in &lines[at..hunk_end] {
+            push_line(&mut body, ' ', line);
+        }
+        old_len += hunk_end - at;
+        new_len += hunk_end - at;
+        let new_start = hunk_start as isize + shift;
+        // where the text after `@@` names the function, name the mutations
+        let sites: Vec<&str> = chunks[i..=j]
+            .iter()
+            .flat_map(|chunk| chunk.sites.iter().map(String::as_str))
+            .collect();
+        out.push_str(&format!(
+            "@@ -{} +{} @@ {}\n",
+            range(hunk_start, old_len),
+            range(new_start as usize, new_len),
+            sites.join(", ")
+        ));
+        out.push_str(&body);
+        shift += new_len as isize - old_len as isize;
+        i = j + 1;
+    }
+    out
+}
+
+/// `start,len` of a hunk header, counting lines from 1; an empty range
+/// names the line before it.
+fn range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+fn push_line(out: &mut String, marker: char, line: &str) {
+    out.push(marker);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
@@ -0,0 +1,461 @@
+//! Generation: the variants every selected operator makes of one input
+//! file, filtered and numbered.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use tracing::{debug, info_span};
+use tree_sitter::Tree;
+
+use crusher_core::{
+    crusher::Crusher,
+    features, higher_order,
+    operators::{self, Mutation, Mutator},
+    rng::Rng,
+    targets,
+};
+use crusher_oracle::{compiletest::Directives, equivalence::EquivalenceFilter};
+
+use super::{Cli, ParseFilter, TargetArgs};
+use crate::{
+    diff, encoding,
+    manifest::MutationRecord,
+    select,
+    writer::{Emit, Variant},
+};
+
+/// What generation yields for one input file.
+#[derive(Default)]
+pub(super) struct Generated {
+    /// The input file, as [`Variant::source`] names it.
+    pub(super) source: String,
+    pub(super) variants: Vec<Variant>,
+    pub(super) unparseable: usize,
+    pub(super) dropped_equivalent: usize,
+    /// Variants identical to an earlier one of the file, dropped.
+    pub(super) duplicates: usize,
+    /// The path and why it was skipped, if it was.
+    pub(super) skipped: Option<(String, String)>,
+    /// Not mutated at all since `--max-total` had been reached.
+    pub(super) capped: bool,
+    /// [`crate::cache::content_hash`] of the file under `--incremental`.
+    pub(super) hash: Option<u64>,
+    /// Unchanged since the last `--incremental` run, which has its variants.
+    pub(super) reused: bool,
+    /// The relative path and text of the file for `--emit-baseline`.
+    pub(super) baseline: Option<(PathBuf, String)>,
+}
+
+/// Higher-order variants per file and operator: all combinations up to this
+/// many, a random sample of this size beyond.
+pub(super) const HIGHER_ORDER_LIMIT: usize = 1000;
+
+/// What a run makes of every input file: the selected operators, and the
+/// flags that decide which of their variants are kept and how.
+pub(super) struct Generator<'a> {
+    pub(super) args: &'a Cli,
+    pub(super) mutators: &'a [&'a dyn Mutator],
+    pub(super) equivalence: Option<EquivalenceFilter>,
+    /// Set once `--max-total` is reached, so that the remaining files are
+    /// not mutated in vain.
+    pub(super) full: AtomicBool,
+}
+
+/// An input file parsed, with what all its operators share.
+struct Parsed<'s> {
+    path: &'s Path,
+    relative: &'s Path,
+    source_code: &'s str,
+    tree: Tree,
+    /// The timeout covers all operators, a file is kept or skipped as a whole.
+    deadline: Option<Instant>,
+    directives: Arc<Directives>,
+    /// The `--feature-gate` insertion every variant gets.
+    gate: Option<Mutation>,
+    /// What the variants of no real mutation would be, for the equivalence
+    /// filter.
+    gated: Option<String>,
+}
+
+impl Generator<'_> {
+    /// The variants of one input file, from its path, the path relative to
+    /// the input directory and its content.
+    pub(super) fn generate(
+        &self,
+        crusher: &mut Crusher,
+        path: &Path,
+        relative: &Path,
+        source_code: &str,
+    ) -> Generated {
+        let _file = info_span!("file", path = %path.display()).entered();
+        let args = self.args;
+        let mut generated = Generated {
+            source: path.display().to_string(),
+            ..Generated::default()
+        };
+        if self.full.load(Ordering::Relaxed) {
+            generated.capped = true;
+            return generated;
+        }
+        let skip = |reason| Generated {
+            source: path.display().to_string(),
+            skipped: Some((path.display().to_string(), reason)),
+            ..Generated::default()
+        };
+        let per_file_timeout = args.per_file_timeout.map(Duration::from_secs_f64);
+        let timed_out = || {
+            skip(format!(
+                "per-file timeout of {:?} exceeded",
+                per_file_timeout.unwrap()
+            ))
+        };
+        if let Some(reason) = too_large(args, source_code, None) {
+            return skip(reason);
+        }
+        let deadline = per_file_timeout.map(|t| Instant::now() + t);
+        // one parse serves every operator
+        let parse_started = Instant::now();
+        let Some(tree) = crusher.parse(source_code, deadline) else {
+            return timed_out();
+        };
+        debug!(bytes = source_code.len(), elapsed = ?parse_started.elapsed(), "parsed");
+        if let Some(reason) = too_large(args, source_code, Some(&tree)) {
+            return skip(reason);
+        }
+        let directives = match args.rustc.compiletest {
+            true => Arc::new(Directives::parse(source_code)),
+            false => Arc::default(),
+        };
+        if args.rustc.skip_aux_builds && directives.needs_aux() {
+            return skip(format!(
+                "needs the auxiliary crates {}",
+                directives.aux.join(", ")
+            ));
+        }
+        let gate = self.feature_gate(source_code, &tree);
+        let file = Parsed {
+            path,
+            relative,
+            source_code,
+            deadline,
+            directives,
+            gated: gate.as_ref().map(|gate| gate.apply(source_code)),
+            gate,
+            tree,
+        };
+        // the variants so far across operators by the hash of their text, as
+        // indices into `generated.variants` and then into the kept ones
+        let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+        for &mutator in self.mutators {
+            if self
+                .add_variants(crusher, &file, mutator, &mut seen, &mut generated)
+                .is_none()
+            {
+                return timed_out();
+            }
+        }
+        if let Some(gate) = &file.gate {
+            for variant in &mut generated.variants {
+                variant
+                    .mutations
+                    .insert(0, MutationRecord::new(gate, source_code));
+            }
+        }
+        self.add_edition_twins(&mut generated.variants);
+        if args.emit_baseline {
+            generated.baseline = Some((relative.to_path_buf(), source_code.to_string()));
+        }
+        if args.emit == Emit::Diff {
+            let relative = relative.to_string_lossy();
+            for variant in &mut generated.variants {
+                variant.text = diff::unified_diff(&relative, source_code, &variant.mutations);
+            }
+        }
+        if !args.weight.is_empty() {
+            generated.variants = select::by_weight(
+                &args.weight,
+                self.mutators,
+                generated.variants,
+                &mut Rng::derive(args.seed, source_code),
+            );
+        }
+        if let Some(cap) = args.max_per_file {
+            generated.variants = select::within_cap(
+                args.cap_selection,
+                cap,
+                generated.variants,
+                &mut Rng::derive(args.seed, source_code),
+            );
+        }
+        generated
+    }
+
+    /// The `--feature-gate` and `--random-feature-gates` insertion of
+    /// `source_code`, if there are any.
+    fn feature_gate(&self, source_code: &str, tree: &Tree) -> Option<Mutation> {
+        let args = self.args;
+        let mut gates: Vec<&str> = args.feature_gates.iter().map(String::as_str).collect();
+        if let Some(count) = args.random_feature_gates {
+            let mut rng = Rng::derive(args.seed, source_code);
+            gates.extend(features::sample(&mut rng, count));
+        }
+        (!gates.is_empty()).then(|| {
+            operators::insert_inner_attribute(
+                source_code,
+                tree,
+                encoding::fit_line_endings(
+                    &format!("#![feature({})]\n", gates.join(", ")),
+                    encoding::line_ending(source_code),
+                ),
+            )
+        })
+    }
+
+    /// Adds the variants `mutator` makes of `file` to `generated`, through
+    /// the parse, equivalence and duplicate filters; `None` once the
+    /// per-file timeout is exceeded.
+    fn add_variants(
+        &self,
+        crusher: &mut Crusher,
+        file: &Parsed,
+        mutator: &dyn Mutator,
+        seen: &mut HashMap<u64, Vec<usize>>,
+        generated: &mut Generated,
+    ) -> Option<()> {
+        let args = self.args;
+        let (tree, source_code) = (&file.tree, file.source_code);
+        let variants = crushed(
+            mutator,
+            tree,
+            source_code,
+            &args.targets,
+            args.mutation_order as usize,
+            args.seed,
+            file.gate.as_ref(),
+            file.deadline,
+        )?;
+        debug!(
+            operator = mutator.name(),
+            variants = variants.len(),
+            "mutated"
+        );
+        let mut variants: Vec<_> = variants
+            .into_iter()
+            .map(|(text, mutations)| {
+                let broken = args.require_parse_ok.is_some() && crusher.has_syntax_errors(&text);
+                (text, mutations, broken)
+            })
+            .collect();
+        generated.unparseable += variants.iter().filter(|(_, _, broken)| *broken).count();
+        if args.require_parse_ok == Some(ParseFilter::Drop) {
+            variants.retain(|(_, _, broken)| !broken);
+        }
+        let variants = match &self.equivalence {
+            Some(filter) => {
+                let (kept, dropped) = filter.retain_distinct(
+                    file.gated.as_deref().unwrap_or(source_code),
+                    variants,
+                    |(text, _, _)| text,
+                );
+                generated.dropped_equivalent += dropped;
+                kept
+            }
+            None => variants,
+        };
+        let variants = match args.keep_duplicates {
+            true => variants,
+            false => {
+                let count = variants.len();
+                let earlier: Vec<&str> =
+                    generated.variants.iter().map(|v| v.text.as_str()).collect();
+                let kept = select::drop_duplicates(
+                    &earlier,
+                    variants,
+                    |(text, _, _)| text,
+                    seen,
+                    select::text_hash,
+                );
+                generated.duplicates += count - kept.len();
+                kept
+            }
+        };
+        generated
+            .variants
+            .extend(variants.into_iter().enumerate().map(
+                |(number, (text, mutations, parse_error))| Variant {
+                    target: args.dry_run.map(|_| {
+                        let first = &mutations[0];
+                        (
+                            targets::node_id(tree, first.start, first.end),
+                            targets::target_name(tree, source_code, first.start, first.end)
+                                .map(str::to_string),
+                        )
+                    }),
+                    source: file.path.display().to_string(),
+                    relative: file.relative.to_path_buf(),
+                    operator: mutator.name(),
+                    number,
+                    parse_error,
+                    mutations,
+                    text,
+                    edition: None,
+                    directives: file.directives.clone(),
+                },
+            ));
+        Some(())
+    }
+
+    /// A copy of every variant for each of `--switch-editions`, numbered
+    /// after the variants of its operator.
+    fn add_edition_twins(&self, variants: &mut Vec<Variant>) {
+        let editions = &self.args.rustc.switch_editions;
+        if editions.is_empty() {
+            return;
+        }
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for variant in variants.iter() {
+            let count = counts.entry(variant.operator).or_default();
+            *count = (*count).max(variant.number + 1);
+        }
+        let mut twins = Vec::new();
+        for (k, edition) in editions.iter().enumerate() {
+            for variant in variants.iter() {
+                twins.push(Variant {
+                    number: variant.number + counts[variant.operator] * (k + 1),
+                    edition: Some(edition.clone()),
+                    ..variant.clone()
+                });
+            }
+        }
+        variants.extend(twins);
+    }
+}
+
+/// Why `--max-file-bytes`, or `--max-nodes` once there is a `tree`, skips
+/// `source_code`, if it does.
+pub(super) fn too_large(args: &Cli, source_code: &str, tree: Option<&Tree>) -> Option<String> {
+    if let Some(max) = args.max_file_bytes.filter(|&max| source_code.len() > max) {
+        return Some(format!(
+            "{} bytes, more than --max-file-bytes {}",
+            source_code.len(),
+            max
+        ));
+    }
+    let nodes = tree?.root_node().descendant_count();
+    let max = args.max_nodes.filter(|&max| nodes > max)?;
+    Some(format!("{} nodes, more than --max-nodes {}", nodes, max))
+}
+
+/// Variants of `source_code`, each with `order` of the mutations of
+/// `mutator` that `targets` admits applied, and the `--feature-gate`
+/// insertion `gate` too, together with records of those mutations; the
+/// record of `gate` is left to the caller.
+#[allow(clippy::too_many_arguments)]
+fn crushed(
+    mutator: &dyn Mutator,
+    tree: &Tree,
+    source_code: &str,
+    targets: &TargetArgs,
+    order: usize,
+    seed: u64,
+    gate: Option<&Mutation>,
+    deadline: Option<Instant>,
+) -> Option<Vec<(String, Vec<MutationRecord>)>> {
+    let mut found = mutator.mutations(source_code, tree);
+    let line_ending = encoding::line_ending(source_code);
+    for mutation in &mut found {
+        if mutation.replacement.contains('\n') {
+            mutation.replacement = encoding::fit_line_endings(&mutation.replacement, line_ending);
+        }
+    }
+    targets::retain_admitted(tree, source_code, targets, &mut found);
+    if deadline.is_some_and(|d| Instant::now() >= d) {
+        return None;
+    }
+    let sets = if order == 1 {
+        (0..found.len()).map(|i| vec![i]).collect()
+    } else {
+        let mut rng = Rng::derive(seed, source_code);
+        higher_order::combinations(&found, order, HIGHER_ORDER_LIMIT, &mut rng)
+    };
+    let mut variants = Vec::with_capacity(sets.len());
+    for set in sets {
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return None;
+        }
+        let mut picked: Vec<&Mutation> = set.iter().map(|&i| &found[i]).collect();
+        let records = picked
+            .iter()
+            .map(|m| MutationRecord::new(m, source_code))
+            .collect();
+        let moved;
+        if let Some(gate) = gate {
+            // a mutation across the offset of the gate takes it along to its start
+            match picked
+                .iter()
+                .find(|m| m.start < gate.start && gate.start < m.end)
+            {
+                Some(across) => {
+                    moved = Mutation {
+                        start: across.start,
+                        end: across.start,
+                        ..gate.clone()
+                    };
+                    picked.insert(0, &moved);
+                }
+                // first, so that it goes before insertions at the same offset
+                None => picked.insert(0, gate),
+            }
+        }
+        variants.push((higher_order::apply_all(source_code, &picked), records));
+    }
+    Some(variants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crusher_core::operators::{TokenCrusher, TriviaCrusher};
+
+    #[test]
+    fn feature_gate_after_shebang_of_token_mutants() {
+        let source_code = "#!/usr/bin/env run-cargo-script\nfn f(a: u8) -> u8 { a }\n";
+        let tree = Crusher::new().parse(source_code, None).unwrap();
+        let at = operators::inner_attribute_offset(source_code, &tree);
+        let gate = Mutation {
+            start: at,
+            end: at,
+            replacement: "#![feature(never_type)]\n".to_string(),
+            kind: "source_file",
+        };
+        let mutators: [&dyn Mutator; 2] = [&TokenCrusher { seed: 0 }, &TriviaCrusher { seed: 0 }];
+        for mutator in mutators {
+            let variants = crushed(
+                mutator,
+                &tree,
+                source_code,
+                &TargetArgs::default(),
+                1,
+                0,
+                Some(&gate),
+                None,
+            )
+            .unwrap();
+            assert!(!variants.is_empty());
+            for (text, records) in variants {
+                let (first, rest) = text.split_once('\n').unwrap();
+                assert!(first.starts_with("#!"), "{:?}", text);
+                assert!(rest.starts_with("#![feature(never_type)]\n"), "{:?}", text);
+                // the gate goes with the text, not with the records
+                assert_eq!(records.len(), 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,2081 @@
+//! Command line driver shared by the crusher binaries.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fs,
+    io::{Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process::{self, ExitCode},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{
+    parser::ValueSource, ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand,
+    ValueEnum,
+};
+use notify::{RecursiveMode, Watcher};
+use regex::Regex;
+use serde_json::json;
+use tracing::{info, warn};
+use tree_sitter::Tree;
+
+use crusher_core::{
+    crusher::Crusher,
+    operators::{
+        self, context, CrossoverCrusher, Crush, KeepAttrs, KeepGenerics, Mutation, Mutator,
+        NestingCrusher, ParamCrusher, QueryCrusher, Registry, StructCrusher, Template,
+        TokenCrusher, TriviaCrusher, TypeContext, TypePool, TypePositions, TypenameCrusher,
+    },
+    rng::{Reservoir, Rng},
+    targets,
+};
+use crusher_oracle::{
+    cargo_mode::{self, CargoMode, CargoOptions},
+    compiletest::Directives,
+    equivalence::{EquivalenceFilter, PrettyForm},
+    rustc::{self, Checker, RustcOracle},
+};
+
+use crate::{
+    archive::{Archive, OutputFormat},
+    cache::{self, CachedFile},
+    check::{check_variants, diff_compilers, CheckOptions, DEFAULT_RUSTC_TIMEOUT},
+    config::{self, Setting, Value},
+    encoding::{self, Encoding, Newline},
+    html,
+    logging::{self, LogFormat, LogLevel},
+    manifest::{self, ManifestEntry, RunHeader},
+    parallel::{parallel_for_each, parallel_map},
+    progress::{Progress, ProgressFormat},
+    remote,
+    select::{self, CapSelection, ShardBy},
+    stash,
+    state::{self, StateFile},
+    stats::{self, FileStats},
+    walk::{self, Glob, Shard},
+    writer::{
+        remove_variants, DryRun, Emit, Naming, VariantWriter, PARSE_ERROR_DIR, STREAM_FILE_NAME,
+    },
+};
+
+mod generate;
+
+pub use crate::check::MINIMIZED_DIR;
+use generate::{too_large, Generated, Generator, HIGHER_ORDER_LIMIT};
+
+// use clap cli parser
+/// Crush the structs and other items of Rust sources into variants that
+/// stress the compiler
+#[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Read flags from this file instead of `crusher.toml` in the current directory
+    #[arg(long, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Input file, `-` for standard input
+    #[arg(long)]
+    input_file: Option<String>,
+    #[arg(short, long)]
+    input_dir: Option<String>,
+    /// Fetch the input directory from a git repository at a revision,
+    /// `<url>@<rev>`, into the temporary directory
+    #[arg(long, value_name = "URL@REV", conflicts_with_all = ["input_file", "input_dir", "input_url"])]
+    input_git: Option<String>,
+    /// Download and unpack a tarball as the input directory, into the
+    /// temporary directory
+    #[arg(long, value_name = "URL", conflicts_with_all = ["input_file", "input_dir"])]
+    input_url: Option<String>,
+    #[arg(short, long)]
+    output_dir: Option<String>,
+    /// Print the variant with this index among those of `--input-file` to
+    /// standard output instead of writing any files
+    #[arg(long, requires = "input_file")]
+    index: Option<usize>,
+    /// Count only the variants aimed at the syntax node with this pre-order
+    /// number towards `--index`, which defaults to 0
+    #[arg(long, requires = "input_file")]
+    node_id: Option<usize>,
+    /// Replace the files and manifest of an earlier run in the output directory
+    #[arg(long, conflicts_with = "append")]
+    overwrite: bool,
+    /// Continue the interrupted run in the output directory where it stopped,
+    /// with the same flags
+    #[arg(long, conflicts_with_all = ["overwrite", "append"])]
+    resume: bool,
+    /// Add to an earlier run in the output directory, numbering after its variants
+    #[arg(long)]
+    append: bool,
+    /// Keep content hashes in the output directory and, run again there with
+    /// the same flags, only crush the input files that changed since
+    #[arg(long, conflicts_with_all = ["resume", "append"])]
+    incremental: bool,
+    /// Keep running and, whenever `.rs` files change in the input directory,
+    /// crush the changed ones as `--incremental` does
+    #[arg(long, requires = "input_dir", conflicts_with_all = ["resume", "append", "dry_run", "stats"])]
+    watch: bool,
+    /// Comma-separated mutation operators to run on every input (defaults to
+    /// the one this binary is named after)
+    #[arg(long, alias = "operator", value_delimiter = ',')]
+    operators: Vec<String>,
+    /// Also run the mutations of this tree-sitter query file, an operator
+    /// named after its file stem; may be repeated
+    #[arg(long, value_name = "FILE.scm")]
+    query: Vec<PathBuf>,
+    /// Combine this many independent mutations into every variant
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+    mutation_order: u64,
+    /// Enable this nightly feature in every variant with `#![feature(..)]`,
+    /// may be repeated
+    #[arg(long = "feature-gate", value_name = "FEATURE")]
+    feature_gates: Vec<String>,
+    /// Also enable this many nightly features in every variant, picked per
+    /// input file by `--seed` out of a bundled list
+    #[arg(long, value_name = "N")]
+    random_feature_gates: Option<usize>,
+    /// Write only a random subset of this many variants
+    #[arg(long)]
+    sample: Option<usize>,
+    /// Write at most this many variants in all
+    #[arg(long)]
+    max_total: Option<usize>,
+    /// Keep at most this many variants of every input file
+    #[arg(long)]
+    max_per_file: Option<usize>,
+    /// Which variants `--max-total` and `--max-per-file` keep
+    #[arg(long, value_enum, default_value = "first")]
+    cap_selection: CapSelection,
+    /// Draw the variants of every file from the operators in proportion to
+    /// their weights, `structs=7`, so that the caps keep that mix; operators
+    /// not listed weigh 1, and 0 drops them; may be repeated
+    #[arg(long, value_name = "OPERATOR=WEIGHT", value_parser = parse_weight, conflicts_with = "cap_selection")]
+    weight: Vec<(String, f64)>,
+    /// Seed for `--sample` and all other random choices; recorded in the manifest
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Drop variants tree-sitter cannot parse, or with `bucket` write them
+    /// under `parse_errors/` instead
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "drop")]
+    require_parse_ok: Option<ParseFilter>,
+    /// List the variants a run would write, with operator, source, spans and
+    /// target name, and their count, without writing anything
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "text",
+        conflicts_with_all = ["check_with", "diff_compilers", "cargo_project"]
+    )]
+    dry_run: Option<DryRun>,
+    /// Count, without writing a mutant, the nodes the operators target and
+    /// the mutants they would make per file and in all, and the parse times
+    #[arg(
+        long,
+        value_enum,
+        num_args = 0..=1,
+        default_missing_value = "text",
+        conflicts_with_all = ["dry_run", "check_with", "diff_compilers", "cargo_project", "report_html"]
+    )]
+    stats: Option<DryRun>,
+    /// Compile every variant and classify the outcome into `rustc_report.json`
+    #[arg(long, value_enum)]
+    check_with: Option<Checker>,
+    /// Comma-separated verdicts of `--check-with` to keep, e.g. `ice,crash`;
+    /// other variants are deleted
+    #[arg(long, value_delimiter = ',', requires = "check_with", value_parser = rustc::VERDICT_LABELS)]
+    keep: Vec<String>,
+    /// With `--check-with`, keep one variant per distinct ICE signature
+    #[arg(long, requires = "check_with")]
+    dedup_ices: bool,
+    /// With `--check-with`, shrink every kept ICE by deleting syntax nodes
+    /// while the same crash persists, into `minimized/`
+    #[arg(long, requires = "check_with")]
+    minimize: bool,
+    /// Compile every variant with both toolchains and report in
+    /// `diff_report.json` the variants they disagree on
+    #[arg(long, num_args = 2, value_names = ["RUSTC_A", "RUSTC_B"])]
+    diff_compilers: Vec<String>,
+    /// Experimental: drop variants whose pretty-printed form equals the original's
+    #[arg(long, value_enum)]
+    drop_equivalent: Option<PrettyForm>,
+    /// Keep variants byte-identical to an earlier variant of the same file
+    #[arg(long)]
+    keep_duplicates: bool,
+    /// Mutate this Cargo project or workspace and run cargo against every mutant
+    #[arg(long)]
+    cargo_project: Option<PathBuf>,
+    #[arg(long, value_enum, default_value = "mutate", requires = "cargo_project")]
+    mode: CargoMode,
+    /// Also render a browsable HTML report of the run into this directory
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["dry_run", "cargo_project"])]
+    report_html: Option<PathBuf>,
+    /// Apply every mutant to the `--cargo-project` itself and revert it
+    /// afterwards instead of working in copies, reusing its `target` directory
+    #[arg(long, requires = "cargo_project")]
+    in_place: bool,
+    /// Number of input files mutated, or mutants evaluated, concurrently
+    #[arg(short, long, default_value_t = 1)]
+    jobs: usize,
+    /// How the progress of every phase is shown on standard error
+    #[arg(long, value_enum, default_value = "bar")]
+    progress: ProgressFormat,
+    /// Show no progress
+    #[arg(short, long, conflicts_with = "progress")]
+    quiet: bool,
+    /// Diagnostics shown on standard error; `RUST_LOG` overrides it
+    #[arg(long, value_enum, default_value = "warn")]
+    log_level: LogLevel,
+    #[arg(long, value_enum, default_value = "text")]
+    log_format: LogFormat,
+    /// Seconds before a single evaluation (test run, compilation) is killed
+    #[arg(long)]
+    timeout: Option<u64>,
+    /// Mebibytes of address space each rustc run of `--check-with` and
+    /// `--diff-compilers` may use; runs beyond it are reported as `oom`
+    #[arg(long)]
+    memory_limit: Option<u64>,
+    /// Seconds parsing and mutant generation may spend on one input file
+    #[arg(long)]
+    per_file_timeout: Option<f64>,
+    /// Skip input files larger than this many bytes, such as generated ones
+    #[arg(long)]
+    max_file_bytes: Option<usize>,
+    /// Skip input files whose syntax tree has more nodes than this
+    #[arg(long)]
+    max_nodes: Option<usize>,
+    /// Write every variant as a full copy of its input file, or as a unified
+    /// diff against it
+    #[arg(long, value_enum, default_value = "full")]
+    emit: Emit,
+    /// Write the variants of every operator into a directory of its own,
+    /// `<operator>/dir/<operator>_foo_<n>.rs`
+    #[arg(long)]
+    per_operator_dirs: bool,
+    /// With `--emit jsonl`, include the whole mutated file as `text` in every record
+    #[arg(long)]
+    with_text: bool,
+    /// Also write every input file with variants as `foo.baseline.rs` next
+    /// to them, exactly the text they were made from
+    #[arg(long)]
+    emit_baseline: bool,
+    /// Write the variants as loose files or stream them into one archive in
+    /// the output directory, with the paths of the manifest
+    #[arg(long, value_enum, default_value = "dir")]
+    output_format: OutputFormat,
+    /// How input files are decoded
+    #[arg(long, value_enum, default_value = "utf8")]
+    encoding: Encoding,
+    /// The line endings of the variants
+    #[arg(long, value_enum, default_value = "preserve")]
+    newline: Newline,
+    /// Walk only the paths below `--input-dir` matching this glob, may be repeated
+    #[arg(long, requires = "input_dir")]
+    include: Vec<Glob>,
+    /// Skip the paths below `--input-dir` matching this glob, such as
+    /// `target` or `tests/ui/parser`; may be repeated
+    #[arg(long, requires = "input_dir")]
+    exclude: Vec<Glob>,
+    /// Skip the files git ignores below `--input-dir`
+    #[arg(long, requires = "input_dir")]
+    respect_gitignore: bool,
+    /// Also walk the directories symlinks below `--input-dir` point to
+    #[arg(long, requires = "input_dir")]
+    follow_symlinks: bool,
+    /// Take only the `i`th of `n` disjoint parts of the input files, or of
+    /// their variants with `--shard-by variant`, so that `n` machines share
+    /// a campaign
+    #[arg(long, value_name = "I/N")]
+    shard: Option<Shard>,
+    /// What `--shard` splits up
+    #[arg(long, value_enum, default_value = "file")]
+    shard_by: ShardBy,
+    #[command(flatten)]
+    targets: TargetArgs,
+    #[command(flatten)]
+    operator_args: OperatorArgs,
+    #[command(flatten)]
+    rustc: RustcArgs,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ParseFilter {
+    Drop,
+    Bucket,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Run the rustc command recorded in the manifest for one variant
+    Repro {
+        variant_id: usize,
+        /// Directory holding the variants and their manifest (defaults to current dir)
+        #[arg(short, long)]
+        output_dir: Option<String>,
+    },
+    /// Make one variant again from its manifest entry and its input file,
+    /// printing it unless it is written elsewhere
+    Reproduce {
+        #[command(flatten)]
+        variant: VariantArgs,
+        /// Write the variant to this file
+        #[arg(short, long, conflicts_with = "in_place")]
+        output: Option<PathBuf>,
+        /// Overwrite the input file with the variant, as in a checkout
+        #[arg(long)]
+        in_place: bool,
+    },
+    /// Put one variant in place of its input file, keeping the original in a
+    /// stash for `revert`, so tests run in the project layout
+    Apply {
+        #[command(flatten)]
+        variant: VariantArgs,
+        /// Where the originals are kept
+        #[arg(long, default_value = stash::STASH_DIR)]
+        stash: PathBuf,
+    },
+    /// Put back every file `apply` replaced
+    Revert {
+        /// The stash `apply` kept the originals in
+        #[arg(long, default_value = stash::STASH_DIR)]
+        stash: PathBuf,
+    },
+    /// Describe an operator and show what it does to an example, or list all operators
+    Explain { operator: Option<String> },
+}
+
+// A variant of a manifest and the input file it is made from again.
+#[derive(Args, Debug)]
+struct VariantArgs {
+    /// `manifest.jsonl`, or the directory holding it
+    #[arg(long, default_value = ".")]
+    manifest: PathBuf,
+    /// Id of the variant in the manifest
+    #[arg(long)]
+    id: usize,
+    /// The input file, when not where the manifest says it was
+    #[arg(long)]
+    source: Option<PathBuf>,
+    /// How the input file is decoded
+    #[arg(long, value_enum, default_value = "utf8")]
+    encoding: Encoding,
+    /// The line endings the run gave the variants
+    #[arg(long, value_enum, default_value = "preserve")]
+    newline: Newline,
+}
+
+// Which mutations of the operators are kept, by what they are aimed at.
+#[derive(Args, Debug, Default)]
+struct TargetArgs {
+    /// Mutate only structs and types whose name matches this regex
+    #[arg(long)]
+    include_regex: Option<Regex>,
+    /// Leave structs and types whose name matches this regex alone
+    #[arg(long)]
+    exclude_regex: Option<Regex>,
+    /// Leave `#[test]` functions and `#[cfg(test)]` items alone
+    #[arg(long)]
+    skip_tests: bool,
+    /// Mutate only items declared `pub`, in `pub` modules
+    #[arg(long)]
+    public_only: bool,
+    /// Mutate only what intersects lines FIRST-LAST, counting from 1
+    #[arg(long, value_name = "FIRST-LAST", value_parser = parse_lines, conflicts_with = "span")]
+    lines: Option<(usize, usize)>,
+    /// Mutate only what intersects bytes START..END
+    #[arg(long, value_name = "START..END", value_parser = parse_span)]
+    span: Option<Range<usize>>,
+}
+
+fn parse_lines(text: &str) -> Result<(usize, usize), String> {
+    let parsed = match text.split_once('-') {
+        Some((first, last)) => first.trim().parse().ok().zip(last.trim().parse().ok()),
+        None => text.trim().parse().ok().map(|line| (line, line)),
+    };
+    match parsed {
+        Some((first, last)) if 1 <= first && first <= last => Ok((first, last)),
+        _ => Err(format!(
+            "expected FIRST-LAST with 1 <= FIRST <= LAST, got {:?}",
+            text
+        )),
+    }
+}
+
+fn parse_weight(text: &str) -> Result<(String, f64), String> {
+    let parsed = text
+        .split_once('=')
+        .and_then(|(name, weight)| Some((name.trim(), weight.trim().parse::<f64>().ok()?)));
+    match parsed {
+        Some((name, weight)) if !name.is_empty() && weight >= 0.0 && weight.is_finite() => {
+            Ok((name.to_string(), weight))
+        }
+        _ => Err(format!(
+            "expected OPERATOR=WEIGHT with a WEIGHT of 0 or more, got {:?}",
+            text
+        )),
+    }
+}
+
+fn parse_span(text: &str) -> Result<Range<usize>, String> {
+    let parsed = text
+        .split_once("..")
+        .and_then(|(start, end)| start.trim().parse().ok().zip(end.trim().parse().ok()));
+    match parsed {
+        Some((start, end)) if start <= end => Ok(start..end),
+        _ => Err(format!(
+            "expected START..END with START <= END, got {:?}",
+            text
+        )),
+    }
+}
+
+impl targets::Targets for TargetArgs {
+    fn retain(&self, tree: &Tree, source_code: &str, mutations: &mut Vec<Mutation>) {
+        let span = match self.lines {
+            Some((first, last)) => Some(targets::line_span(source_code, first, last)),
+            None => self.span.clone(),
+        };
+        mutations.retain(|m| {
+            span.as_ref()
+                .is_none_or(|span| targets::intersects(m, span))
+                && self.admits(tree, source_code, m)
+        });
+    }
+}
+
+impl TargetArgs {
+    fn admits(&self, tree: &Tree, source_code: &str, mutation: &Mutation) -> bool {
+        if self.skip_tests && targets::in_test_code(tree, source_code, mutation) {
+            return false;
+        }
+        if self.public_only && !targets::in_public_api(tree, mutation) {
+            return false;
+        }
+        if self.include_regex.is_none() && self.exclude_regex.is_none() {
+            return true;
+        }
+        let name = targets::target_name(tree, source_code, mutation.start, mutation.end);
+        let matches = |regex: &Regex| name.is_some_and(|name| regex.is_match(name));
+        self.include_regex.as_ref().is_none_or(matches)
+            && !self.exclude_regex.as_ref().is_some_and(matches)
+    }
+}
+
+// Settings of individual operators, listed by `explain <operator>`.
+#[derive(Args, Debug)]
+struct OperatorArgs {
+    /// Replacements of the `typenames` operator
+    #[arg(long, value_enum, default_value = "fixed")]
+    type_pool: TypePool,
+    /// Occurrences the `typenames` operator mutates: where types are defined, used, or both
+    #[arg(long, value_enum, default_value = "both")]
+    positions: TypePositions,
+    /// Contexts the `typenames` operator mutates types in, all by default
+    #[arg(long, value_enum, value_delimiter = ',')]
+    contexts: Vec<TypeContext>,
+    /// Replacement type of the `typenames` operator's fixed pool in place of
+    /// nothing, `i32`, `str` and `Copy`, may be repeated
+    #[arg(long = "type", value_name = "TYPE")]
+    types: Vec<String>,
+    /// What the `structs` operator does with the attributes and doc comments
+    /// before a struct
+    #[arg(long, value_enum, default_value = "keep")]
+    keep_attrs: KeepAttrs,
+    /// Whether the `structs` operator keeps the generic parameters of a
+    /// struct on its empty declaration, or writes a mutant each way
+    #[arg(long, value_enum, default_value = "drop")]
+    keep_generics: KeepGenerics,
+    /// Make the `structs` operator also rewrite the struct's construction
+    /// sites, patterns and field accesses in the file to its new form
+    #[arg(long)]
+    fix_uses: bool,
+    /// Forms the `structs` operator crushes every struct into, one mutant
+    /// each, `halved` and `duplicated` keeping half or twice its fields; by
+    /// default unit and tuple structs become `struct S();` and those with
+    /// named fields `struct S;`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    forms: Vec<Crush>,
+    /// Make the `params` operator also rewrite the arguments of the calls
+    /// to the function in the file
+    #[arg(long)]
+    fix_calls: bool,
+    /// Layers of `Option<..>`, parentheses or blocks the `nesting` operator
+    /// wraps around every site
+    #[arg(long, value_name = "N", default_value_t = 256)]
+    nesting_depth: usize,
+}
+
+// How the recorded reproduction command invokes rustc.
+#[derive(Args, Debug)]
+pub(crate) struct RustcArgs {
+    #[arg(long, default_value = "rustc")]
+    rustc: String,
+    /// Edition of the rustc commands; tree-sitter parses all editions alike
+    #[arg(long, default_value = "2021", value_parser = EDITIONS)]
+    edition: String,
+    /// Also write every variant once for each of these editions, to be
+    /// compiled with it
+    #[arg(long, value_delimiter = ',', value_parser = EDITIONS)]
+    switch_editions: Vec<String>,
+    #[arg(long, default_value = "lib")]
+    crate_type: String,
+    /// Extra rustc flag such as `-Zvalidate-mir`, may be repeated
+    #[arg(long = "rustc-flag", allow_hyphen_values = true)]
+    rustc_flags: Vec<String>,
+    /// Read the `//@` compiletest directives of every input, such as the
+    /// `tests/ui` files of rust-lang/rust, into its rustc commands: the
+    /// edition, compile-flags and first revision
+    #[arg(long)]
+    compiletest: bool,
+    /// Skip the inputs whose directives build auxiliary crates first
+    /// (`aux-build`, `aux-crate`, `aux-bin` and `proc-macro`)
+    #[arg(long, requires = "compiletest")]
+    skip_aux_builds: bool,
+}
+
+const EDITIONS: [&str; 4] = ["2015", "2018", "2021", "2024"];
+
+impl RustcArgs {
+    /// The command compiling `file_name`, with `--edition` unless `edition`
+    /// or the compiletest `directives` of its input give one.
+    pub(crate) fn command_for(
+        &self,
+        file_name: &str,
+        edition: Option<&str>,
+        directives: &Directives,
+    ) -> Vec<String> {
+        // rustc derives the crate name from the file stem, which for
+        // `foo.mutant_structs_0.rs` is not a valid identifier
+        let stem = Path::new(file_name).file_stem().unwrap().to_string_lossy();
+        let crate_name: String = stem
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        let edition = edition
+            .or(directives.edition.as_deref())
+            .unwrap_or(&self.edition);
+        let mut command = vec![
+            self.rustc.clone(),
+            "--edition".to_string(),
+            edition.to_string(),
+        ];
+        // a second `--crate-type` would build both
+        if !directives.sets_crate_type() {
+            command.extend(["--crate-type".to_string(), self.crate_type.clone()]);
+        }
+        command.extend(["--crate-name".to_string(), crate_name]);
+        command.extend(self.rustc_flags.iter().cloned());
+        command.extend(directives.rustc_args());
+        command.push(file_name.to_string());
+        command
+    }
+}
+
+fn repro(variant_id: usize, output_dir: Option<String>) -> Result<ExitCode> {
+    let dir: PathBuf = match output_dir {
+        Some(dir) => dir.into(),
+        None => std::env::current_dir()?,
+    };
+    let entries = manifest::read_manifest(&dir)
+        .with_context(|| format!("Cannot read the manifest in {:?}", dir))?;
+    let Some(entry) = entries.iter().find(|e| e.id == variant_id) else {
+        bail!("No variant with id {} in {:?}", variant_id, dir);
+    };
+
+    let Some((program, arguments)) = entry.command.split_first() else {
+        bail!("Variant {} has no command in the manifest", variant_id);
+    };
+
+    println!("{}", manifest::shell_quote(&entry.command));
+    let status = process::Command::new(program)
+        .args(arguments)
+        .current_dir(&dir)
+        .status()
+        .with_context(|| format!("Cannot run {:?}", program))?;
+    // a command killed by a signal has no code of its own
+    Ok(match status.code() {
+        Some(code) => ExitCode::from(code as u8),
+        None => ExitCode::FAILURE,
+    })
+}
+
+/// The manifest entry of a variant and its input file.
+fn find_variant(variant: &VariantArgs) -> Result<(ManifestEntry, PathBuf)> {
+    let manifest = &variant.manifest;
+    let entries = match manifest.is_dir() {
+        true => manifest::read_manifest(manifest),
+        false => manifest::read_manifest_file(manifest),
+    }
+    .with_context(|| format!("Cannot read the manifest {:?}", manifest))?;
+    let Some(entry) = entries.into_iter().find(|e| e.id == variant.id) else {
+        bail!("No variant with id {} in {:?}", variant.id, manifest);
+    };
+    let source = match &variant.source {
+        Some(source) => source.clone(),
+        None if entry.source == STDIN => {
+            bail!(
+                "Variant {} was made from standard input, pass --source",
+                entry.id
+            )
+        }
+        None => PathBuf::from(&entry.source),
+    };
+    Ok((entry, source))
+}
+
+/// The text of the variant of `entry`, made again from `source`.
+fn reproduce(entry: &ManifestEntry, source: &Path, variant: &VariantArgs) -> Result<String> {
+    let source_code = encoding::read_source(source, variant.encoding, variant.newline)
+        .with_context(|| format!("Cannot read {:?}", source))?;
+    entry.apply(&source_code).map_err(|err| {
+        anyhow!(
+            "Cannot make variant {} again from {:?}: {}",
+            entry.id,
+            source,
+            err
+        )
+    })
+}
+
+fn explain(operator: Option<String>, registry: &Registry, options: &OperatorArgs) -> Result<()> {
+    let Some(name) = operator else {
+        for mutator in registry.iter() {
+            println!("{:<12} {}", mutator.name(), mutator.doc().summary);
+        }
+        return Ok(());
+    };
+    let configured = configured_operators(options);
+    let mutator = select_operator(&name, registry, &configured)?;
+    let doc = mutator.doc();
+    println!("{}: {}\n", mutator.name(), doc.summary);
+    println!("Targets: {}", doc.node_kinds.join(", "));
+    if !mutator.templates().is_empty() {
+        println!("Templates:");
+        for template in mutator.templates() {
+            println!("  {:?}", template.text);
+        }
+    }
+    if doc.flags.is_empty() {
+        println!("Flags: none specific to this operator");
+    } else {
+        println!("Flags:");
+        for (flag, effect) in doc.flags {
+            println!("  {:<24} {}", flag, effect);
+        }
+    }
+
+    println!("\nExample input:\n{}", doc.example);
+    for (idx, variant) in operators::crushed_sources(mutator, doc.example)
+        .iter()
+        .enumerate()
+    {
+        println!("Mutant {}:\n{}", idx, variant);
+    }
+    Ok(())
+}
+
+/// Input files, or the path of one that cannot be read and why.
+type InputFiles = Vec<Result<PathBuf, (String, String)>>;
+
+/// The `.rs` files below `input_dir` the path flags let through.
+fn input_files(args: &Cli, input_dir: &str) -> Result<InputFiles> {
+    let filter = walk::PathFilter {
+        include: args.include.clone(),
+        exclude: args.exclude.clone(),
+    };
+    let mut files = walk::rust_files(Path::new(input_dir), &filter, args.follow_symlinks);
+    if args.respect_gitignore {
+        walk::drop_gitignored(Path::new(input_dir), &mut files)
+            .context("Cannot ask git which files --respect-gitignore skips")?;
+    }
+    Ok(files)
+}
+
+/// The operators the operator flags set up, standing in for those of the
+/// registry.
+fn configured_operators(options: &OperatorArgs) -> Vec<Box<dyn Mutator>> {
+    let structs = StructCrusher {
+        attrs: options.keep_attrs,
+        generics: options.keep_generics,
+        fix_uses: options.fix_uses,
+        forms: options.forms.clone(),
+    };
+    let typenames = TypenameCrusher {
+        pool: options.type_pool,
+        positions: options.positions,
+        contexts: options.contexts.clone(),
+        // leaked, as templates are: the operator lives as long as the run
+        types: options
+            .types
+            .iter()
+            .map(|t| Template::new(context::TYPE, String::leak(t.clone())))
+            .collect::<Vec<_>>()
+            .leak(),
+    };
+    let params = ParamCrusher {
+        fix_calls: options.fix_calls,
+    };
+    let nesting = NestingCrusher {
+        depth: options.nesting_depth,
+    };
+    vec![
+        Box::new(structs),
+        Box::new(typenames),
+        Box::new(params),
+        Box::new(nesting),
+    ]
+}
+
+/// The crossover operator with the items of every file below
+/// `--input-dir`, at most [`CORPUS_SNIPPETS`] of them.
+fn configured_crossover(args: &Cli) -> Result<Box<dyn Mutator>> {
+    let Some(input_dir) = args.input_dir.as_deref() else {
+        bail!("The crossover operator harvests its items from --input-dir");
+    };
+    let mut crusher = Crusher::new();
+    let mut rng = Rng::new(args.seed);
+    let mut corpus = Reservoir::new(CORPUS_SNIPPETS);
+    // unreadable files are reported when their turn comes to be crushed
+    for path in input_files(args, input_dir)?.into_iter().flatten() {
+        let Ok(source_code) = encoding::read_source(&path, args.encoding, args.newline) else {
+            continue;
+        };
+        if let Some(tree) = crusher.parse(&source_code, None) {
+            for snippet in operators::harvest(&source_code, &tree) {
+                corpus.offer(&mut rng, snippet);
+            }
+        }
+    }
+    Ok(Box::new(CrossoverCrusher {
+        snippets: corpus.into_items(),
+        seed: args.seed,
+    }))
+}
+
+/// `name` of the registry, or the operator of `configured` standing in
+/// for it.
+fn select_operator<'r>(
+    name: &str,
+    registry: &'r Registry,
+    configured: &'r [Box<dyn Mutator>],
+) -> Result<&'r dyn Mutator> {
+    let mutator = match registry.get(name) {
+        Some(mutator) => configured
+            .iter()
+            .find(|m| m.name() == name)
+            .map_or(mutator, |m| m.as_ref()),
+        None => bail!(
+            "Unknown operator {:?}, expected one of {:?}",
+            name,
+            registry.names()
+        ),
+    };
+    // catch a broken template now rather than after generating a corpus of garbage
+    for warning in operators::validate(mutator.name(), mutator.templates()) {
+        warn!("{}", warning);
+    }
+    Ok(mutator)
+}
+
+/// The operator of the query file `path`, named after its file stem.
+fn load_query(path: &Path, registry: &Registry) -> Result<QueryCrusher> {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if name.is_empty() || registry.get(&name).is_some() {
+        bail!(
+            "The --query file {:?} needs a file stem other than the operators {:?}",
+            path,
+            registry.names()
+        );
+    }
+    let source = fs::read_to_string(path).with_context(|| format!("Cannot read {:?}", path))?;
+    // leaked, as templates are: the operator lives as long as the run
+    QueryCrusher::new(String::leak(name), &source)
+        .map_err(|err| anyhow!("{}:{}", path.display(), err))
+}
+
+/// Items of the corpus the crossover operator splices from, a sample when
+/// the input directory holds more.
+const CORPUS_SNIPPETS: usize = 4096;
+
+/// Inputs that were not mutated, one `path<TAB>reason` line each.
+const SKIPPED_FILE_NAME: &str = "skipped_files.tsv";
+
+fn prepare_output_dir(output_dir: Option<String>) -> Result<PathBuf> {
+    if let Some(o) = output_dir {
+        // if directory exists then use it, otherwise create it (and notice it to the user)
+        if !PathBuf::from(&o).exists() {
+            fs::create_dir_all(&o)
+                .with_context(|| format!("Cannot create output directory {:?}", o))?;
+            info!("Created output directory: {}", o);
+        }
+        Ok(o.into())
+    } else {
+        // notice it uses current dir to user
+        let current_dir = std::env::current_dir()?;
+        info!(
+            "No output directory provided, using current directory: {:?}",
+            current_dir
+        );
+        Ok(current_dir)
+    }
+}
+
+/// `--input-file` naming standard input.
+const STDIN: &str = "-";
+
+/// `--output-dir` naming standard output.
+const STDOUT: &str = "-";
+
+/// What the variants of standard input are named after.
+const STDIN_FILE_NAME: &str = "stdin.rs";
+
+fn read_input(input_file: &str, encoding: Encoding, newline: Newline) -> Result<String> {
+    if input_file == STDIN {
+        let mut bytes = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut bytes)
+            .context("Cannot read standard input")?;
+        return Ok(encoding::decode_source(bytes, encoding, newline)?);
+    }
+    encoding::read_source(Path::new(input_file), encoding, newline)
+        .with_context(|| format!("Cannot read {:?}", input_file))
+}
+
+/// `--stats` of every input file and their total, on standard output.
+fn print_stats(args: &Cli, mutators: &[&dyn Mutator], format: DryRun) -> Result<()> {
+    let mut kinds: Vec<&'static str> = Vec::new();
+    for mutator in mutators {
+        for &kind in mutator.doc().node_kinds {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+    }
+    let file_stats = |crusher: &mut Crusher, source: String, source_code: &str| {
+        let skip = |reason| warn!("Skipped {}: {}", source, reason);
+        if let Some(reason) = too_large(args, source_code, None) {
+            skip(reason);
+            return None;
+        }
+        let started = Instant::now();
+        let tree = crusher.parse(source_code, None)?;
+        let parse_time = started.elapsed();
+        if let Some(reason) = too_large(args, source_code, Some(&tree)) {
+            skip(reason);
+            return None;
+        }
+        let mutants = mutators
+            .iter()
+            .map(|&mutator| {
+                let mut found = mutator.mutations(source_code, &tree);
+                targets::retain_admitted(&tree, source_code, &args.targets, &mut found);
+                (mutator.name(), estimated_mutants(found.len(), args))
+            })
+            .collect();
+        Some(FileStats {
+            source,
+            bytes: source_code.len(),
+            parse_time,
+            nodes: stats::count_nodes(&tree, &kinds),
+            mutants,
+        })
+    };
+
+    let progress = (!args.quiet).then_some(args.progress);
+    let files: Vec<FileStats> = match (&args.input_file, args.input_dir.as_deref()) {
+        (Some(input_file), _) => {
+            let source_code = read_input(input_file, args.encoding, args.newline)?;
+            file_stats(&mut Crusher::new(), input_file.clone(), &source_code)
+                .into_iter()
+                .collect()
+        }
+        (None, Some(input_dir)) => {
+            let paths: Vec<PathBuf> = input_files(args, input_dir)?
+                .into_iter()
+                .filter_map(|file| {
+                    file.map_err(|(path, reason)| warn!("Skipped {}: {}", path, reason))
+                        .ok()
+                })
+                .collect();
+            parallel_map(
+                args.jobs,
+                &paths,
+                (progress, "stats", "files"),
+                |_| RefCell::new(Crusher::new()),
+                |crusher, path| match encoding::read_source(path, args.encoding, args.newline) {
+                    Ok(source_code) => file_stats(
+                        &mut crusher.borrow_mut(),
+                        path.display().to_string(),
+                        &source_code,
+                    ),
+                    Err(err) => {
+                        warn!("Skipped {}: {}", path.display(), err);
+                        None
+                    }
+                },
+            )
+            .into_iter()
+            .flatten()
+            .collect()
+        }
+        (None, None) => bail!("No input file or directory provided"),
+    };
+    let total = FileStats::total(&files);
+    match format {
+        DryRun::Text => {
+            for file in &files {
+                print!("{}", file.to_text());
+            }
+            print!("Total of {}", total.to_text());
+        }
+        DryRun::Json => {
+            for file in &files {
+                println!("{}", file.to_json());
+            }
+            println!("{}", json!({ "total": total.to_json() }));
+        }
+    }
+    Ok(())
+}
+
+/// The variants a file with `found` mutations of an operator gets: one per
+/// mutation, or per combination of `--mutation-order` of them up to
+/// [`HIGHER_ORDER_LIMIT`] (whether they overlap is not checked), times the
+/// editions, within `--max-per-file`; the parse and equivalence filters and
+/// dropped duplicates are not foreseen.
+fn estimated_mutants(found: usize, args: &Cli) -> usize {
+    let order = args.mutation_order as usize;
+    let mut combinations: usize = 1;
+    for i in 0..order {
+        combinations = combinations.saturating_mul(found.saturating_sub(i)) / (i + 1);
+        if combinations > HIGHER_ORDER_LIMIT && order > 1 {
+            combinations = HIGHER_ORDER_LIMIT;
+            break;
+        }
+    }
+    let variants = combinations * (1 + args.rustc.switch_editions.len());
+    args.max_per_file.map_or(variants, |cap| variants.min(cap))
+}
+
+/// Parse the command line, taking every setting it leaves out from the
+/// config file.
+fn parse_args() -> Result<Cli> {
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    let mut command = Cli::command();
+    command.build();
+    let given = command.clone().get_matches_from(&argv);
+    let path = match given.get_one::<PathBuf>("config") {
+        Some(path) => path.clone(),
+        None if Path::new(config::CONFIG_FILE_NAME).exists() => config::CONFIG_FILE_NAME.into(),
+        None => return Ok(Cli::from_arg_matches(&given)?),
+    };
+    // the subcommands take none of the flags
+    if given.subcommand().is_some() {
+        return Ok(Cli::from_arg_matches(&given)?);
+    }
+    let mut merged = vec![argv[0].clone()];
+    for setting in config::read_config(&path)? {
+        merged.extend(
+            config_flags(&command, &given, &setting)
+                .map_err(|message| anyhow!("{}:{}: {}", path.display(), setting.line, message))?,
+        );
+    }
+    merged.extend(argv.into_iter().skip(1));
+    match command.try_get_matches_from(merged) {
+        Ok(matches) => Ok(Cli::from_arg_matches(&matches)?),
+        Err(err) => {
+            let _ = err.print();
+            eprintln!("(with the flags of {})", path.display());
+            process::exit(err.exit_code());
+        }
+    }
+}
+
+/// The flags standing for `setting`, none if the command line `given` sets
+/// the same flag or one conflicting with it.
+fn config_flags(
+    command: &clap::Command,
+    given: &ArgMatches,
+    setting: &Setting,
+) -> Result<Vec<OsString>, String> {
+    let Some(arg) = command
+        .get_arguments()
+        .find(|arg| arg.get_long() == Some(&setting.key))
+    else {
+        return Err(format!("there is no flag --{}", setting.key));
+    };
+    if arg.get_id() == "config" {
+        return Err("a config file cannot name another".to_string());
+    }
+    let on_command_line =
+        |id: &clap::Id| given.value_source(id.as_str()) == Some(ValueSource::CommandLine);
+    let overridden = on_command_line(arg.get_id())
+        || command.get_arguments().any(|other| {
+            on_command_line(other.get_id())
+                && (command.get_arg_conflicts_with(arg).contains(&other)
+                    || command.get_arg_conflicts_with(other).contains(&arg))
+        });
+    if overridden {
+        return Ok(Vec::new());
+    }
+    let flag = format!("--{}", setting.key);
+    let scalar = |value: &Value| match value {
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Scalar(s) => Ok(s.clone()),
+        Value::Array(_) => Err(format!("{} takes no array", flag)),
+    };
+    Ok(match &setting.value {
+        Value::Bool(b) if !arg.get_action().takes_values() => {
+            if *b {
+                vec![flag.into()]
+            } else {
+                Vec::new()
+            }
+        }
+        // `--diff-compilers A B` takes its values together
+        Value::Array(items) if arg.get_num_args().is_some_and(|n| n.max_values() > 1) => {
+            let mut flags = vec![flag.clone().into()];
+            for item in items {
+                flags.push(scalar(item)?.into());
+            }
+            flags
+        }
+        Value::Array(items) if arg.get_action().takes_values() => items
+            .iter()
+            .map(|item| Ok(format!("{}={}", flag, scalar(item)?).into()))
+            .collect::<Result<_, String>>()?,
+        Value::Array(_) => return Err(format!("{} takes no array", flag)),
+        value => vec![format!("{}={}", flag, scalar(value)?).into()],
+    })
+}
+
+/// The flags of a run as far as its output goes, to tell whether `--resume`
+/// continues the same run.
+fn fingerprint(args: &mut Cli) -> String {
+    let jobs = std::mem::replace(&mut args.jobs, 1);
+    let overwrite = std::mem::take(&mut args.overwrite);
+    let resume = std::mem::take(&mut args.resume);
+    let config = args.config.take();
+    let progress = std::mem::replace(&mut args.progress, ProgressFormat::Bar);
+    let quiet = std::mem::take(&mut args.quiet);
+    let log_level = std::mem::replace(&mut args.log_level, LogLevel::Warn);
+    let log_format = std::mem::replace(&mut args.log_format, LogFormat::Text);
+    // a round of `--watch` is an `--incremental` run
+    let watch = std::mem::take(&mut args.watch);
+    args.incremental |= watch;
+    let fingerprint = format!("{:?}", args);
+    (args.jobs, args.overwrite, args.resume, args.config) = (jobs, overwrite, resume, config);
+    (args.progress, args.quiet) = (progress, quiet);
+    (args.log_level, args.log_format, args.watch) = (log_level, log_format, watch);
+    fingerprint
+}
+
+/// The directory `--input-git` or `--input-url` fetched, if given.
+fn fetch_input(args: &Cli) -> Result<Option<PathBuf>> {
+    let dir = if let Some(spec) = &args.input_git {
+        let (url, rev) =
+            remote::parse_git_spec(spec).map_err(|err| anyhow!("--input-git: {}", err))?;
+        remote::fetch_git(url, rev).with_context(|| format!("Cannot fetch {}", spec))?
+    } else if let Some(url) = &args.input_url {
+        remote::fetch_url(url).with_context(|| format!("Cannot fetch {}", url))?
+    } else {
+        return Ok(None);
+    };
+    info!("Input directory: {}", dir.display());
+    Ok(Some(dir))
+}
+
+/// Parse the command line and run the selected operators on every input source.
+pub fn run(default_operator: &str) -> Result<ExitCode> {
+    run_with(&Registry::builtin(), default_operator)
+}
+
+/// [`run`] choosing the operators from `registry`, for front ends adding
+/// operators of their own.
+pub fn run_with(registry: &Registry, default_operator: &str) -> Result<ExitCode> {
+    let mut args = parse_args()?;
+    logging::init(args.log_level, args.log_format);
+    // a fetched input counts by its `--input-git` or `--input-url`, not by
+    // the directory it is fetched into
+    let fingerprint = fingerprint(&mut args);
+    if let Some(input_dir) = fetch_input(&args)? {
+        args.input_dir = Some(input_dir.display().to_string());
+    }
+
+    match args.command {
+        Some(Command::Repro {
+            variant_id,
+            output_dir,
+        }) => return repro(variant_id, output_dir),
+        Some(Command::Reproduce {
+            variant,
+            output,
+            in_place,
+        }) => {
+            let (entry, source) = find_variant(&variant)?;
+            let text = reproduce(&entry, &source, &variant)?;
+            let output = match in_place {
+                true => Some(&source),
+                false => output.as_ref(),
+            };
+            match output {
+                Some(path) => {
+                    fs::write(path, &text).with_context(|| format!("Cannot write {:?}", path))?
+                }
+                None => std::io::stdout().write_all(text.as_bytes())?,
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Apply { variant, stash }) => {
+            let (entry, source) = find_variant(&variant)?;
+            // a file holding a variant already has its original in the stash
+            let original = stash::original(&stash, &source)
+                .with_context(|| format!("Cannot read the stash {:?}", stash))?;
+            let from = original.as_ref().unwrap_or(&source);
+            let text = reproduce(&entry, from, &variant)?;
+            stash::stash(&stash, &source, variant.id)
+                .with_context(|| format!("Cannot stash {:?} in {:?}", source, stash))?;
+            fs::write(&source, &text).with_context(|| format!("Cannot write {:?}", source))?;
+            println!("Applied variant {} to {}", variant.id, source.display());
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Revert { stash }) => {
+            let restored = stash::restore(&stash)
+                .with_context(|| format!("Cannot revert from {:?}", stash))?;
+            for file in &restored {
+                println!("Reverted variant {} in {}", file.id, file.path.display());
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Explain { operator }) => {
+            explain(operator, registry, &args.operator_args)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        None => {}
+    }
+
+    let mut names: Vec<&str> = Vec::new();
+    for name in args.operators.iter() {
+        if !names.contains(&name.as_str()) {
+            names.push(name);
+        }
+    }
+    let mut queries: Vec<QueryCrusher> = Vec::new();
+    for path in &args.query {
+        let query = load_query(path, registry)?;
+        if queries.iter().any(|q| q.name() == query.name()) {
+            bail!("Two --query files are named {:?}", query.name());
+        }
+        queries.push(query);
+    }
+    if names.is_empty() && queries.is_empty() {
+        names.push(default_operator);
+    }
+    let mut configured = configured_operators(&args.operator_args);
+    configured.push(Box::new(TokenCrusher { seed: args.seed }));
+    configured.push(Box::new(TriviaCrusher { seed: args.seed }));
+    if names.contains(&"crossover") {
+        configured.push(configured_crossover(&args)?);
+    }
+    let mut mutators: Vec<&dyn Mutator> = names
+        .into_iter()
+        .map(|name| select_operator(name, registry, &configured))
+        .collect::<Result<_>>()?;
+    mutators.extend(queries.iter().map(|q| q as &dyn Mutator));
+
+    if let Some(project) = args.cargo_project {
+        if args.in_place && args.jobs > 1 {
+            bail!(
+                "--in-place mutates one project, it cannot run --jobs {}",
+                args.jobs
+            );
+        }
+        let options = CargoOptions {
+            project,
+            mode: args.mode,
+            in_place: args.in_place,
+            jobs: args.jobs,
+            timeout: args.timeout.map(Duration::from_secs),
+            output_dir: prepare_output_dir(args.output_dir)?,
+            targets: &args.targets,
+        };
+        cargo_mode::run(&mutators, &options)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if args.rustc.switch_editions.contains(&args.rustc.edition) {
+        bail!(
+            "--switch-editions lists {}, the edition of all variants already",
+            args.rustc.edition
+        );
+    }
+    if let Some((name, _)) = args
+        .weight
+        .iter()
+        .find(|(n, _)| !mutators.iter().any(|m| m.name() == n))
+    {
+        bail!("--weight names {}, which is not a selected operator", name);
+    }
+    if let Some(format) = args.stats {
+        print_stats(&args, &mutators, format)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    let generator = Generator {
+        args: &args,
+        mutators: &mutators,
+        equivalence: args.drop_equivalent.map(|form| EquivalenceFilter {
+            rustc: args.rustc.rustc.clone(),
+            edition: args.rustc.edition.clone(),
+            crate_type: args.rustc.crate_type.clone(),
+            form,
+        }),
+        full: AtomicBool::new(false),
+    };
+
+    if args.index.is_some() || args.node_id.is_some() {
+        let input_file = args.input_file.as_deref().unwrap();
+        let source_code = read_input(input_file, args.encoding, args.newline)?;
+        let mut crusher = Crusher::new();
+        let generated = generator.generate(
+            &mut crusher,
+            Path::new(input_file),
+            Path::new(""),
+            &source_code,
+        );
+        if let Some((path, reason)) = generated.skipped {
+            bail!("Cannot mutate {}: {}", path, reason);
+        }
+        let tree = crusher.parse(&source_code, None).unwrap();
+        let mut candidates = generated.variants.into_iter().filter(|variant| {
+            args.node_id.is_none_or(|id| {
+                let first = &variant.mutations[0];
+                targets::node_id(&tree, first.start, first.end) == id
+            })
+        });
+        let index = args.index.unwrap_or(0);
+        let Some(variant) = candidates.nth(index) else {
+            bail!("No variant with index {}", index);
+        };
+        std::io::stdout().write_all(variant.text.as_bytes())?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    match args.watch {
+        true => watch(&args, &fingerprint, &generator)?,
+        false => crush_inputs(&args, &fingerprint, &generator)?,
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Time without further changes before `--watch` starts a round, so that a
+/// file being written or a batch of files is taken in one go.
+const WATCH_SETTLE: Duration = Duration::from_millis(500);
+
+/// `--watch`: a run over the input directory, then one more every time `.rs`
+/// files in it change, added, written or deleted, until interrupted. Only
+/// the first run stops at an error, the later ones report it and wait for
+/// the next change.
+fn watch(args: &Cli, fingerprint: &str, generator: &Generator) -> Result<()> {
+    let input_dir = args.input_dir.as_deref().unwrap();
+    let input_dir = fs::canonicalize(input_dir)
+        .with_context(|| format!("Cannot read the input directory {}", input_dir))?;
+    let (sender, events) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).context("Cannot watch the input directory")?;
+    watcher
+        .watch(&input_dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Cannot watch {:?}", input_dir))?;
+    crush_inputs(args, fingerprint, generator)?;
+    // the variants are `.rs` files too, and must not set off another round
+    let output_dir = fs::canonicalize(args.output_dir.as_deref().unwrap_or("."))?;
+    let changed = |event: notify::Result<notify::Event>| match event {
+        Ok(event) => {
+            !event.kind.is_access()
+                && event.paths.iter().any(|path| {
+                    path.extension().is_some_and(|ext| ext == "rs")
+                        && !path.starts_with(&output_dir)
+                })
+        }
+        Err(err) => {
+            warn!("Watching {:?}: {}", input_dir, err);
+            false
+        }
+    };
+    loop {
+        info!("Watching {:?} for changes", input_dir);
+        while !changed(
+            events
+                .recv()
+                .context("The input directory is no longer watched")?,
+        ) {}
+        while events.recv_timeout(WATCH_SETTLE).is_ok() {}
+        generator.full.store(false, Ordering::Relaxed);
+        if let Err(err) = crush_inputs(args, fingerprint, generator) {
+            warn!("{:#}", err);
+        }
+    }
+}
+
+/// What the files of a run add up to, for its summary.
+#[derive(Default)]
+struct Tally {
+    unparseable: usize,
+    dropped_equivalent: usize,
+    duplicates: usize,
+    /// Unchanged files whose variants `--incremental` kept.
+    reused: usize,
+    /// The path of every skipped file and why it was skipped.
+    skipped: Vec<(String, String)>,
+}
+
+impl Tally {
+    fn add(&mut self, unparseable: usize, dropped_equivalent: usize, duplicates: usize) {
+        self.unparseable += unparseable;
+        self.dropped_equivalent += dropped_equivalent;
+        self.duplicates += duplicates;
+    }
+
+    /// The lines of the summary after the generation, through `status`.
+    fn report(&self, args: &Cli, equivalent: bool, status: impl Fn(String)) {
+        match args.require_parse_ok {
+            Some(ParseFilter::Drop) => status(format!(
+                "Dropped unparseable variants: {}",
+                self.unparseable
+            )),
+            Some(ParseFilter::Bucket) => status(format!(
+                "Unparseable variants (in {}{}/): {}",
+                if args.per_operator_dirs {
+                    "<operator>/"
+                } else {
+                    ""
+                },
+                PARSE_ERROR_DIR,
+                self.unparseable
+            )),
+            None => {}
+        }
+        if equivalent {
+            status(format!(
+                "Dropped equivalent variants: {}",
+                self.dropped_equivalent
+            ));
+        }
+        if self.duplicates > 0 {
+            status(format!("Dropped duplicate variants: {}", self.duplicates));
+        }
+        if args.incremental {
+            status(format!(
+                "Reused the variants of unchanged files: {}",
+                self.reused
+            ));
+        }
+        if !self.skipped.is_empty() {
+            status(format!("Skipped files: {}", self.skipped.len()));
+        }
+    }
+}
+
+/// The output directory of a run, refusing one that holds an earlier run
+/// the flags do not say what to do with.
+fn run_output_dir(args: &Cli, to_stdout: bool) -> Result<PathBuf> {
+    if to_stdout && (args.emit != Emit::Jsonl || args.append) {
+        bail!("Only --emit jsonl can write a run to standard output, and it cannot --append");
+    }
+    let output_dir = match args.dry_run {
+        // nothing is written, the directory only decides the numbering under `--append`
+        Some(_) => args.output_dir.clone().unwrap_or_default().into(),
+        None if to_stdout => PathBuf::new(),
+        None => prepare_output_dir(args.output_dir.clone())?,
+    };
+    if args.dry_run.is_none()
+        && !to_stdout
+        && !args.overwrite
+        && !args.append
+        && !args.resume
+        && !args.incremental
+        && output_dir.join(manifest::MANIFEST_FILE_NAME).exists()
+    {
+        bail!(
+            "{:?} already holds an earlier run, pass --overwrite or --append",
+            output_dir
+        );
+    }
+    Ok(output_dir)
+}
+
+/// Picks up the interrupted run in `output_dir` for `--resume`: `writer`
+/// takes its variants and `tally` its counts. The input files it has done
+/// and the verdicts it has by id are returned.
+fn resume_run(
+    output_dir: &Path,
+    fingerprint: &str,
+    writer: &mut VariantWriter,
+    tally: &mut Tally,
+) -> Result<(HashSet<String>, HashMap<usize, rustc::Verdict>)> {
+    let state_path = output_dir.join(state::STATE_FILE_NAME);
+    let Some(previous) =
+        state::read_state(output_dir).with_context(|| format!("Cannot read {:?}", state_path))?
+    else {
+        if output_dir.join(manifest::MANIFEST_FILE_NAME).exists() {
+            bail!(
+                "The run in {:?} has finished, there is nothing to resume",
+                output_dir
+            );
+        }
+        return Ok(Default::default());
+    };
+    if previous.fingerprint != fingerprint {
+        bail!(
+            "The run in {:?} was started with other flags, resume it with the same ones",
+            output_dir
+        );
+    }
+    info!(
+        "Resuming after {} files and {} checked variants",
+        previous.files.len(),
+        previous.verdicts.len()
+    );
+    let mut done = HashSet::new();
+    for file in previous.files {
+        writer.keep(file.entries);
+        if let Some(reason) = file.skipped {
+            tally.skipped.push((file.source.clone(), reason));
+        }
+        tally.add(file.unparseable, file.dropped_equivalent, file.duplicates);
+        done.insert(file.source);
+    }
+    Ok((done, previous.verdicts))
+}
+
+/// The files below `input_dir` a run crushes, without those `done` before
+/// `--resume` and those of other shards, and the names of those differing
+/// from another in case only.
+fn pending_files(
+    args: &Cli,
+    input_dir: &str,
+    done: &HashSet<String>,
+    progress: Option<ProgressFormat>,
+) -> Result<(InputFiles, HashMap<PathBuf, PathBuf>)> {
+    let discovery = Progress::start(progress, "discover", "files", None);
+    let mut files = input_files(args, input_dir)?;
+    // from all files, so that the names do not depend on `--shard` or `--resume`
+    let renamed = walk::case_safe_paths(
+        files
+            .iter()
+            .filter_map(|file| file.as_ref().ok())
+            .map(|path| path.strip_prefix(input_dir).unwrap()),
+    );
+    for (relative, path) in &renamed {
+        info!(
+            "{} differs from another input file in case only, its variants are named after {}",
+            relative.display(),
+            path.display()
+        );
+    }
+    files.retain(|file| {
+        let path = match file {
+            Ok(path) => path.as_path(),
+            Err((path, _)) => Path::new(path),
+        };
+        let relative = path.strip_prefix(input_dir).unwrap_or(path);
+        !done.contains(&path.display().to_string())
+            && select::file_in_shard(args.shard, args.shard_by, relative)
+    });
+    discovery.advance(files.len());
+    discovery.finish();
+    Ok((files, renamed))
+}
+
+/// The manifest entries of a run by input file, with `verdicts` and the
+/// `hashes` of the files, for the next `--incremental` run.
+fn cached_files(
+    hashes: Vec<(String, u64)>,
+    entries: &[ManifestEntry],
+    mut verdicts: HashMap<usize, rustc::Verdict>,
+) -> Vec<CachedFile> {
+    let mut files: Vec<CachedFile> = hashes
+        .into_iter()
+        .map(|(source, hash)| CachedFile {
+            source,
+            hash,
+            entries: Vec::new(),
+            verdicts: Vec::new(),
+        })
+        .collect();
+    let index: HashMap<String, usize> = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| (file.source.clone(), i))
+        .collect();
+    for entry in entries {
+        let file = &mut files[index[&entry.source]];
+        if let Some(verdict) = verdicts.remove(&entry.id) {
+            file.verdicts.push((entry.id, verdict));
+        }
+        file.entries.push(entry.clone());
+    }
+    files
+}
+
+/// `--check-with` and `--diff-compilers` over the written `entries`: the
+/// variants left and their verdicts by id, adding to `known` from a resumed
+/// run and recording new ones in `state`.
+fn check_written(
+    args: &Cli,
+    output_dir: &Path,
+    entries: Vec<ManifestEntry>,
+    known: HashMap<usize, rustc::Verdict>,
+    state: Option<&Mutex<StateFile>>,
+) -> Result<(Vec<ManifestEntry>, HashMap<usize, rustc::Verdict>)> {
+    let mut oracle = RustcOracle::new(Duration::from_secs(
+        args.timeout.unwrap_or(DEFAULT_RUSTC_TIMEOUT),
+    ));
+    oracle.memory_limit = args.memory_limit.map(|mib| mib << 20);
+    let progress = (!args.quiet).then_some(args.progress);
+    let (entries, verdicts) = match args.check_with {
+        Some(Checker::Rustc) => {
+            let options = CheckOptions {
+                jobs: args.jobs,
+                keep: &args.keep,
+                dedup_ices: args.dedup_ices,
+                minimize: args.minimize,
+                progress,
+                known,
+                state,
+            };
+            check_variants(output_dir, entries, &oracle, &options)?
+        }
+        None => (entries, HashMap::new()),
+    };
+    if !args.diff_compilers.is_empty() {
+        diff_compilers(
+            output_dir,
+            &entries,
+            &oracle,
+            args.jobs,
+            &args.diff_compilers,
+            progress,
+        )?;
+    }
+    Ok((entries, verdicts))
+}
+
+/// The writer of a run into `output_dir`, numbering after the variants
+/// already there under `--append`, into the stream or archive the run
+/// writes to if any.
+fn open_writer<'a>(
+    args: &'a Cli,
+    output_dir: &'a Path,
+    to_stdout: bool,
+) -> Result<VariantWriter<'a>> {
+    let stream_path = output_dir.join(STREAM_FILE_NAME);
+    if args.emit == Emit::Jsonl
+        && args.dry_run.is_none()
+        && !to_stdout
+        && !args.overwrite
+        && !args.append
+        && stream_path.exists()
+    {
+        bail!(
+            "{:?} already holds an earlier run, pass --overwrite or --append",
+            output_dir
+        );
+    }
+    let first_id = if args.emit == Emit::Jsonl && args.append {
+        // records are numbered by their line in the stream
+        fs::read_to_string(&stream_path)
+            .map(|records| records.lines().count())
+            .unwrap_or(0)
+    } else if args.append {
+        let previous = manifest::read_manifest(output_dir).unwrap_or_default();
+        previous.iter().map(|e| e.id + 1).max().unwrap_or(0)
+    } else {
+        0
+    };
+    let mut writer = VariantWriter {
+        output_dir,
+        rustc: &args.rustc,
+        replace: args.overwrite || args.append || args.resume || args.incremental,
+        offsets: args.append.then(HashMap::new),
+        written: HashSet::new(),
+        next_id: first_id,
+        entries: Vec::new(),
+        dry_run: args.dry_run,
+        naming: Naming {
+            emit: args.emit,
+            per_operator: args.per_operator_dirs,
+        },
+        archive: None,
+        stream: None,
+        with_text: args.with_text,
+        baseline: args.emit_baseline,
+        remaining: None,
+        capped: 0,
+    };
+
+    if args.emit != Emit::Full && (args.check_with.is_some() || !args.diff_compilers.is_empty()) {
+        bail!("--check-with and --diff-compilers need --emit full");
+    }
+    if args.emit == Emit::Jsonl && args.dry_run.is_none() {
+        if args.report_html.is_some() {
+            bail!("--report-html reads the manifest, which --emit jsonl does not write");
+        }
+        if args.output_format != OutputFormat::Dir {
+            bail!("--emit jsonl writes one stream, not an archive");
+        }
+        if args.emit_baseline {
+            bail!("--emit-baseline writes files, which --emit jsonl does not");
+        }
+        writer.stream = Some(if to_stdout {
+            Box::new(std::io::stdout().lock())
+        } else {
+            let file = fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(args.append)
+                .truncate(!args.append)
+                .open(&stream_path)
+                .with_context(|| format!("Cannot create {:?}", stream_path))?;
+            Box::new(std::io::BufWriter::new(file))
+        });
+    }
+    if let Some(name) = args.output_format.archive_name() {
+        if args.check_with.is_some() || !args.diff_compilers.is_empty() || args.append {
+            bail!("--check-with, --diff-compilers and --append need --output-format dir");
+        }
+        if args.dry_run.is_none() {
+            let path = output_dir.join(name);
+            writer.archive = Some(
+                Archive::create(&path, args.output_format)
+                    .with_context(|| format!("Cannot create {:?}", path))?,
+            );
+        }
+    }
+    if args.cap_selection == CapSelection::First {
+        writer.remaining = args.max_total;
+    }
+    Ok(writer)
+}
+
+/// One run over the inputs, from output directory to manifest and oracles.
+fn crush_inputs(args: &Cli, fingerprint: &str, generator: &Generator) -> Result<()> {
+    let to_stdout = args.output_dir.as_deref() == Some(STDOUT);
+    let output_dir = run_output_dir(args, to_stdout)?;
+    let mut writer = open_writer(args, &output_dir, to_stdout)?;
+    let first_id = writer.next_id;
+    let sample_size = select::sample_size(args.cap_selection, args.sample, args.max_total);
+
+    // runs holding their variants until the end cannot stop halfway
+    let resumable = args.dry_run.is_none()
+        && args.emit != Emit::Jsonl
+        && writer.archive.is_none()
+        && sample_size.is_none()
+        && !args.append;
+    let state_path = output_dir.join(state::STATE_FILE_NAME);
+    if args.resume && !resumable {
+        bail!("--resume cannot continue a run with --dry-run, --emit jsonl, --output-format, --sample or --append");
+    }
+    if args.incremental && !resumable {
+        bail!("--incremental cannot keep a run with --dry-run, --emit jsonl, --output-format or --sample");
+    }
+    // an interrupted `--incremental` run is made up for by the next one
+    if !args.resume && !args.overwrite && !args.incremental && resumable && state_path.exists() {
+        bail!(
+            "{:?} holds an interrupted run, pass --resume to continue it or --overwrite",
+            output_dir
+        );
+    }
+    let mut tally = Tally::default();
+    let (done, mut resumed_verdicts) = match args.resume {
+        true => resume_run(&output_dir, fingerprint, &mut writer, &mut tally)?,
+        false => Default::default(),
+    };
+    if args.resume && writer.is_full() {
+        generator.full.store(true, Ordering::Relaxed);
+    }
+    let cached = match args.incremental {
+        true => cache::read_cache(&output_dir, fingerprint).with_context(|| {
+            format!("Cannot read {:?}", output_dir.join(cache::CACHE_FILE_NAME))
+        })?,
+        false => HashMap::new(),
+    };
+    // variants of the files crushed again are numbered after the ones kept
+    let kept_ids = cached.values().flat_map(|file| &file.entries);
+    writer.next_id = writer
+        .next_id
+        .max(kept_ids.map(|e| e.id + 1).max().unwrap_or(0));
+    let mut hashes: Vec<(String, u64)> = Vec::new();
+    let mut processed: HashSet<String> = HashSet::new();
+    // files the last `--incremental` run left as they are now are not crushed again
+    let crush = |crusher: &mut Crusher, path: &Path, relative: &Path, source_code: &str| {
+        if !args.incremental {
+            return generator.generate(crusher, path, relative, source_code);
+        }
+        let hash = cache::content_hash(source_code);
+        let source = path.display().to_string();
+        let unchanged = cached.get(&source).is_some_and(|file| {
+            file.hash == hash
+                && file
+                    .entries
+                    .iter()
+                    .all(|entry| output_dir.join(&entry.file).exists())
+        });
+        let mut generated = match unchanged {
+            true => Generated {
+                source,
+                reused: true,
+                ..Generated::default()
+            },
+            false => generator.generate(crusher, path, relative, source_code),
+        };
+        generated.hash = Some(hash);
+        generated
+    };
+    let state_file = match resumable {
+        true => Some(Mutex::new(
+            StateFile::open(&output_dir, fingerprint, args.resume)
+                .with_context(|| format!("Cannot write {:?}", state_path))?,
+        )),
+        false => None,
+    };
+    let progress = (!args.quiet).then_some(args.progress);
+    let (files, renamed) = match (&args.input_file, args.input_dir.as_deref()) {
+        (None, Some(input_dir)) => pending_files(args, input_dir, &done, progress)?,
+        _ => Default::default(),
+    };
+    let generation = Progress::start(
+        progress,
+        "generate",
+        "files",
+        Some(args.input_file.as_ref().map_or(files.len(), |_| 1)),
+    );
+
+    // with `--sample` only the sampled variants are held until the end
+    let mut sample = sample_size.map(|count| (Reservoir::new(count), Rng::new(args.seed)));
+    let mut sink = |mut generated: Generated| -> Result<()> {
+        select::retain_shard_variants(args.shard, args.shard_by, &mut generated.variants);
+        tally.add(
+            generated.unparseable,
+            generated.dropped_equivalent,
+            generated.duplicates,
+        );
+        writer.capped += generated.capped as usize;
+        if let Some((path, reason)) = &generated.skipped {
+            warn!("Skipped {}: {}", path, reason);
+            tally.skipped.push((path.clone(), reason.clone()));
+        }
+        let written = writer.entries.len();
+        let first_written = writer.next_id;
+        processed.insert(generated.source.clone());
+        if let Some(file) = cached.get(&generated.source) {
+            if generated.reused {
+                writer.keep(file.entries.iter().cloned());
+                resumed_verdicts.extend(file.verdicts.iter().cloned());
+                tally.reused += 1;
+            } else {
+                // fewer variants of the file than before leave none behind
+                remove_variants(&output_dir, &file.entries, writer.naming)?;
+            }
+        }
+        if let (Some(hash), None, false) = (generated.hash, &generated.skipped, generated.capped) {
+            hashes.push((generated.source.clone(), hash));
+        }
+        generation.count("variants", generated.variants.len());
+        if let Some((relative, source_code)) = &generated.baseline {
+            if !generated.variants.is_empty() {
+                writer.write_baseline(relative, source_code)?;
+            }
+        }
+        for variant in generated.variants {
+            match &mut sample {
+                Some((reservoir, rng)) => reservoir.offer(rng, variant),
+                None => writer.write(variant)?,
+            }
+        }
+        generation.count("written", writer.next_id - first_written);
+        if let Some(state) = &state_file {
+            let done = state::FileDone {
+                source: generated.source,
+                entries: writer.entries[written..].to_vec(),
+                skipped: generated.skipped.map(|(_, reason)| reason),
+                unparseable: generated.unparseable,
+                dropped_equivalent: generated.dropped_equivalent,
+                duplicates: generated.duplicates,
+            };
+            state
+                .lock()
+                .unwrap()
+                .file_done(&done)
+                .context("Cannot record the progress for --resume")?;
+        }
+        if sample.is_none() && writer.is_full() {
+            generator.full.store(true, Ordering::Relaxed);
+        }
+        Ok(())
+    };
+    if let Some(input_file) = args.input_file.as_deref() {
+        let source_code = read_input(input_file, args.encoding, args.newline)?;
+        let path = Path::new(input_file);
+        let relative = match input_file {
+            STDIN => Path::new(STDIN_FILE_NAME),
+            _ => Path::new(path.file_name().unwrap()),
+        };
+        if select::file_in_shard(args.shard, args.shard_by, relative)
+            && !done.contains(&path.display().to_string())
+        {
+            sink(crush(&mut Crusher::new(), path, relative, &source_code))?;
+        }
+        generation.advance(1);
+    } else if let Some(input_dir) = args.input_dir.as_deref() {
+        parallel_for_each(
+            args.jobs,
+            &files,
+            &generation,
+            // tree-sitter parsers are not Sync, so every thread has its own
+            |_| RefCell::new(Crusher::new()),
+            |crusher, file| {
+                let path = match file {
+                    Ok(path) => path,
+                    Err(skip) => {
+                        return Generated {
+                            source: skip.0.clone(),
+                            skipped: Some(skip.clone()),
+                            ..Generated::default()
+                        }
+                    }
+                };
+                match encoding::read_source(path, args.encoding, args.newline) {
+                    Ok(source_code) => {
+                        let relative = path.strip_prefix(input_dir).unwrap();
+                        let relative = renamed.get(relative).map_or(relative, PathBuf::as_path);
+                        crush(&mut crusher.borrow_mut(), path, relative, &source_code)
+                    }
+                    Err(err) => Generated {
+                        source: path.display().to_string(),
+                        skipped: Some((path.display().to_string(), err.to_string())),
+                        ..Generated::default()
+                    },
+                }
+            },
+            sink,
+        )?;
+    } else {
+        bail!("No input file or directory provided");
+    }
+    generation.finish();
+    for file in cached.values() {
+        if !processed.contains(&file.source) {
+            // gone from the input, or left out by the filters now
+            remove_variants(&output_dir, &file.entries, writer.naming)?;
+        }
+    }
+
+    // a dry run or a stream keeps standard output for itself
+    let status = |line: String| {
+        if args.dry_run.is_some() || to_stdout {
+            eprintln!("{}", line)
+        } else {
+            println!("{}", line)
+        }
+    };
+    tally.report(args, generator.equivalence.is_some(), status);
+    if let Some((reservoir, _)) = sample {
+        let total = reservoir.seen();
+        let items = reservoir.into_items();
+        status(format!("Sampled {} of {} variants", items.len(), total));
+        for variant in items {
+            writer.write(variant)?;
+        }
+    }
+    if writer.capped > 0 {
+        status(format!(
+            "Stopped at --max-total {}",
+            args.max_total.unwrap()
+        ));
+    }
+    if let Some(format) = args.dry_run {
+        let planned = writer.next_id - first_id;
+        match format {
+            DryRun::Text => println!("Number of planned files: {}", planned),
+            DryRun::Json => println!("{}", json!({ "total": planned })),
+        }
+        return Ok(());
+    }
+    if let Some(mut stream) = writer.stream.take() {
+        stream.flush().context("Cannot write a JSONL record")?;
+        status(format!("Number of records: {}", writer.next_id - first_id));
+        return Ok(());
+    }
+    if let Some(archive) = writer.archive.take() {
+        archive.finish().context("Cannot finish the archive")?;
+    }
+    let entries = writer.entries;
+    println!("Number of generated files: {}", entries.len());
+
+    let (entries, verdicts) = check_written(
+        args,
+        &output_dir,
+        entries,
+        resumed_verdicts,
+        state_file.as_ref(),
+    )?;
+    let header = RunHeader {
+        operators: generator
+            .mutators
+            .iter()
+            .map(|m| m.name().to_string())
+            .collect(),
+        seed: args.seed,
+        sample: sample_size,
+    };
+    manifest::write_manifest(&output_dir, &header, &entries, args.append)
+        .with_context(|| format!("Cannot write the manifest in {:?}", output_dir))?;
+    let cache_path = output_dir.join(cache::CACHE_FILE_NAME);
+    if args.incremental {
+        let files = cached_files(hashes, &entries, verdicts);
+        cache::write_cache(&output_dir, fingerprint, &files)
+            .with_context(|| format!("Cannot write {:?}", cache_path))?;
+    } else {
+        // the variants it lists are no longer those of the manifest
+        cache::remove(&output_dir).with_context(|| format!("Cannot delete {:?}", cache_path))?;
+    }
+    if state_file.is_some() {
+        state::remove(&output_dir).with_context(|| format!("Cannot delete {:?}", state_path))?;
+    }
+
+    if !tally.skipped.is_empty() {
+        let listing: String = tally
+            .skipped
+            .iter()
+            .map(|(path, reason)| format!("{}\t{}\n", path, reason))
+            .collect();
+        fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(args.append)
+            .truncate(!args.append)
+            .open(output_dir.join(SKIPPED_FILE_NAME))
+            .and_then(|mut file| file.write_all(listing.as_bytes()))
+            .with_context(|| format!("Cannot write {}", SKIPPED_FILE_NAME))?;
+    }
+    if let Some(report_dir) = &args.report_html {
+        let pages = html::write_report(&output_dir, report_dir)
+            .with_context(|| format!("Cannot write the HTML report to {:?}", report_dir))?;
+        println!(
+            "HTML report ({} pages): {:?}",
+            pages,
+            report_dir.join("index.html")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags_of(argv: &[&str], key: &str, value: Value) -> Result<Vec<OsString>, String> {
+        let mut command = Cli::command();
+        command.build();
+        let given = command
+            .clone()
+            .get_matches_from(["crusher"].iter().chain(argv));
+        let setting = Setting {
+            key: key.to_string(),
+            value,
+            line: 1,
+        };
+        config_flags(&command, &given, &setting)
+    }
+
+    #[test]
+    fn about_text() {
+        // the doc comments of flattened groups would stand in for it
+        let command = Cli::command();
+        command.clone().debug_assert();
+        let about = command.get_about().unwrap().to_string();
+        assert!(about.starts_with("Crush the structs"), "{}", about);
+    }
+
+    #[test]
+    fn config_under_command_line() {
+        let seven = || Value::Scalar("7".to_string());
+        assert_eq!(flags_of(&[], "seed", seven()).unwrap(), ["--seed=7"]);
+        assert!(flags_of(&["--seed", "5"], "seed", seven())
+            .unwrap()
+            .is_empty());
+        // nor does a config flag conflicting with one on the command line count
+        let lines = Value::Scalar("1-2".to_string());
+        assert!(flags_of(&["--span", "0..4"], "lines", lines)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            flags_of(&[], "skip-tests", Value::Bool(true)).unwrap(),
+            ["--skip-tests"]
+        );
+        assert!(flags_of(&[], "skip-tests", Value::Bool(false))
+            .unwrap()
+            .is_empty());
+        let operators = Value::Array(vec![
+            Value::Scalar("structs".to_string()),
+            Value::Scalar("tokens".to_string()),
+        ]);
+        assert_eq!(
+            flags_of(&[], "operators", operators).unwrap(),
+            ["--operators=structs", "--operators=tokens"]
+        );
+    }
+
+    #[test]
+    fn unknown_config_keys() {
+        assert_eq!(
+            flags_of(&[], "no-such-flag", Value::Bool(true)),
+            Err("there is no flag --no-such-flag".to_string())
+        );
+        assert_eq!(
+            flags_of(&[], "config", Value::Scalar("other.toml".to_string())),
+            Err("a config file cannot name another".to_string())
+        );
+        assert_eq!(
+            flags_of(&[], "skip-tests", Value::Array(Vec::new())),
+            Err("--skip-tests takes no array".to_string())
+        );
+    }
+
+    #[test]
+    fn repro_exit_status() {
+        let dir = std::env::temp_dir().join(format!("crusher-repro-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let entry = |id, command: &[&str]| ManifestEntry {
+            id,
+            file: format!("{}.rs", id),
+            source: "in.rs".to_string(),
+            operator: "structs".to_string(),
+            mutations: Vec::new(),
+            command: command.iter().map(|arg| arg.to_string()).collect(),
+        };
+        let header = RunHeader {
+            operators: vec!["structs".to_string()],
+            seed: 0,
+            sample: None,
+        };
+        let entries = [entry(1, &[]), entry(2, &["sh", "-c", "exit 3"])];
+        manifest::write_manifest(&dir, &header, &entries, false).unwrap();
+        let output_dir = Some(dir.display().to_string());
+        let empty = repro(1, output_dir.clone());
+        let status = repro(2, output_dir);
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(empty.unwrap_err().to_string().contains("no command"));
+        assert_eq!(status.unwrap(), ExitCode::from(3));
+    }
+}
@@ -0,0 +1,97 @@
+//! `--encoding`: how input files that are not plain UTF-8 are read, and
+//! `--newline`: the line endings of the text made of them.
+
+use std::{fs, io, path::Path};
+
+use clap::ValueEnum;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Encoding {
+    /// Strip a byte order mark, transcode UTF-16, read other invalid UTF-8 as Latin-1
+    Auto,
+    /// Exactly as on disk; files that are not valid UTF-8 are skipped
+    Utf8,
+    /// Replace invalid UTF-8 sequences with U+FFFD
+    Lossy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Newline {
+    /// As in the input file; inserted lines end like most of its lines
+    Preserve,
+    /// `\n` everywhere
+    Lf,
+    /// `\r\n` everywhere
+    Crlf,
+}
+
+impl Newline {
+    /// `text` with the line endings of `self`.
+    pub fn normalize(self, text: String) -> String {
+        match self {
+            Newline::Preserve => text,
+            Newline::Lf => text.replace("\r\n", "\n"),
+            Newline::Crlf => text.replace("\r\n", "\n").replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Read `path` as source text according to `encoding` and `newline`.
+pub fn read_source(path: &Path, encoding: Encoding, newline: Newline) -> io::Result<String> {
+    decode_source(fs::read(path)?, encoding, newline)
+}
+
+/// Source text from the raw `bytes` of a file or stream.
+pub fn decode_source(bytes: Vec<u8>, encoding: Encoding, newline: Newline) -> io::Result<String> {
+    let text = match encoding {
+        Encoding::Utf8 => String::from_utf8(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.utf8_error()))?,
+        Encoding::Lossy => String::from_utf8_lossy(&bytes).into_owned(),
+        Encoding::Auto => decode(&bytes),
+    };
+    Ok(newline.normalize(text))
+}
+
+/// The line ending of most lines of `source_code`, `\n` for a tie.
+pub fn line_ending(source_code: &str) -> &'static str {
+    let lines = source_code.matches('\n').count();
+    match source_code.matches("\r\n").count() * 2 > lines {
+        true => "\r\n",
+        false => "\n",
+    }
+}
+
+/// `text`, to go into a file whose lines end with `line_ending`, ending its
+/// lines alike; templates end theirs with `\n`, and crossover snippets as
+/// the file they come from.
+pub fn fit_line_endings(text: &str, line_ending: &str) -> String {
+    let text = text.replace("\r\n", "\n");
+    match line_ending {
+        "\n" => text,
+        _ => text.replace('\n', line_ending),
+    }
+}
+
+fn decode(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(b"\xEF\xBB\xBF") {
+        return String::from_utf8_lossy(rest).into_owned();
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFF\xFE") {
+        return utf16(rest, u16::from_le_bytes);
+    }
+    if let Some(rest) = bytes.strip_prefix(b"\xFE\xFF") {
+        return utf16(rest, u16::from_be_bytes);
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => text.to_string(),
+        // every byte is a Latin-1 character, so this never fails
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let units = bytes.chunks_exact(2).map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect()
+}
@@ -0,0 +1,414 @@
+//! `--report-html`: a browsable report of an output directory, built from its
+//! manifest and, when the variants were checked, its rustc report.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use crusher_oracle::rustc;
+use serde_json::Value;
+
+use crate::manifest::{self, ManifestEntry};
+
+/// What the oracle said about one variant.
+struct Outcome {
+    verdict: String,
+    /// Error codes or ICE signature.
+    detail: Option<String>,
+    /// Reduced reproducer, relative to the output directory.
+    minimized: Option<String>,
+}
+
+/// The outcomes in the rustc report of `output_dir`, by manifest id; none
+/// when the run did not check its variants.
+fn read_outcomes(output_dir: &Path) -> io::Result<HashMap<usize, Outcome>> {
+    let text = match fs::read_to_string(output_dir.join(rustc::REPORT_FILE_NAME)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(err) => return Err(err),
+    };
+    let report: Value = serde_json::from_str(&text).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("malformed {}: {}", rustc::REPORT_FILE_NAME, err),
+        )
+    })?;
+    let results = report
+        .get("results")
+        .and_then(Value::as_array)
+        .map_or(&[][..], Vec::as_slice);
+    Ok(results
+        .iter()
+        .filter_map(|result| {
+            let id = result.get("id")?.as_u64()? as usize;
+            let detail = match result.get("codes").and_then(Value::as_array) {
+                Some(codes) => Some(
+                    codes
+                        .iter()
+                        .filter_map(Value::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+                None => result
+                    .get("signature")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            };
+            let outcome = Outcome {
+                verdict: result.get("verdict")?.as_str()?.to_string(),
+                detail: detail.filter(|d| !d.is_empty()),
+                minimized: result
+                    .get("minimized")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+            };
+            Some((id, outcome))
+        })
+        .collect())
+}
+
+/// Write `index.html` and a page per input file into `report_dir`, linking
+/// to the variants in `output_dir`; returns the number of pages.
+pub fn write_report(output_dir: &Path, report_dir: &Path) -> io::Result<usize> {
+    let entries = manifest::read_manifest(output_dir)?;
+    let outcomes = read_outcomes(output_dir)?;
+    let files_dir = report_dir.join("files");
+    fs::create_dir_all(&files_dir)?;
+    // links from the pages in `files/` to the variants
+    let to_output = relative_path(&files_dir.canonicalize()?, &output_dir.canonicalize()?);
+
+    let mut by_source: BTreeMap<&str, Vec<&ManifestEntry>> = BTreeMap::new();
+    for entry in &entries {
+        by_source.entry(&entry.source).or_default().push(entry);
+    }
+    let verdicts: Vec<&str> = rustc::VERDICT_LABELS
+        .into_iter()
+        .filter(|label| outcomes.values().any(|o| o.verdict == *label))
+        .collect();
+
+    let mut operators: Vec<&str> = Vec::new();
+    for entry in &entries {
+        if !operators.contains(&entry.operator.as_str()) {
+            operators.push(&entry.operator);
+        }
+    }
+    let mut body = format!(
+        "<h1>Crusher report</h1>\n<p>{} variants of {} files in <code>{}</code></p>\n",
+        entries.len(),
+        by_source.len(),
+        escape(&output_dir.display().to_string())
+    );
+    body.push_str("<h2>Operators</h2>\n");
+    let rows: Vec<(String, Vec<&ManifestEntry>)> = operators
+        .iter()
+        .map(|&op| {
+            let of_op = entries.iter().filter(|e| e.operator == op).collect();
+            (format!("<code>{}</code>", escape(op)), of_op)
+        })
+        .collect();
+    body.push_str(&count_table("operator", &rows, &verdicts, &outcomes));
+
+    body.push_str("<h2>Files</h2>\n");
+    let mut rows = Vec::new();
+    for (page, (source, of_source)) in by_source.iter().enumerate() {
+        let name = format!("{}.html", page);
+        let title = format!("<a href=\"files/{}\">{}</a>", name, escape(source));
+        rows.push((title, of_source.clone()));
+        let html = source_page(source, of_source, &outcomes, &to_output, &verdicts);
+        fs::write(files_dir.join(name), html)?;
+    }
+    body.push_str(&count_table("file", &rows, &verdicts, &outcomes));
+    fs::write(
+        report_dir.join("index.html"),
+        page("Crusher report", &body, ""),
+    )?;
+    Ok(by_source.len() + 1)
+}
+
+/// Variants and oracle verdicts per row, with a total.
+fn count_table(
+    group: &str,
+    rows: &[(String, Vec<&ManifestEntry>)],
+    verdicts: &[&str],
+    outcomes: &HashMap<usize, Outcome>,
+) -> String {
+    let mut table = format!("<table>\n<tr><th>{}</th><th>variants</th>", group);
+    for verdict in verdicts {
+        table.push_str(&format!("<th class=\"{0}\">{0}</th>", verdict));
+    }
+    table.push_str("</tr>\n");
+    let all: Vec<&ManifestEntry> = rows.iter().flat_map(|(_, e)| e.iter().copied()).collect();
+    for (title, of_row) in rows.iter().chain([&("total".to_string(), all)]) {
+        table.push_str(&format!("<tr><td>{}</td><td>{}</td>", title, of_row.len()));
+        for verdict in verdicts {
+            let n = of_row
+                .iter()
+                .filter(|e| outcomes.get(&e.id).is_some_and(|o| o.verdict == *verdict))
+                .count();
+            table.push_str(&format!("<td>{}</td>", n));
+        }
+        table.push_str("</tr>\n");
+    }
+    table.push_str("</table>\n");
+    table
+}
+
+/// The input file with every mutated span marked, and a row per variant.
+fn source_page(
+    source: &str,
+    entries: &[&ManifestEntry],
+    outcomes: &HashMap<usize, Outcome>,
+    to_output: &Path,
+    verdicts: &[&str],
+) -> String {
+    let source_code = fs::read_to_string(source).ok();
+    let mut body = format!(
+        "<p><a href=\"../index.html\">&larr; all files</a></p>\n<h1><code>{}</code></h1>\n",
+        escape(source)
+    );
+    match &source_code {
+        Some(source_code) => body.push_str(&highlighted(source_code, entries)),
+        None => body.push_str("<p>The input file cannot be read any more.</p>\n"),
+    }
+
+    body.push_str("<p>Show <select id=\"verdict\"><option value=\"\">all verdicts</option>");
+    for verdict in verdicts {
+        body.push_str(&format!("<option>{}</option>", verdict));
+    }
+    body.push_str("</select></p>\n<table id=\"variants\">\n<tr><th>id</th><th>operator</th><th>at</th><th>original</th><th>replacement</th><th>verdict</th><th>variant</th></tr>\n");
+    for entry in entries {
+        let outcome = outcomes.get(&entry.id);
+        let verdict = outcome.map_or("", |o| o.verdict.as_str());
+        let at: Vec<String> = entry
+            .mutations
+            .iter()
+            .map(|m| match &source_code {
+                Some(source_code) => {
+                    let (line, column) = line_column(source_code, m.start);
+                    format!("{}:{}", line, column)
+                }
+                None => format!("{}..{}", m.start, m.end),
+            })
+            .collect();
+        let code_cell = |texts: Vec<&str>| {
+            texts
+                .iter()
+                .map(|t| format!("<code>{}</code>", escape(&shorten(t))))
+                .collect::<Vec<_>>()
+                .join("<br>")
+        };
+        let mut verdict_cell = escape(verdict);
+        if let Some(detail) = outcome.and_then(|o| o.detail.as_ref()) {
+            verdict_cell.push_str(&format!(" <small>{}</small>", escape(detail)));
+        }
+        let mut links = link(to_output, &entry.file, "file");
+        if let Some(minimized) = outcome.and_then(|o| o.minimized.as_ref()) {
+            links.push_str(&format!(" {}", link(to_output, minimized, "minimized")));
+        }
+        body.push_str(&format!(
+            "<tr id=\"v{}\" data-verdict=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"{}\">{}</td><td>{} <details><summary>command</summary><code>{}</code></details></td></tr>\n",
+            entry.id,
+            escape(verdict),
+            entry.id,
+            escape(&entry.operator),
+            at.join("<br>"),
+            code_cell(entry.mutations.iter().map(|m| m.original.as_str()).collect()),
+            code_cell(entry.mutations.iter().map(|m| m.replacement.as_str()).collect()),
+            escape(verdict),
+            verdict_cell,
+            links,
+            escape(&manifest::shell_quote(&entry.command)),
+        ));
+    }
+    body.push_str("</table>\n");
+    page(source, &body, SCRIPT)
+}
+
+/// `source_code` with one `<mark>` per stretch of text the same variants
+/// mutate, and a marker where variants insert.
+fn highlighted(source_code: &str, entries: &[&ManifestEntry]) -> String {
+    let mut spans: Vec<(usize, usize, usize)> = entries
+        .iter()
+        .flat_map(|e| e.mutations.iter().map(|m| (m.start, m.end, e.id)))
+        .filter(|&(start, end, _)| {
+            end <= source_code.len()
+                && source_code.is_char_boundary(start)
+                && source_code.is_char_boundary(end)
+        })
+        .collect();
+    spans.sort();
+    let mut points: Vec<usize> = spans
+        .iter()
+        .flat_map(|&(start, end, _)| [start, end])
+        .chain([0, source_code.len()])
+        .collect();
+    points.sort();
+    points.dedup();
+
+    let mut out = String::from("<pre class=\"source\">");
+    let mut next = 0;
+    // variants rewriting the text at the current point, by end
+    let mut active: Vec<(usize, usize)> = Vec::new();
+    for window in points.windows(2) {
+        let (from, to) = (window[0], window[1]);
+        active.retain(|&(end, _)| end > from);
+        let mut inserting = Vec::new();
+        while next < spans.len() && spans[next].0 == from {
+            let (start, end, id) = spans[next];
+            if start == end {
+                inserting.push(id);
+            } else {
+                active.push((end, id));
+            }
+            next += 1;
+        }
+        if !inserting.is_empty() {
+            out.push_str(&mark("ins", &inserting, "&#x25BE;"));
+        }
+        let text = escape(&source_code[from..to]);
+        if active.is_empty() {
+            out.push_str(&text);
+        } else {
+            let mut ids: Vec<usize> = active.iter().map(|&(_, id)| id).collect();
+            ids.sort();
+            out.push_str(&mark("m", &ids, &text));
+        }
+    }
+    let at_end: Vec<usize> = spans[next..].iter().map(|&(_, _, id)| id).collect();
+    if !at_end.is_empty() {
+        out.push_str(&mark("ins", &at_end, "&#x25BE;"));
+    }
+    out.push_str("</pre>\n");
+    out
+}
+
+fn mark(class: &str, ids: &[usize], html: &str) -> String {
+    let ids: Vec<String> = ids.iter().map(usize::to_string).collect();
+    format!(
+        "<mark class=\"{}\" data-ids=\"{}\" title=\"variants {}\">{}</mark>",
+        class,
+        ids.join(" "),
+        ids.join(", "),
+        html
+    )
+}
+
+fn link(to_output: &Path, file: &str, text: &str) -> String {
+    format!(
+        "<a href=\"{}\">{}</a>",
+        escape(&url_path(&to_output.join(file))),
+        text
+    )
+}
+
+/// 1-based `line:column` of byte `offset`.
+fn line_column(source_code: &str, offset: usize) -> (usize, usize) {
+    let before = &source_code[..offset.min(source_code.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Characters of a table cell before it is cut short.
+const CELL_CHARS: usize = 120;
+
+fn shorten(text: &str) -> String {
+    match text.char_indices().nth(CELL_CHARS) {
+        Some((cut, _)) => format!("{}\u{2026}", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `to` relative to the directory `from`, both absolute.
+fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    path.extend(&to[common..]);
+    path
+}
+
+/// A relative URL for `path`, percent-encoding all but unreserved characters.
+fn url_path(path: &Path) -> String {
+    let components: Vec<String> = path
+        .components()
+        .map(|c| {
+            c.as_os_str()
+                .to_string_lossy()
+                .bytes()
+                .map(|b| {
+                    if b.is_ascii_alphanumeric() || b"-._~".contains(&b) {
+                        (b as char).to_string()
+                    } else {
+                        format!("%{:02X}", b)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    components.join("/")
+}
+
+fn page(title: &str, body: &str, script: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}<script>{}</script>\n</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body,
+        script
+    )
+}
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; vertical-align: top; }
+pre.source { background: #f8f8f8; padding: 1em; overflow-x: auto; }
+mark.m { background: #ffe08a; cursor: pointer; }
+mark.ins { background: none; color: #c00; cursor: pointer; }
+mark.selected, tr.selected { outline: 2px solid #06c; }
+.compiles { color: #080; } .error { color: #666; } .ice, .crash { color: #c00; font-weight: bold; }
+.timeout, .oom { color: #c60; }
+";
+
+/// Clicking a mark selects its variants; the menu filters rows by verdict.
+const SCRIPT: &str = "
+document.querySelectorAll('mark').forEach(function (mark) {
+  mark.addEventListener('click', function () {
+    document.querySelectorAll('.selected').forEach(function (e) { e.classList.remove('selected'); });
+    mark.classList.add('selected');
+    var rows = mark.dataset.ids.split(' ').map(function (id) { return document.getElementById('v' + id); });
+    rows.forEach(function (row) { if (row) { row.classList.add('selected'); } });
+    if (rows[0]) { rows[0].scrollIntoView({block: 'center'}); }
+  });
+});
+document.getElementById('verdict').addEventListener('change', function (event) {
+  var wanted = event.target.value;
+  document.querySelectorAll('#variants tr[data-verdict]').forEach(function (row) {
+    row.style.display = !wanted || row.dataset.verdict === wanted ? '' : 'none';
+  });
+});
+";
@@ -0,0 +1,19 @@
+pub mod archive;
+pub mod cache;
+pub mod check;
+pub mod config;
+pub mod diff;
+pub mod driver;
+pub mod encoding;
+pub mod html;
+pub mod logging;
+pub mod manifest;
+pub mod parallel;
+pub mod progress;
+pub mod remote;
+pub mod select;
+pub mod stash;
+pub mod state;
+pub mod stats;
+pub mod walk;
+pub mod writer;
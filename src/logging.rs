@@ -0,0 +1,55 @@
+//! Diagnostics on standard error through `tracing`: warnings only by
+//! default, more with `--log-level`, as JSON lines with `--log-format json`.
+
+use std::io::IsTerminal;
+
+use clap::ValueEnum;
+use tracing_subscriber::EnvFilter;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// Also what the run does: directories created, files minimized
+    Info,
+    /// Also per file and operator: parse times and mutation counts
+    Debug,
+    /// Also every mutation site found
+    Trace,
+}
+
+impl LogLevel {
+    fn directive(self) -> &'static str {
+        match self {
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per event, with the file it belongs to
+    Json,
+}
+
+/// Installs the logger of the run. `RUST_LOG`, when set, filters instead
+/// of `level`, e.g. `RUST_LOG=crusher_core=trace`.
+pub fn init(level: LogLevel, format: LogFormat) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level.directive()));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .with_target(false);
+    // a front end may have installed its own logger already
+    let _ = match format {
+        LogFormat::Text => builder.try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    };
+}
@@ -0,0 +1,256 @@
+//! `manifest.jsonl`: one record per generated variant, written next to the outputs.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crusher_core::operators::Mutation;
+use serde_json::{json, Value};
+use tree_sitter::Point;
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.jsonl";
+
+#[derive(Debug, Clone)]
+pub struct ManifestEntry {
+    pub id: usize,
+    /// Output file, relative to the manifest's directory.
+    pub file: String,
+    /// Input file the variant was generated from, as given on the command line.
+    pub source: String,
+    /// Name of the operator that produced it.
+    pub operator: String,
+    /// The edits applied to `source`, more than one for higher-order mutants.
+    pub mutations: Vec<MutationRecord>,
+    /// The exact command line reproducing the oracle result for this variant,
+    /// to be run from the manifest's directory.
+    pub command: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MutationRecord {
+    /// Kind of the tree-sitter node that was rewritten.
+    pub kind: String,
+    /// Byte span in the original file.
+    pub start: usize,
+    pub end: usize,
+    /// Rows and byte columns of the span's ends, from 0; written to the
+    /// manifest as lines and columns from 1, as editors count them.
+    pub start_point: Point,
+    pub end_point: Point,
+    pub original: String,
+    pub replacement: String,
+}
+
+impl MutationRecord {
+    pub fn new(mutation: &Mutation, source_code: &str) -> MutationRecord {
+        let site = mutation.site(source_code);
+        MutationRecord {
+            kind: mutation.kind.to_string(),
+            start: mutation.start,
+            end: mutation.end,
+            start_point: site.start_point,
+            end_point: site.end_point,
+            original: mutation.original(source_code).to_string(),
+            replacement: mutation.replacement.clone(),
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "kind": self.kind,
+            "start": self.start,
+            "end": self.end,
+            "start_line": self.start_point.row + 1,
+            "start_column": self.start_point.column + 1,
+            "end_line": self.end_point.row + 1,
+            "end_column": self.end_point.column + 1,
+            "original": self.original,
+            "replacement": self.replacement,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Option<MutationRecord> {
+        // manifests written before lines and columns were recorded lack them
+        let point = |line: &str, column: &str| Point {
+            row: value
+                .get(line)
+                .and_then(Value::as_u64)
+                .unwrap_or(1)
+                .saturating_sub(1) as usize,
+            column: value
+                .get(column)
+                .and_then(Value::as_u64)
+                .unwrap_or(1)
+                .saturating_sub(1) as usize,
+        };
+        Some(MutationRecord {
+            kind: value.get("kind")?.as_str()?.to_string(),
+            start: value.get("start")?.as_u64()? as usize,
+            end: value.get("end")?.as_u64()? as usize,
+            start_point: point("start_line", "start_column"),
+            end_point: point("end_line", "end_column"),
+            original: value.get("original")?.as_str()?.to_string(),
+            replacement: value.get("replacement")?.as_str()?.to_string(),
+        })
+    }
+
+    /// `line:column-line:column` of the span, counting from 1.
+    pub fn span(&self) -> String {
+        format!(
+            "{}:{}-{}:{}",
+            self.start_point.row + 1,
+            self.start_point.column + 1,
+            self.end_point.row + 1,
+            self.end_point.column + 1
+        )
+    }
+}
+
+/// Settings of a run, written as `{"run": {...}}` before its records.
+#[derive(Debug, Clone)]
+pub struct RunHeader {
+    pub operators: Vec<String>,
+    pub seed: u64,
+    /// `--sample` size, when only a subset of the variants was written.
+    pub sample: Option<usize>,
+}
+
+impl RunHeader {
+    pub fn to_json(&self) -> Value {
+        let mut run = json!({ "operators": self.operators, "seed": self.seed });
+        if let Some(sample) = self.sample {
+            run["sample"] = sample.into();
+        }
+        json!({ "run": run })
+    }
+}
+
+impl ManifestEntry {
+    /// The variant, made again from the input file it was made from by its
+    /// recorded mutations; fails if the file no longer holds what they
+    /// replaced.
+    pub fn apply(&self, source_code: &str) -> Result<String, String> {
+        // from the end, so the spans before stay put; an insertion goes
+        // before the replacement starting where it is, and before the
+        // insertions listed after it there
+        let mut mutations: Vec<&MutationRecord> = self.mutations.iter().rev().collect();
+        mutations.sort_by_key(|m| std::cmp::Reverse((m.start, m.end)));
+        let mut text = source_code.to_string();
+        for mutation in mutations {
+            if source_code.get(mutation.start..mutation.end) != Some(mutation.original.as_str()) {
+                return Err(format!(
+                    "{:?} is no longer at {}",
+                    mutation.original,
+                    mutation.span()
+                ));
+            }
+            text.replace_range(mutation.start..mutation.end, &mutation.replacement);
+        }
+        Ok(text)
+    }
+
+    pub fn to_json(&self) -> Value {
+        let mutations: Vec<Value> = self.mutations.iter().map(MutationRecord::to_json).collect();
+        json!({
+            "id": self.id,
+            "file": self.file,
+            "source": self.source,
+            "operator": self.operator,
+            "mutations": mutations,
+            "command": self.command,
+        })
+    }
+
+    pub fn from_json(value: &Value) -> Option<ManifestEntry> {
+        Some(ManifestEntry {
+            id: value.get("id")?.as_u64()? as usize,
+            file: value.get("file")?.as_str()?.to_string(),
+            source: value.get("source")?.as_str()?.to_string(),
+            operator: value.get("operator")?.as_str()?.to_string(),
+            mutations: value
+                .get("mutations")?
+                .as_array()?
+                .iter()
+                .map(MutationRecord::from_json)
+                .collect::<Option<_>>()?,
+            command: value
+                .get("command")?
+                .as_array()?
+                .iter()
+                .map(|arg| arg.as_str().map(str::to_string))
+                .collect::<Option<_>>()?,
+        })
+    }
+}
+
+/// Write the manifest of a run, after the records of earlier runs if `append`.
+pub fn write_manifest(
+    dir: &Path,
+    header: &RunHeader,
+    entries: &[ManifestEntry],
+    append: bool,
+) -> io::Result<()> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(dir.join(MANIFEST_FILE_NAME))?;
+    let mut file = io::BufWriter::new(file);
+    writeln!(file, "{}", header.to_json())?;
+    for entry in entries {
+        writeln!(file, "{}", entry.to_json())?;
+    }
+    file.flush()
+}
+
+pub fn read_manifest(dir: &Path) -> io::Result<Vec<ManifestEntry>> {
+    read_manifest_file(&dir.join(MANIFEST_FILE_NAME))
+}
+
+/// [`read_manifest`] of a manifest by its own path, wherever it was moved.
+pub fn read_manifest_file(path: &Path) -> io::Result<Vec<ManifestEntry>> {
+    let text = fs::read_to_string(path)?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(n, line)| {
+            let value: Option<Value> = serde_json::from_str(line).ok();
+            if value.as_ref().is_some_and(|v| v.get("run").is_some()) {
+                return None;
+            }
+            Some(
+                value
+                    .as_ref()
+                    .and_then(ManifestEntry::from_json)
+                    .ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("malformed manifest record on line {}", n + 1),
+                        )
+                    }),
+            )
+        })
+        .collect()
+}
+
+/// Render a command line so it can be pasted into a POSIX shell.
+pub fn shell_quote(command: &[String]) -> String {
+    command
+        .iter()
+        .map(|arg| {
+            let safe = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+            if safe {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
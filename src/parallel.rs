@@ -0,0 +1,154 @@
+//! Work spread over `--jobs` threads, its results in input order.
+
+use std::{
+    collections::BTreeMap,
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use anyhow::Result;
+
+use crate::progress::{Progress, ProgressFormat};
+
+/// `f` over every item on `jobs` threads, handing the results to `sink` in
+/// the order of `items` as soon as all earlier ones are in, advancing
+/// `progress` by every finished item. `init` makes the state of every thread
+/// from its number. Stops at the first error of `sink`; a panic of `f`
+/// reaches the caller once the other threads are done.
+///
+/// A pool of scoped std threads rather than rayon, which the offline
+/// registry this builds from does not have.
+pub fn parallel_for_each<T: Sync, S, R: Send>(
+    jobs: usize,
+    items: &[T],
+    progress: &Progress,
+    init: impl Fn(usize) -> S + Sync,
+    f: impl Fn(&S, &T) -> R + Sync,
+    mut sink: impl FnMut(R) -> Result<()>,
+) -> Result<()> {
+    let queue = Mutex::new(items.iter().enumerate());
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for worker in 0..jobs.max(1) {
+            let sender = sender.clone();
+            let (queue, init, f) = (&queue, &init, &f);
+            scope.spawn(move || {
+                let state = init(worker);
+                loop {
+                    let Some((i, item)) = queue.lock().unwrap().next() else {
+                        break;
+                    };
+                    // the receiver is gone once the sink has failed
+                    if sender.send((i, f(&state, item))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        let receiver = receiver;
+        // results that overtook an earlier one wait here
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        let finished = receiver.iter().take(items.len());
+        for (i, result) in finished {
+            progress.advance(1);
+            pending.insert(i, result);
+            while let Some(result) = pending.remove(&next) {
+                sink(result)?;
+                next += 1;
+            }
+        }
+        Ok(())
+    })
+}
+
+/// [`parallel_for_each`] collecting the results, as the phase `phase` of
+/// `format`'s progress counting `unit`s.
+pub fn parallel_map<T: Sync, S, R: Send>(
+    jobs: usize,
+    items: &[T],
+    (format, phase, unit): (Option<ProgressFormat>, &'static str, &'static str),
+    init: impl Fn(usize) -> S + Sync,
+    f: impl Fn(&S, &T) -> R + Sync,
+) -> Vec<R> {
+    let progress = Progress::start(format, phase, unit, Some(items.len()));
+    let mut results = Vec::with_capacity(items.len());
+    parallel_for_each(jobs, items, &progress, init, f, |result| {
+        results.push(result);
+        Ok(())
+    })
+    .unwrap();
+    progress.finish();
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn parallel_order_and_panics() {
+        let items: Vec<u64> = (0..40).collect();
+        for jobs in [1, 2, 3, 8, 64] {
+            let progress = Progress::start(None, "test", "items", Some(items.len()));
+            let mut results = Vec::new();
+            // later items finish first, the sink still gets them in order
+            let f = |worker: &usize, item: &u64| {
+                thread::sleep(Duration::from_micros((40 - item) * 50));
+                (*worker, item * 2)
+            };
+            parallel_for_each(
+                jobs,
+                &items,
+                &progress,
+                |worker| worker,
+                f,
+                |result| {
+                    results.push(result);
+                    Ok(())
+                },
+            )
+            .unwrap();
+            let doubled: Vec<u64> = results.iter().map(|&(_, item)| item).collect();
+            assert_eq!(
+                doubled,
+                items.iter().map(|item| item * 2).collect::<Vec<_>>()
+            );
+            assert!(results.iter().all(|&(worker, _)| worker < jobs));
+        }
+
+        let progress = Progress::start(None, "test", "items", Some(items.len()));
+        let mut sunk = 0;
+        let stopped = parallel_for_each(
+            4,
+            &items,
+            &progress,
+            |_| (),
+            |_, item| *item,
+            |item| {
+                sunk += 1;
+                match item {
+                    5 => Err(anyhow!("stop")),
+                    _ => Ok(()),
+                }
+            },
+        );
+        assert_eq!(stopped.unwrap_err().to_string(), "stop");
+        assert_eq!(sunk, 6);
+
+        let panicked = std::panic::catch_unwind(|| {
+            let progress = Progress::start(None, "test", "items", Some(items.len()));
+            let f = |_: &(), item: &u64| match item {
+                7 => panic!("item 7"),
+                _ => *item,
+            };
+            parallel_for_each(4, &items, &progress, |_| (), f, |_| Ok(()))
+        });
+        assert!(panicked.is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Progress of the phases of a run on standard error: discovering the input
+//! files, generating and writing their variants, and the oracles.
+
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use serde_json::{json, Value};
+
+/// How progress is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ProgressFormat {
+    /// A line per phase, redrawn in place
+    Bar,
+    /// A JSON object per update, one per line
+    Json,
+}
+
+impl ProgressFormat {
+    /// Time between two updates of a phase.
+    fn interval(self) -> Duration {
+        match self {
+            ProgressFormat::Bar => Duration::from_millis(100),
+            ProgressFormat::Json => Duration::from_secs(1),
+        }
+    }
+}
+
+/// Width of the bar, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// One phase of a run, counting the `unit`s it is done with out of `total`
+/// when that is known, and further counters such as the variants written.
+/// Shown on standard error unless `format` is `None`.
+pub struct Progress {
+    format: Option<ProgressFormat>,
+    phase: &'static str,
+    unit: &'static str,
+    total: Option<usize>,
+    done: Cell<usize>,
+    counters: RefCell<Vec<(&'static str, usize)>>,
+    started: Instant,
+    drawn: Cell<Option<Instant>>,
+}
+
+impl Progress {
+    pub fn start(
+        format: Option<ProgressFormat>,
+        phase: &'static str,
+        unit: &'static str,
+        total: Option<usize>,
+    ) -> Progress {
+        let progress = Progress {
+            format,
+            phase,
+            unit,
+            total,
+            done: Cell::new(0),
+            counters: RefCell::new(Vec::new()),
+            started: Instant::now(),
+            drawn: Cell::new(None),
+        };
+        progress.draw(false);
+        progress
+    }
+
+    pub fn advance(&self, n: usize) {
+        self.done.set(self.done.get() + n);
+        self.draw(false);
+    }
+
+    /// Adds `n` to the counter `name`, shown after the units.
+    pub fn count(&self, name: &'static str, n: usize) {
+        let mut counters = self.counters.borrow_mut();
+        match counters.iter_mut().find(|(counter, _)| *counter == name) {
+            Some((_, count)) => *count += n,
+            None => counters.push((name, n)),
+        }
+        drop(counters);
+        self.draw(false);
+    }
+
+    /// Shows the phase a last time, as finished.
+    pub fn finish(self) {
+        self.draw(true);
+    }
+
+    /// The time left, from the pace so far.
+    fn eta(&self) -> Option<Duration> {
+        let (done, total) = (self.done.get(), self.total?);
+        if done == 0 || done >= total {
+            return None;
+        }
+        Some(
+            self.started
+                .elapsed()
+                .mul_f64((total - done) as f64 / done as f64),
+        )
+    }
+
+    fn draw(&self, finished: bool) {
+        let Some(format) = self.format else {
+            return;
+        };
+        let now = Instant::now();
+        if !finished
+            && self
+                .drawn
+                .get()
+                .is_some_and(|at| now - at < format.interval())
+        {
+            return;
+        }
+        self.drawn.set(Some(now));
+        let line = match format {
+            ProgressFormat::Bar => self.bar(finished),
+            ProgressFormat::Json => self.json(finished).to_string() + "\n",
+        };
+        // progress is best effort, a closed standard error stops nothing
+        let _ = io::stderr().write_all(line.as_bytes());
+    }
+
+    fn bar(&self, finished: bool) -> String {
+        let done = self.done.get();
+        let mut line = format!("\r\x1b[2K{}: ", self.phase);
+        match self.total {
+            Some(total) => {
+                let filled = BAR_WIDTH * done / total.max(1);
+                line += &format!(
+                    "[{}{}] {}/{} {}",
+                    "#".repeat(filled.min(BAR_WIDTH)),
+                    " ".repeat(BAR_WIDTH.saturating_sub(filled)),
+                    done,
+                    total,
+                    self.unit
+                );
+            }
+            None => line += &format!("{} {}", done, self.unit),
+        }
+        for (name, count) in self.counters.borrow().iter() {
+            line += &format!(", {} {}", count, name);
+        }
+        if finished {
+            line += &format!(" in {}\n", clock(self.started.elapsed()));
+        } else if let Some(eta) = self.eta() {
+            line += &format!(", ETA {}", clock(eta));
+        }
+        line
+    }
+
+    fn json(&self, finished: bool) -> Value {
+        let mut update = json!({
+            "phase": self.phase,
+            "unit": self.unit,
+            "done": self.done.get(),
+        });
+        if let Some(total) = self.total {
+            update["total"] = total.into();
+        }
+        for (name, count) in self.counters.borrow().iter() {
+            update[*name] = (*count).into();
+        }
+        update["elapsed_ms"] = (self.started.elapsed().as_millis() as u64).into();
+        if let Some(eta) = self.eta() {
+            update["eta_ms"] = (eta.as_millis() as u64).into();
+        }
+        update["finished"] = finished.into();
+        update
+    }
+}
+
+/// `1h02m03s`, `2m03s` or `3s`.
+fn clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m{:02}s", m, s),
+        (h, m, s) => format!("{}h{:02}m{:02}s", h, m, s),
+    }
+}
@@ -0,0 +1,116 @@
+//! `--input-git` and `--input-url`: a corpus fetched before the run into the
+//! system's temporary directory, by the `git`, `curl` and `tar` programs. It
+//! is kept there, so the next runs on the same revision or tarball find it.
+
+use std::{
+    env, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crusher_core::rng;
+
+/// Where fetched corpora go, in the temporary directory.
+pub const REMOTE_DIR: &str = "crusher-inputs";
+
+/// The repository and revision of a `<url>@<rev>`.
+pub fn parse_git_spec(spec: &str) -> Result<(&str, &str), String> {
+    match spec.rsplit_once('@') {
+        // `git@host:path` has no revision
+        Some((url, rev)) if !url.is_empty() && !rev.is_empty() && !rev.contains(':') => {
+            Ok((url, rev))
+        }
+        _ => Err(format!("expected <url>@<rev>, got {:?}", spec)),
+    }
+}
+
+/// The checkout of `rev` of the repository at `url`, fetched unless an
+/// earlier run did.
+pub fn fetch_git(url: &str, rev: &str) -> io::Result<PathBuf> {
+    fetch(&format!("git {}@{}", url, rev), |dir| {
+        run(Command::new("git").arg("init").arg("-q").arg(dir))?;
+        // only the one revision, without its history
+        run(Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["fetch", "-q", "--depth", "1", url, rev]))?;
+        run(Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(["checkout", "-q", "FETCH_HEAD"]))
+    })
+}
+
+/// The tarball at `url` unpacked, downloaded unless an earlier run did. An
+/// archive holding a single directory, as the ones of forges do, is that
+/// directory.
+pub fn fetch_url(url: &str) -> io::Result<PathBuf> {
+    let dir = fetch(&format!("url {}", url), |dir| {
+        let download = dir.with_extension("download");
+        run(Command::new("curl")
+            .args(["-fsSL", "-o"])
+            .arg(&download)
+            .arg(url))?;
+        // tar finds out the compression on its own
+        let unpacked = run(Command::new("tar")
+            .arg("-xf")
+            .arg(&download)
+            .arg("-C")
+            .arg(dir));
+        fs::remove_file(&download)?;
+        unpacked
+    })?;
+    let mut entries = fs::read_dir(&dir)?.collect::<io::Result<Vec<_>>>()?;
+    match entries.pop() {
+        Some(entry) if entries.is_empty() && entry.file_type()?.is_dir() => Ok(entry.path()),
+        _ => Ok(dir),
+    }
+}
+
+/// The directory `source` is fetched into by `fill`. A fetch that fails
+/// halfway leaves nothing the next run would take for done.
+fn fetch(source: &str, fill: impl FnOnce(&Path) -> io::Result<()>) -> io::Result<PathBuf> {
+    let root = env::temp_dir().join(REMOTE_DIR);
+    let dir = root.join(format!("{:016x}", rng::fnv1a(source)));
+    if dir.exists() {
+        return Ok(dir);
+    }
+    let partial = dir.with_extension("partial");
+    if partial.exists() {
+        fs::remove_dir_all(&partial)?;
+    }
+    fs::create_dir_all(&partial)?;
+    if let Err(err) = fill(&partial) {
+        fs::remove_dir_all(&partial)?;
+        return Err(err);
+    }
+    fs::rename(&partial, &dir)?;
+    Ok(dir)
+}
+
+/// Runs `command`, failing with its standard error if it does.
+fn run(command: &mut Command) -> io::Result<()> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            let program = command.get_program().to_string_lossy().into_owned();
+            io::Error::new(err.kind(), format!("cannot run {}: {}", program, err))
+        })?;
+    let mut stderr = String::new();
+    child.stderr.take().unwrap().read_to_string(&mut stderr)?;
+    let status = child.wait()?;
+    if !status.success() {
+        let program = command.get_program().to_string_lossy().into_owned();
+        return Err(io::Error::other(format!(
+            "{} failed ({}): {}",
+            program,
+            status,
+            stderr.trim()
+        )));
+    }
+    Ok(())
+}
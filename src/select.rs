@@ -0,0 +1,180 @@
+//! Which variants a run keeps: the share of `--shard`, duplicates,
+//! `--weight` and the `--max-per-file` cap.
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use clap::ValueEnum;
+
+use crusher_core::{
+    operators::Mutator,
+    rng::{self, Rng},
+};
+
+use crate::{walk::Shard, writer::Variant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ShardBy {
+    /// Every shard crushes its input files
+    File,
+    /// Every shard crushes all files and writes its variants, to share out
+    /// a corpus of a few large files
+    Variant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CapSelection {
+    /// The first ones, in input and operator order
+    First,
+    /// A sample seeded by `--seed`
+    Sample,
+}
+
+/// Only compared within a run, never persisted.
+pub fn text_hash(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// `variants` without those whose `text` equals one of `earlier` or of an
+/// earlier one of them. `seen` files the indices of the texts so far, those
+/// of `earlier` and then those kept, by their `hash`; texts of the same hash
+/// are compared.
+pub fn drop_duplicates<T>(
+    earlier: &[&str],
+    variants: Vec<T>,
+    text: impl Fn(&T) -> &str,
+    seen: &mut HashMap<u64, Vec<usize>>,
+    hash: impl Fn(&str) -> u64,
+) -> Vec<T> {
+    let mut kept: Vec<T> = Vec::new();
+    for variant in variants {
+        let same_hash = seen.entry(hash(text(&variant))).or_default();
+        let duplicate = same_hash.iter().any(|&i| {
+            let other = match i.checked_sub(earlier.len()) {
+                Some(k) => text(&kept[k]),
+                None => earlier[i],
+            };
+            other == text(&variant)
+        });
+        if !duplicate {
+            same_hash.push(earlier.len() + kept.len());
+            kept.push(variant);
+        }
+    }
+    kept
+}
+
+/// Whether the input file at `relative` is one of `shard` when sharding
+/// `by` file.
+pub fn file_in_shard(shard: Option<Shard>, by: ShardBy, relative: &Path) -> bool {
+    match (shard, by) {
+        (Some(shard), ShardBy::File) => shard.contains(&Shard::file_key(relative)),
+        _ => true,
+    }
+}
+
+/// `variants` without those of other shards than `shard` when sharding
+/// `by` variant.
+pub(crate) fn retain_shard_variants(
+    shard: Option<Shard>,
+    by: ShardBy,
+    variants: &mut Vec<Variant>,
+) {
+    if let (Some(shard), ShardBy::Variant) = (shard, by) {
+        variants.retain(|variant| {
+            let relative = Shard::file_key(&variant.relative);
+            shard.contains(&format!(
+                "{}:{}:{}",
+                relative, variant.operator, variant.number
+            ))
+        });
+    }
+}
+
+/// The size of the sample a run holds its variants in, if it does:
+/// `--max-total` with sampled selection is a `--sample` of that size.
+pub fn sample_size(
+    selection: CapSelection,
+    sample: Option<usize>,
+    max_total: Option<usize>,
+) -> Option<usize> {
+    match selection {
+        CapSelection::Sample => sample.into_iter().chain(max_total).min(),
+        CapSelection::First => sample,
+    }
+}
+
+/// `variants` drawn by the `weights` of their operators among `mutators`,
+/// 1 for those without one, the last one counting for those with several.
+pub(crate) fn by_weight(
+    weights: &[(String, f64)],
+    mutators: &[&dyn Mutator],
+    variants: Vec<Variant>,
+    rng: &mut Rng,
+) -> Vec<Variant> {
+    let weight_of = |name: &str| {
+        let listed = weights.iter().rev().find(|(n, _)| n == name);
+        listed.map_or(1.0, |(_, weight)| *weight)
+    };
+    let mut groups: Vec<(f64, Vec<Variant>)> = mutators
+        .iter()
+        .map(|mutator| (weight_of(mutator.name()), Vec::new()))
+        .collect();
+    for variant in variants {
+        let i = mutators
+            .iter()
+            .position(|m| m.name() == variant.operator)
+            .unwrap();
+        groups[i].1.push(variant);
+    }
+    rng::weighted_draw(rng, groups)
+}
+
+/// At most `cap` of `variants`, the first ones or a sample as `selection`
+/// says.
+pub(crate) fn within_cap(
+    selection: CapSelection,
+    cap: usize,
+    mut variants: Vec<Variant>,
+    rng: &mut Rng,
+) -> Vec<Variant> {
+    match selection {
+        CapSelection::First => {
+            variants.truncate(cap);
+            variants
+        }
+        CapSelection::Sample => {
+            let keep = rng::sample_indices(rng, variants.len(), cap);
+            let mut keep = keep.into_iter().peekable();
+            variants
+                .into_iter()
+                .enumerate()
+                .filter_map(|(i, variant)| keep.next_if_eq(&i).map(|_| variant))
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicates_by_text() {
+        let mut seen = HashMap::new();
+        let kept = drop_duplicates(&[], vec!["a", "b", "a"], |t| t, &mut seen, text_hash);
+        assert_eq!(kept, ["a", "b"]);
+        // across operators, `seen` and `earlier` carry over
+        let kept = drop_duplicates(&kept, vec!["b", "c"], |t| t, &mut seen, text_hash);
+        assert_eq!(kept, ["c"]);
+        // texts whose hashes collide are told apart
+        let mut seen = HashMap::new();
+        let kept = drop_duplicates(&[], vec!["a", "b", "b", "c"], |t| t, &mut seen, |_| 0);
+        assert_eq!(kept, ["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,111 @@
+//! The stash of `apply` and `revert`: the bytes of every file `apply`
+//! overwrote with a variant, kept until `revert` puts them back.
+//!
+//! `stash.jsonl` in the stash directory has a line per file, its path, the
+//! id of the variant it holds and the name of its copy next to it.
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use serde_json::{json, Value};
+
+/// Where the stash goes by default, in the current directory.
+pub const STASH_DIR: &str = ".crusher_stash";
+
+const INDEX_FILE_NAME: &str = "stash.jsonl";
+
+/// A file holding a variant.
+pub struct Stashed {
+    /// Absolute, so `revert` may run from elsewhere.
+    pub path: PathBuf,
+    pub id: usize,
+    /// The copy of the original, in the stash directory.
+    backup: String,
+}
+
+impl Stashed {
+    fn to_json(&self) -> Value {
+        json!({
+            "path": self.path.to_string_lossy(),
+            "id": self.id,
+            "backup": self.backup,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Stashed> {
+        Some(Stashed {
+            path: value.get("path")?.as_str()?.into(),
+            id: value.get("id")?.as_u64()? as usize,
+            backup: value.get("backup")?.as_str()?.to_string(),
+        })
+    }
+}
+
+/// The files stashed in `dir`, none if there is no stash.
+pub fn read(dir: &Path) -> io::Result<Vec<Stashed>> {
+    let text = match fs::read_to_string(dir.join(INDEX_FILE_NAME)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            serde_json::from_str::<Value>(line)
+                .ok()
+                .as_ref()
+                .and_then(Stashed::from_json)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed record in {}", INDEX_FILE_NAME),
+                    )
+                })
+        })
+        .collect()
+}
+
+/// The copy of `path` in the stash in `dir`, if it has one.
+pub fn original(dir: &Path, path: &Path) -> io::Result<Option<PathBuf>> {
+    let path = fs::canonicalize(path)?;
+    Ok(read(dir)?
+        .into_iter()
+        .find(|s| s.path == path)
+        .map(|s| dir.join(s.backup)))
+}
+
+/// Copy `path` into the stash in `dir` before variant `id` replaces it. A
+/// file stashed already keeps its copy, the original, and now holds `id`.
+pub fn stash(dir: &Path, path: &Path, id: usize) -> io::Result<()> {
+    let path = fs::canonicalize(path)?;
+    let mut stashed = read(dir)?;
+    match stashed.iter_mut().find(|s| s.path == path) {
+        Some(earlier) => earlier.id = id,
+        None => {
+            fs::create_dir_all(dir)?;
+            let backup = format!("{}.orig", stashed.len());
+            fs::copy(&path, dir.join(&backup))?;
+            stashed.push(Stashed { path, id, backup });
+        }
+    }
+    let mut index = io::BufWriter::new(fs::File::create(dir.join(INDEX_FILE_NAME))?);
+    for record in &stashed {
+        writeln!(index, "{}", record.to_json())?;
+    }
+    index.flush()
+}
+
+/// Put every file stashed in `dir` back as it was and drop the stash.
+pub fn restore(dir: &Path) -> io::Result<Vec<Stashed>> {
+    let stashed = read(dir)?;
+    for file in &stashed {
+        fs::copy(dir.join(&file.backup), &file.path)?;
+    }
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(stashed)
+}
@@ -0,0 +1,187 @@
+//! `crusher_state.jsonl`: the progress of a run, kept in its output directory
+//! until the manifest is written, so that `--resume` can continue it.
+//!
+//! The first line fingerprints the flags of the run. Every input file adds a
+//! line once its variants are written, and every checked variant one with its
+//! verdict. A line cut short by the interruption is ignored.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use crusher_oracle::rustc::Verdict;
+use serde_json::{json, Value};
+
+use crate::manifest::ManifestEntry;
+
+pub const STATE_FILE_NAME: &str = "crusher_state.jsonl";
+
+/// One input file whose variants are all written.
+pub struct FileDone {
+    pub source: String,
+    pub entries: Vec<ManifestEntry>,
+    /// Why the file was skipped, if it was.
+    pub skipped: Option<String>,
+    pub unparseable: usize,
+    pub dropped_equivalent: usize,
+    pub duplicates: usize,
+}
+
+impl FileDone {
+    fn to_json(&self) -> Value {
+        let entries: Vec<Value> = self.entries.iter().map(ManifestEntry::to_json).collect();
+        let mut record = json!({
+            "done": self.source,
+            "entries": entries,
+            "unparseable": self.unparseable,
+            "dropped_equivalent": self.dropped_equivalent,
+            "duplicates": self.duplicates,
+        });
+        if let Some(reason) = &self.skipped {
+            record["skipped"] = reason.as_str().into();
+        }
+        record
+    }
+
+    fn from_json(value: &Value) -> Option<FileDone> {
+        Some(FileDone {
+            source: value.get("done")?.as_str()?.to_string(),
+            entries: value
+                .get("entries")?
+                .as_array()?
+                .iter()
+                .map(ManifestEntry::from_json)
+                .collect::<Option<_>>()?,
+            skipped: value
+                .get("skipped")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            unparseable: value.get("unparseable")?.as_u64()? as usize,
+            dropped_equivalent: value.get("dropped_equivalent")?.as_u64()? as usize,
+            duplicates: value.get("duplicates")?.as_u64()? as usize,
+        })
+    }
+}
+
+pub(crate) fn verdict_to_json(id: usize, verdict: &Verdict) -> Value {
+    let mut record = json!({ "checked": id, "verdict": verdict.label() });
+    match verdict {
+        Verdict::Error(codes) => record["codes"] = codes.clone().into(),
+        Verdict::Ice(stderr) => record["stderr"] = stderr.as_str().into(),
+        Verdict::Crash(signal) => record["signal"] = (*signal).into(),
+        _ => {}
+    }
+    record
+}
+
+pub(crate) fn verdict_from_json(value: &Value) -> Option<(usize, Verdict)> {
+    let id = value.get("checked")?.as_u64()? as usize;
+    let verdict = match value.get("verdict")?.as_str()? {
+        "compiles" => Verdict::Compiles,
+        "error" => Verdict::Error(
+            value
+                .get("codes")?
+                .as_array()?
+                .iter()
+                .map(|code| code.as_str().map(str::to_string))
+                .collect::<Option<_>>()?,
+        ),
+        "ice" => Verdict::Ice(value.get("stderr")?.as_str()?.to_string()),
+        "timeout" => Verdict::Timeout,
+        "oom" => Verdict::OutOfMemory,
+        "crash" => Verdict::Crash(value.get("signal")?.as_u64()? as i32),
+        _ => return None,
+    };
+    Some((id, verdict))
+}
+
+/// What an interrupted run got done.
+pub struct Progress {
+    /// The flags it ran with.
+    pub fingerprint: String,
+    /// In the order they were finished.
+    pub files: Vec<FileDone>,
+    pub verdicts: HashMap<usize, Verdict>,
+}
+
+/// The progress recorded in `dir`, `None` when there is no state file.
+pub fn read_state(dir: &Path) -> io::Result<Option<Progress>> {
+    let text = match fs::read_to_string(dir.join(STATE_FILE_NAME)) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    let mut lines = text.lines();
+    let fingerprint = lines
+        .next()
+        .and_then(|line| serde_json::from_str::<Value>(line).ok())
+        .and_then(|header| Some(header.get("state")?.as_str()?.to_string()))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} has no header", STATE_FILE_NAME),
+            )
+        })?;
+    let mut progress = Progress {
+        fingerprint,
+        files: Vec::new(),
+        verdicts: HashMap::new(),
+    };
+    for line in lines {
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if let Some(done) = FileDone::from_json(&record) {
+            progress.files.push(done);
+        } else if let Some((id, verdict)) = verdict_from_json(&record) {
+            progress.verdicts.insert(id, verdict);
+        }
+    }
+    Ok(Some(progress))
+}
+
+/// The state file of a run being written, every record flushed as it comes.
+pub struct StateFile {
+    file: File,
+}
+
+impl StateFile {
+    /// Start the state file of a run in `dir`, or continue the one there
+    /// under `resume`.
+    pub fn open(dir: &Path, fingerprint: &str, resume: bool) -> io::Result<StateFile> {
+        let path = dir.join(STATE_FILE_NAME);
+        if resume && path.exists() {
+            let mut file = fs::OpenOptions::new().append(true).open(path)?;
+            // a line cut short by the interruption ends here
+            file.write_all(b"\n")?;
+            return Ok(StateFile { file });
+        }
+        let mut file = File::create(path)?;
+        writeln!(file, "{}", json!({ "state": fingerprint }))?;
+        Ok(StateFile { file })
+    }
+
+    pub fn file_done(&mut self, done: &FileDone) -> io::Result<()> {
+        self.record(done.to_json())
+    }
+
+    pub fn checked(&mut self, id: usize, verdict: &Verdict) -> io::Result<()> {
+        self.record(verdict_to_json(id, verdict))
+    }
+
+    fn record(&mut self, record: Value) -> io::Result<()> {
+        writeln!(self.file, "{}", record)?;
+        self.file.flush()
+    }
+}
+
+/// Drop the state file of the finished run in `dir`.
+pub fn remove(dir: &Path) -> io::Result<()> {
+    match fs::remove_file(dir.join(STATE_FILE_NAME)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,109 @@
+//! `--stats`: what a run would work on, counted without writing a mutant:
+//! the targeted nodes and the mutants of every operator, per file and in
+//! all, and the time parsing takes, to budget a campaign.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use serde_json::{json, Map, Value};
+use tree_sitter::Tree;
+
+#[derive(Debug, Clone, Default)]
+pub struct FileStats {
+    /// The input file, or the number of files for the total.
+    pub source: String,
+    pub bytes: usize,
+    pub parse_time: Duration,
+    /// Nodes of every kind one of the operators targets.
+    pub nodes: BTreeMap<&'static str, usize>,
+    /// Mutants every operator would write, in the order of the operators.
+    pub mutants: Vec<(&'static str, usize)>,
+}
+
+impl FileStats {
+    /// The totals of `files`.
+    pub fn total(files: &[FileStats]) -> FileStats {
+        let mut total = FileStats {
+            source: match files.len() {
+                1 => "1 file".to_string(),
+                n => format!("{} files", n),
+            },
+            ..FileStats::default()
+        };
+        for file in files {
+            total.bytes += file.bytes;
+            total.parse_time += file.parse_time;
+            for (kind, count) in &file.nodes {
+                *total.nodes.entry(kind).or_default() += count;
+            }
+            for &(operator, count) in &file.mutants {
+                match total.mutants.iter_mut().find(|(o, _)| *o == operator) {
+                    Some((_, total)) => *total += count,
+                    None => total.mutants.push((operator, count)),
+                }
+            }
+        }
+        total
+    }
+
+    pub fn to_text(&self) -> String {
+        let list = |pairs: Vec<(&str, usize)>| match pairs.is_empty() {
+            true => "none".to_string(),
+            false => pairs
+                .iter()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        let mutants: usize = self.mutants.iter().map(|(_, count)| count).sum();
+        format!(
+            "{}: {} bytes, parsed in {:?}\n  nodes: {}\n  mutants: {} ({} in all)\n",
+            self.source,
+            self.bytes,
+            self.parse_time,
+            list(self.nodes.iter().map(|(k, c)| (*k, *c)).collect()),
+            list(self.mutants.clone()),
+            mutants
+        )
+    }
+
+    pub fn to_json(&self) -> Value {
+        let counts = |pairs: Vec<(&str, usize)>| {
+            let counts: Map<String, Value> = pairs
+                .into_iter()
+                .map(|(name, count)| (name.to_string(), count.into()))
+                .collect();
+            counts
+        };
+        json!({
+            "source": self.source,
+            "bytes": self.bytes,
+            "parse_us": self.parse_time.as_micros() as u64,
+            "nodes": counts(self.nodes.iter().map(|(k, c)| (*k, *c)).collect()),
+            "mutants": counts(self.mutants.clone()),
+        })
+    }
+}
+
+/// How many nodes of each of `kinds` `tree` has.
+pub fn count_nodes(tree: &Tree, kinds: &[&'static str]) -> BTreeMap<&'static str, usize> {
+    let mut counts = BTreeMap::new();
+    let mut cursor = tree.walk();
+    loop {
+        let kind = cursor.node().kind();
+        if let Some(&kind) = kinds.iter().find(|&&k| k == kind) {
+            *counts.entry(kind).or_default() += 1;
+        }
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+        // up until there is a next sibling, done at the root
+        loop {
+            if !cursor.goto_parent() {
+                return counts;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
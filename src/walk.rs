@@ -0,0 +1,279 @@
+//! The files `--input-dir` stands for: every `.rs` file below it that the
+//! `--include`/`--exclude` globs and, with `--respect-gitignore`, git let through;
+//! and the part of them, or of their variants, a `--shard` takes.
+//! Also where their variants go when two differ in case only.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    process::{Command, Stdio},
+    str::FromStr,
+    thread,
+};
+
+use crusher_core::rng::Rng;
+use walkdir::WalkDir;
+
+/// A path pattern: `*` and `?` match within one path component, a `**`
+/// component any number of them. A pattern without `/` is tried on every
+/// component (`target`, `*.generated.rs`), one with `/` on the path relative
+/// to the input directory (`tests/ui/parser`, `src/**/mod.rs`). Either way a
+/// match on a directory covers everything below it.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    components: Vec<String>,
+}
+
+impl FromStr for Glob {
+    type Err = String;
+
+    fn from_str(pattern: &str) -> Result<Glob, String> {
+        let components: Vec<String> = pattern
+            .split('/')
+            .filter(|c| !c.is_empty() && *c != ".")
+            .map(str::to_string)
+            .collect();
+        if components.is_empty() {
+            return Err(format!("{:?} matches no path", pattern));
+        }
+        Ok(Glob { components })
+    }
+}
+
+impl Glob {
+    /// Whether the glob matches `relative` or one of its ancestors.
+    pub fn matches(&self, relative: &Path) -> bool {
+        let path: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(c) => Some(c.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect();
+        if let [pattern] = self.components.as_slice() {
+            if pattern != "**" {
+                return path
+                    .iter()
+                    .any(|c| wildcard(pattern.as_bytes(), c.as_bytes()));
+            }
+        }
+        (1..=path.len()).any(|n| components_match(&self.components, &path[..n]))
+    }
+}
+
+fn components_match(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| components_match(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((c, path)) => {
+                wildcard(first.as_bytes(), c.as_bytes()) && components_match(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+/// `*` and `?` matching of one path component.
+fn wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| wildcard(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && wildcard(rest, &text[1..]),
+        Some((&b, rest)) => text.first() == Some(&b) && wildcard(rest, &text[1..]),
+    }
+}
+
+/// `--shard <i>/<n>`: the `i`th of `n` disjoint parts of the work, from 1.
+/// What goes into which part only depends on a name of the piece of work,
+/// so every machine of a campaign agrees on it without talking to the others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shard {
+    pub index: u64,
+    pub count: u64,
+}
+
+impl FromStr for Shard {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Shard, String> {
+        let parsed = spec
+            .split_once('/')
+            .and_then(|(i, n)| Some((i.trim().parse().ok()?, n.trim().parse().ok()?)));
+        match parsed {
+            Some((index, count)) if (1..=count).contains(&index) => Ok(Shard { index, count }),
+            _ => Err(format!("expected <i>/<n> with 1 <= i <= n, got {:?}", spec)),
+        }
+    }
+}
+
+impl Shard {
+    /// Whether the work named `key` is in this part.
+    pub fn contains(&self, key: &str) -> bool {
+        // similar names hash to much the same low bits before mixing
+        Rng::from_text(key).next_u64() % self.count == self.index - 1
+    }
+
+    /// The key of a file, its path relative to the input directory with `/`
+    /// between components, the same wherever the corpus is checked out.
+    pub fn file_key(relative: &Path) -> String {
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// `--include` and `--exclude`.
+#[derive(Debug, Clone, Default)]
+pub struct PathFilter {
+    /// When not empty, only paths matching one of these are walked.
+    pub include: Vec<Glob>,
+    pub exclude: Vec<Glob>,
+}
+
+impl PathFilter {
+    fn excludes(&self, relative: &Path) -> bool {
+        self.exclude.iter().any(|glob| glob.matches(relative))
+    }
+
+    fn includes(&self, relative: &Path) -> bool {
+        self.include.is_empty() || self.include.iter().any(|glob| glob.matches(relative))
+    }
+}
+
+/// Every `.rs` file below `input_dir` that `filter` lets through, or the
+/// path and error for the entries that cannot be read: unreadable
+/// directories, broken symlinks. Excluded directories are not entered, nor
+/// symlinked ones unless `follow_symlinks`; a symlink back to a directory
+/// above is then an error, and a file reached twice by different links is
+/// only listed the first time.
+pub fn rust_files(
+    input_dir: &Path,
+    filter: &PathFilter,
+    follow_symlinks: bool,
+) -> Vec<Result<PathBuf, (String, String)>> {
+    let mut seen = HashSet::new();
+    // sorted, so that which of the links to a file counts does not depend
+    // on the file system
+    WalkDir::new(input_dir)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            let relative = entry.path().strip_prefix(input_dir).unwrap_or(entry.path());
+            !filter.excludes(relative)
+        })
+        .filter_map(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    let path = err.path().unwrap_or(input_dir).display().to_string();
+                    return Some(Err((path, err.to_string())));
+                }
+            };
+            let path = entry.into_path();
+            if path.extension().is_none_or(|ext| ext != "rs") {
+                return None;
+            }
+            if !filter.includes(path.strip_prefix(input_dir).unwrap()) {
+                return None;
+            }
+            // follows symlinks, unlike the walk by default
+            match fs::metadata(&path) {
+                Ok(metadata) if !metadata.is_file() => None,
+                Ok(_) if follow_symlinks => match fs::canonicalize(&path) {
+                    Ok(target) => seen.insert(target).then_some(Ok(path)),
+                    Err(err) => Some(Err((path.display().to_string(), err.to_string()))),
+                },
+                Ok(_) => Some(Ok(path)),
+                Err(err) => Some(Err((path.display().to_string(), err.to_string()))),
+            }
+        })
+        .collect()
+}
+
+/// Drop the files git ignores from `files`, asking `git check-ignore` from
+/// `input_dir` so that every `.gitignore`, `.git/info/exclude` and the global
+/// excludes file apply. Fails outside of a git work tree.
+pub fn drop_gitignored(
+    input_dir: &Path,
+    files: &mut Vec<Result<PathBuf, (String, String)>>,
+) -> io::Result<()> {
+    let mut input = Vec::new();
+    for path in files.iter().filter_map(|file| file.as_ref().ok()) {
+        let relative = path.strip_prefix(input_dir).unwrap();
+        input.extend_from_slice(relative.to_string_lossy().as_bytes());
+        input.push(0);
+    }
+    if input.is_empty() {
+        return Ok(());
+    }
+    let mut git = Command::new("git")
+        .arg("-C")
+        .arg(input_dir)
+        .args(["check-ignore", "--stdin", "-z"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // git answers while reading, so feed it from another thread lest both
+    // sides block on a full pipe
+    let mut stdin = git.stdin.take().unwrap();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let output = git.wait_with_output()?;
+    writer.join().unwrap()?;
+    // 1 means nothing is ignored
+    if !matches!(output.status.code(), Some(0 | 1)) {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    let ignored: HashSet<PathBuf> = output
+        .stdout
+        .split(|&b| b == 0)
+        .filter(|path| !path.is_empty())
+        .map(|path| PathBuf::from(String::from_utf8_lossy(path).as_ref()))
+        .collect();
+    files.retain(|file| {
+        file.as_ref().map_or(true, |path| {
+            !ignored.contains(path.strip_prefix(input_dir).unwrap())
+        })
+    });
+    Ok(())
+}
+
+/// Output paths for the files of `relatives` whose paths only differ in
+/// case from one sorting before them: `foo.rs` next to `Foo.rs` becomes
+/// `foo~1.rs`, wherever the walk finds them. The output mirrors the input paths, and a corpus checked out
+/// on Linux may hold both, which a case-insensitive file system (the default
+/// on macOS and Windows) would write over each other.
+pub fn case_safe_paths<'a>(
+    relatives: impl IntoIterator<Item = &'a Path>,
+) -> HashMap<PathBuf, PathBuf> {
+    let folded = |path: &Path| path.to_string_lossy().to_lowercase();
+    let mut relatives: Vec<&Path> = relatives.into_iter().collect();
+    relatives.sort();
+    let mut taken: HashSet<String> = relatives.iter().map(|path| folded(path)).collect();
+    let mut seen = HashSet::new();
+    let mut renamed = HashMap::new();
+    for relative in relatives {
+        if seen.insert(folded(relative)) {
+            continue;
+        }
+        let stem = relative.file_stem().unwrap().to_string_lossy();
+        let extension = relative.extension().unwrap().to_string_lossy();
+        let path = (1..)
+            .map(|n| relative.with_file_name(format!("{}~{}.{}", stem, n, extension)))
+            .find(|path| !taken.contains(&folded(path)))
+            .unwrap();
+        taken.insert(folded(&path));
+        renamed.insert(relative.to_path_buf(), path);
+    }
+    renamed
+}
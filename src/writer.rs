@@ -0,0 +1,362 @@
+//! Writing the variants of a run as they come: files in the output
+//! directory or an archive, a JSONL stream, or the `--dry-run` listing.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use serde_json::json;
+
+use crusher_oracle::compiletest::Directives;
+
+use clap::ValueEnum;
+
+use crate::{
+    archive::Archive,
+    driver::RustcArgs,
+    manifest::{ManifestEntry, MutationRecord},
+};
+
+/// Where `--emit jsonl` writes, in the output directory.
+pub const STREAM_FILE_NAME: &str = "variants.jsonl";
+
+/// Output subdirectory for unparseable variants under `--require-parse-ok bucket`.
+pub const PARSE_ERROR_DIR: &str = "parse_errors";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// `foo.mutant_<operator>_<n>.rs`, the whole mutated file
+    Full,
+    /// `foo.mutant_<operator>_<n>.diff`, to apply with `patch -p1` in the input directory
+    Diff,
+    /// One JSON object per variant in `variants.jsonl`, or on standard output with `-o -`
+    Jsonl,
+}
+
+impl Emit {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Emit::Full | Emit::Jsonl => "rs",
+            Emit::Diff => "diff",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DryRun {
+    /// A tab-separated table
+    Text,
+    /// One JSON object per variant
+    Json,
+}
+
+/// One generated file, with where it came from.
+#[derive(Clone)]
+pub(crate) struct Variant {
+    pub(crate) source: String,
+    /// Path of the source below the input directory, mirrored in the output.
+    pub(crate) relative: PathBuf,
+    pub(crate) operator: &'static str,
+    /// Index among the variants `operator` made of `source`.
+    pub(crate) number: usize,
+    /// Kept despite not parsing, goes to [`PARSE_ERROR_DIR`].
+    pub(crate) parse_error: bool,
+    pub(crate) mutations: Vec<MutationRecord>,
+    pub(crate) text: String,
+    /// Pre-order number and name of what the first mutation is aimed at,
+    /// for `--dry-run`.
+    pub(crate) target: Option<(usize, Option<String>)>,
+    /// The `--switch-editions` edition the variant is compiled with.
+    pub(crate) edition: Option<String>,
+    /// The `--compiletest` directives of the source, shared by its variants.
+    pub(crate) directives: Arc<Directives>,
+}
+
+/// Writes variants as they are generated and keeps their manifest entries;
+/// the text of a variant is dropped once it is on disk.
+pub(crate) struct VariantWriter<'a> {
+    pub(crate) output_dir: &'a Path,
+    pub(crate) rustc: &'a RustcArgs,
+    /// Whether files of an earlier run may be replaced (`--overwrite`, `--append`).
+    pub(crate) replace: bool,
+    /// First free number for every file and operator under `--append`.
+    pub(crate) offsets: Option<HashMap<(PathBuf, &'static str), usize>>,
+    pub(crate) written: HashSet<PathBuf>,
+    pub(crate) next_id: usize,
+    pub(crate) entries: Vec<ManifestEntry>,
+    /// List the variants instead of writing them.
+    pub(crate) dry_run: Option<DryRun>,
+    pub(crate) naming: Naming,
+    /// Where the variants go under `--output-format` other than `dir`.
+    pub(crate) archive: Option<Archive>,
+    /// Where the records go under `--emit jsonl`.
+    pub(crate) stream: Option<Box<dyn Write>>,
+    pub(crate) with_text: bool,
+    /// Whether `--emit-baseline` writes the input files.
+    pub(crate) baseline: bool,
+    /// How many more variants `--max-total` lets through.
+    pub(crate) remaining: Option<usize>,
+    /// Variants, or whole files, dropped for `--max-total`.
+    pub(crate) capped: usize,
+}
+
+impl VariantWriter<'_> {
+    /// Takes the variants of an earlier run as written, those of a resumed
+    /// run or of an unchanged file under `--incremental`.
+    pub(crate) fn keep(&mut self, entries: impl IntoIterator<Item = ManifestEntry>) {
+        for entry in entries {
+            self.written.insert(PathBuf::from(&entry.file));
+            self.next_id = self.next_id.max(entry.id + 1);
+            if let Some(remaining) = &mut self.remaining {
+                *remaining = remaining.saturating_sub(1);
+            }
+            self.entries.push(entry);
+        }
+    }
+
+    /// Whether `--max-total` lets no more variants through.
+    pub(crate) fn is_full(&self) -> bool {
+        self.remaining == Some(0)
+    }
+
+    pub(crate) fn write(&mut self, mut variant: Variant) -> Result<()> {
+        match &mut self.remaining {
+            Some(0) => {
+                self.capped += 1;
+                return Ok(());
+            }
+            Some(remaining) => *remaining -= 1,
+            None => {}
+        }
+        if let Some(offsets) = &mut self.offsets {
+            // continue the numbering of every file and operator after what is there
+            let key = (variant.relative.clone(), variant.operator);
+            let output_dir = self.output_dir;
+            let naming = self.naming;
+            let offset = *offsets.entry(key).or_insert_with(|| {
+                (0..)
+                    .find(|&n| {
+                        [false, true].into_iter().all(|parse_error| {
+                            let path =
+                                naming.path(&variant.relative, variant.operator, n, parse_error);
+                            !output_dir.join(path).exists()
+                        })
+                    })
+                    .unwrap()
+            });
+            variant.number += offset;
+        }
+        let path = self.naming.path(
+            &variant.relative,
+            variant.operator,
+            variant.number,
+            variant.parse_error,
+        );
+        if !self.written.insert(path.clone()) {
+            bail!("Two variants would be written to {:?}", path);
+        }
+        let file_name = path.to_string_lossy().into_owned();
+        let file_path = self.output_dir.join(&file_name);
+        if let Some(format) = self.dry_run {
+            print_planned(format, &variant, &file_name);
+            self.next_id += 1;
+            return Ok(());
+        }
+        if let Some(stream) = &mut self.stream {
+            let mutations: Vec<serde_json::Value> = variant
+                .mutations
+                .iter()
+                .map(MutationRecord::to_json)
+                .collect();
+            let mut record = json!({
+                "id": self.next_id,
+                "source": variant.source,
+                "operator": variant.operator,
+                "mutations": mutations,
+            });
+            if let Some(edition) = &variant.edition {
+                record["edition"] = edition.as_str().into();
+            }
+            if self.with_text {
+                record["text"] = variant.text.as_str().into();
+            }
+            writeln!(stream, "{}", record).context("Cannot write a JSONL record")?;
+            self.next_id += 1;
+            return Ok(());
+        }
+        if !self.replace && self.archive.is_none() && file_path.exists() {
+            bail!(
+                "{:?} already holds an earlier run ({:?} exists), pass --overwrite or --append",
+                self.output_dir,
+                file_path
+            );
+        }
+        match &mut self.archive {
+            Some(archive) => archive
+                .add(&file_name, variant.text.as_bytes())
+                .with_context(|| format!("Cannot add {:?} to the archive", file_name))?,
+            None => fs::create_dir_all(file_path.parent().unwrap())
+                .and_then(|()| fs::write(&file_path, &variant.text))
+                .with_context(|| format!("Cannot write {:?}", file_path))?,
+        }
+        self.entries.push(ManifestEntry {
+            id: self.next_id,
+            command: self.rustc.command_for(
+                &file_name,
+                variant.edition.as_deref(),
+                &variant.directives,
+            ),
+            file: file_name,
+            source: variant.source,
+            operator: variant.operator.to_string(),
+            mutations: variant.mutations,
+        });
+        self.next_id += 1;
+        Ok(())
+    }
+
+    /// Writes the input file at `relative` unchanged, under `--emit-baseline`.
+    pub(crate) fn write_baseline(&mut self, relative: &Path, source_code: &str) -> Result<()> {
+        if !self.baseline || self.dry_run.is_some() {
+            return Ok(());
+        }
+        let path = baseline_path(relative);
+        if !self.written.insert(path.clone()) {
+            bail!("A baseline and another file would be written to {:?}", path);
+        }
+        let file_name = path.to_string_lossy();
+        let file_path = self.output_dir.join(&path);
+        match &mut self.archive {
+            Some(archive) => archive
+                .add(&file_name, source_code.as_bytes())
+                .with_context(|| format!("Cannot add {:?} to the archive", file_name)),
+            None => fs::create_dir_all(file_path.parent().unwrap())
+                .and_then(|()| fs::write(&file_path, source_code))
+                .with_context(|| format!("Cannot write {:?}", file_path)),
+        }
+    }
+}
+
+/// One line of the `--dry-run` listing.
+fn print_planned(format: DryRun, variant: &Variant, file_name: &str) {
+    let (node_id, name) = variant.target.clone().unwrap();
+    match format {
+        DryRun::Text => {
+            let spans: Vec<String> = variant
+                .mutations
+                .iter()
+                .map(|m| format!("{}..{}", m.start, m.end))
+                .collect();
+            println!(
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                variant.source,
+                variant.operator,
+                spans.join(","),
+                node_id,
+                name.as_deref().unwrap_or("-"),
+                file_name
+            );
+        }
+        DryRun::Json => {
+            let spans: Vec<[usize; 2]> =
+                variant.mutations.iter().map(|m| [m.start, m.end]).collect();
+            let mut planned = json!({
+                "source": variant.source,
+                "operator": variant.operator,
+                "spans": spans,
+                "node_id": node_id,
+                "file": file_name,
+            });
+            if let Some(name) = &name {
+                planned["name"] = name.as_str().into();
+            }
+            println!("{}", planned);
+        }
+    }
+}
+
+/// How the files of variants are named.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Naming {
+    pub(crate) emit: Emit,
+    /// `--per-operator-dirs`
+    pub(crate) per_operator: bool,
+}
+
+impl Naming {
+    /// `dir/foo.rs` becomes `dir/foo.mutant_<operator>_<n>.rs`, or
+    /// `<operator>/dir/<operator>_foo_<n>.rs` under `--per-operator-dirs`.
+    /// Variants that do not parse go to [`PARSE_ERROR_DIR`], in the directory
+    /// of the operator if there is one.
+    fn path(self, relative: &Path, operator: &str, number: usize, parse_error: bool) -> PathBuf {
+        let stem = relative.file_stem().unwrap().to_string_lossy();
+        let extension = self.emit.extension();
+        let path = match self.per_operator {
+            true => {
+                relative.with_file_name(format!("{}_{}_{}.{}", operator, stem, number, extension))
+            }
+            false => relative.with_file_name(format!(
+                "{}.mutant_{}_{}.{}",
+                stem, operator, number, extension
+            )),
+        };
+        let path = match parse_error {
+            true => Path::new(PARSE_ERROR_DIR).join(path),
+            false => path,
+        };
+        match self.per_operator {
+            true => Path::new(operator).join(path),
+            false => path,
+        }
+    }
+
+    /// The [`baseline_path`] of the input file `entry` is a variant of.
+    fn baseline_of(self, entry: &ManifestEntry) -> Option<PathBuf> {
+        let path = Path::new(&entry.file);
+        let path = match self.per_operator {
+            true => path.strip_prefix(&entry.operator).ok()?,
+            false => path,
+        };
+        let path = path.strip_prefix(PARSE_ERROR_DIR).unwrap_or(path);
+        let name = path.file_name()?.to_string_lossy();
+        let stem = match self.per_operator {
+            true => {
+                let name = name.strip_prefix(&format!("{}_", entry.operator))?;
+                &name[..name.rfind('_')?]
+            }
+            false => &name[..name.rfind(&format!(".mutant_{}_", entry.operator))?],
+        };
+        Some(path.with_file_name(format!("{}.baseline.rs", stem)))
+    }
+}
+
+/// `dir/foo.rs` becomes `dir/foo.baseline.rs`.
+fn baseline_path(relative: &Path) -> PathBuf {
+    let stem = relative.file_stem().unwrap().to_string_lossy();
+    relative.with_file_name(format!("{}.baseline.rs", stem))
+}
+
+/// Delete the variants of `entries` that are still there.
+pub(crate) fn remove_variants(
+    output_dir: &Path,
+    entries: &[ManifestEntry],
+    naming: Naming,
+) -> Result<()> {
+    let baselines = entries.iter().filter_map(|entry| naming.baseline_of(entry));
+    let files = entries.iter().map(|entry| PathBuf::from(&entry.file));
+    for file in files.chain(baselines) {
+        let path = output_dir.join(file);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                return Err(err).with_context(|| format!("Cannot delete {:?}", path))
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}